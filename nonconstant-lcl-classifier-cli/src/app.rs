@@ -11,6 +11,7 @@ pub fn build_cli() -> App<'static, 'static> {
     let subcommand_create_cache = get_subcommand_create_sql_cache();
     let subcommand_import_problems_from_lcl_classifier_db =
         get_subcommand_import_problems_from_lcl_classifier_db();
+    let subcommand_verify = get_subcommand_verify();
 
     App::new("Nonconstant LCL classifier")
         .version("0.4.1")
@@ -19,7 +20,8 @@ pub fn build_cli() -> App<'static, 'static> {
             subcommand_find,
             subcommand_generate,
             subcommand_create_cache,
-            subcommand_import_problems_from_lcl_classifier_db
+            subcommand_import_problems_from_lcl_classifier_db,
+            subcommand_verify,
         ])
         .about("This tool can be used to find nonconstant lower bounds for LCL-problems in the LOCAL model")
         .long_about(indoc! {"
@@ -88,17 +90,158 @@ fn get_subcommand_find() -> App<'static, 'static> {
         .long("svg-dir")
         .takes_value(true);
 
+    let proof_dir = Arg::with_name("proof_dir")
+        .help("Output DRAT proofs of counterexamples to the given directory")
+        .long_help(indoc! {"
+            Output DRAT proofs of counterexamples to the given directory.
+
+            For each counterexample, the SAT instance is written as a CNF DIMACS file (.cnf),
+            the proof of its unsatisfiability as a DRAT file (.drat) and the graph as a dot file (.dot).
+            Proofs are produced with the kissat executable, which has to be in PATH,
+            and they are checked before they are written.
+            "})
+        .long("proof-dir")
+        .takes_value(true);
+
+    let certificate_dir = Arg::with_name("certificate_dir")
+        .help("Output a certificate of each proven problem to the given directory")
+        .long_help(indoc! {"
+            Output a certificate of each proven problem to the given directory.
+
+            For each problem with a counterexample, the problem and its smallest counterexample graph
+            with explicit port numbers are written as a JSON file named by the index of the problem
+            in the input (<index>.json).
+            Certificates can be checked with the verify subcommand.
+            "})
+        .long("certificate-dir")
+        .takes_value(true);
+
+    let certificate_proof = Arg::with_name("certificate_proof")
+        .help("Include DRAT proofs in certificates")
+        .long_help(indoc! {"
+            Include DRAT proofs in certificates.
+
+            Proofs are produced with the kissat executable, which has to be in PATH.
+            "})
+        .long("certificate-proof")
+        .requires("certificate_dir");
+
+    let solver = Arg::with_name("solver")
+        .help("SAT solver that is used: kissat (default), varisat or external")
+        .long_help(indoc! {"
+            SAT solver that is used: kissat (default), varisat or external.
+
+            Varisat is available if the tool is built with the varisat feature.
+            The external solver is an executable given with --solver-command,
+            which reads CNF DIMACS from stdin and writes its result to stdout in the SAT competition format.
+            The placeholders {conflicts} and {propagations} in the command are replaced
+            with the limits of --max-conflicts and --max-propagations.
+            "})
+        .takes_value(true)
+        .value_name("solver")
+        .long("solver");
+
+    let solver_command = Arg::with_name("solver_command")
+        .help("Command line of the external SAT solver, e.g. \"cadical -q\"")
+        .takes_value(true)
+        .value_name("command")
+        .long("solver-command")
+        .required_if("solver", "external");
+
+    let max_conflicts = Arg::with_name("max_conflicts")
+        .help("Limits the count of conflicts of each SAT instance")
+        .long_help(indoc! {"
+            Limits the count of conflicts of each SAT instance.
+
+            Kissat supports this limit by running the kissat executable, which has to be in PATH.
+            External solvers support it with the placeholder {conflicts}, see --solver.
+            Graphs on which the limit is reached are not counterexamples,
+            and they are listed in the stats and with --write-unknown.
+            "})
+        .takes_value(true)
+        .value_name("count")
+        .long("max-conflicts");
+
+    let max_propagations = Arg::with_name("max_propagations")
+        .help("Limits the count of propagations of each SAT instance")
+        .long_help(indoc! {"
+            Limits the count of propagations of each SAT instance.
+
+            Only external solvers support this limit with the placeholder {propagations}, see --solver.
+            Graphs on which the limit is reached are not counterexamples,
+            and they are listed in the stats and with --write-unknown.
+            "})
+        .takes_value(true)
+        .value_name("count")
+        .long("max-propagations");
+
+    let timeout = Arg::with_name("timeout")
+        .help("Limits the time of each SAT instance in seconds")
+        .long_help(indoc! {"
+            Limits the time of each SAT instance in seconds.
+
+            Kissat is run as the kissat executable, which has to be in PATH, and it is killed at the limit,
+            as are external solvers.
+            Varisat cannot be stopped, so it keeps running in the background after the limit.
+            Graphs on which the limit is reached are not counterexamples,
+            and they are listed in the stats and with --write-unknown.
+            "})
+        .takes_value(true)
+        .value_name("seconds")
+        .long("timeout");
+
+    let encoding = Arg::with_name("encoding")
+        .help("SAT encoding that is used: permutation (default), forbidden-tuple or counting")
+        .long_help(indoc! {"
+            SAT encoding that is used: permutation (default), forbidden-tuple or counting.
+
+            permutation has a variable for each permutation of a configuration on each node.
+            forbidden-tuple has only variables for the labels of edges,
+            and forbids each tuple of labels around a node that is not an allowed configuration.
+            counting has a variable for each configuration on each node,
+            which requires counts of the labels around the node.
+            "})
+        .takes_value(true)
+        .value_name("encoding")
+        .long("encoding");
+
+    let incremental = Arg::with_name("incremental")
+        .help("Encodes each graph once and solves the problems incrementally")
+        .long_help(indoc! {"
+            Encodes each graph once and solves the problems incrementally.
+
+            Each graph is encoded with a selector variable for each configuration into a Varisat solver,
+            and the problems are solved under assumptions that disable the configurations they do not allow.
+            Requires the binary to be built with the varisat feature.
+            Limits, --solver and --encoding are not used with this option.
+            "})
+        .long("incremental")
+        .conflicts_with_all(&["encoding", "max_conflicts", "max_propagations", "timeout"]);
+
     let print_stats = Arg::with_name("print_stats")
         .long("stats")
         .help("Prints different stats of results after finding them");
 
     let write_nonproven_results = Arg::with_name("write_nonproven_result")
-        .help("Path where nonproven results will be written")
+        .help("Path where nonproven results will be written. Constant problems with a 0-round witness are left out")
         .takes_value(true)
         .value_name("path_to_nonproven_results")
         .short("o")
         .long("write-nonproven");
 
+    let write_unknown_results = Arg::with_name("write_unknown_results")
+        .help("Path where graphs of nonproven problems on which the SAT solver reached a limit will be written")
+        .long_help(indoc! {"
+            Path where graphs of nonproven problems on which the SAT solver reached a limit will be written.
+
+            Each line is of form
+                <problem>; n=<node count>; G=<graph index>
+            The problems are also in the nonproven results, so they can be retried with other limits.
+            "})
+        .takes_value(true)
+        .value_name("path_to_unknown_results")
+        .long("write-unknown");
+
     let sqlite_cache = Arg::with_name("sqlite_cache")
         .help("Path to an sqlite database that will be used as a cache")
         .long_help(indoc! {"
@@ -133,13 +276,60 @@ fn get_subcommand_find() -> App<'static, 'static> {
             all_graphs,
             all_graph_sizes,
             output_svg,
+            proof_dir,
+            certificate_dir,
+            certificate_proof,
+            solver,
+            solver_command,
+            max_conflicts,
+            max_propagations,
+            timeout,
+            encoding,
+            incremental,
             print_stats,
             sqlite_cache,
             write_nonproven_results,
+            write_unknown_results,
         ])
         .subcommands([subcommand_single, subcommand_class, subcommand_file])
 }
 
+fn get_arg_general() -> Arg<'static, 'static> {
+    Arg::with_name("general")
+        .short("g")
+        .long("general")
+        .help("Reads problems as node and edge configurations of regular graphs")
+        .long_help(indoc! {"
+            Reads problems as node and edge configurations of regular graphs.
+
+            The active configurations are the node configurations,
+            and the passive configurations are the edge configurations of 2 labels.
+            For example proper 3-coloring of 3-regular graphs:
+                AAA BBB CCC; AB AC BC
+            Each edge of a counterexample graph is a passive node of degree 2.
+            With --svg-dir, counterexample graphs are saved with edges instead of passive nodes.
+        "})
+}
+
+fn get_arg_oriented() -> Arg<'static, 'static> {
+    Arg::with_name("oriented")
+        .long("oriented")
+        .help("Reads problems as oriented problems of directed or rooted trees")
+        .long_help(indoc! {"
+            Reads problems as oriented problems of directed or rooted trees.
+
+            Each configuration is of form
+                <outgoing labels>:<incoming labels>
+            For example, as in the output of fetch_problems --directed:
+                A:BB; A:B B:A
+            A counterexample is a graph with an orientation of its edges on which the problem is unsolvable.
+            Only orientations where each node has a count of outgoing edges of some of its configurations are tried.
+            Limits, incremental solving, other encodings, svg files, proofs and certificates
+            are not supported for oriented problems.
+        "})
+        .conflicts_with("general")
+}
+
 fn get_subcommand_class() -> App<'static, 'static> {
     let active_degree = Arg::with_name("active_degree")
         .help("Degree of the active partition")
@@ -178,9 +368,40 @@ fn get_subcommand_single() -> App<'static, 'static> {
         .min_values(1)
         .max_values(1)
         .required(true);
+    let labeling_dir = Arg::with_name("labeling_dir")
+        .help("Output a labeling of each graph on which the problem can be solved to the given directory")
+        .long_help(indoc! {"
+            Output a labeling of each graph on which the problem can be solved to the given directory.
+
+            For each generated graph on which the problem can be solved, the labeling found by the SAT solver
+            is written as a text file (.txt) with the permutation of each node and the labels of each edge,
+            and as a dot file (.dot) with the labels on the nodes and edges.
+            The files are named by the node count and the index of the graph (n=<n>; G=<index>).
+            "})
+        .long("labeling-dir")
+        .takes_value(true)
+        .conflicts_with("oriented");
     SubCommand::with_name("single")
         .about("Runs for a single problem")
-        .args(&[active_configurations, passive_configurations])
+        .long_about(indoc! {"
+            Runs for a single problem.
+
+            Configurations are separated with whitespace, e.g. -A \"MUU PPP\" -P \"MM PU UU\".
+            The condensed notation of the Round eliminator is supported, e.g. -A \"A[BC]^2 C^3\".
+            Labels with longer names are written in parentheses, e.g. -A \"(in)(out)^2\".
+            The names are kept in the output.
+            Alternatively each configuration can be given on its own line,
+            in which case whitespace inside a configuration is ignored.
+            In the condensed notation, whitespace separates configurations only where they are equally long,
+            so -A \"A [BC]^2 C\" is a single configuration and -A \"A B^3\" is the configuration ABBB.
+            "})
+        .args(&[
+            active_configurations,
+            passive_configurations,
+            labeling_dir,
+            get_arg_general(),
+            get_arg_oriented(),
+        ])
 }
 
 fn get_subcommand_import_problems_from_lcl_classifier_db() -> App<'static, 'static> {
@@ -211,7 +432,7 @@ fn get_subcommand_import_problems_from_lcl_classifier_db() -> App<'static, 'stat
         .takes_value(true)
         .min_values(2)
         .max_values(2)
-        .value_names(&["reminder", "modulus"]);
+        .value_names(&["remainder", "modulus"]);
     let purge = Arg::with_name("purge")
         .short("p")
         .long("purge")
@@ -220,6 +441,20 @@ fn get_subcommand_import_problems_from_lcl_classifier_db() -> App<'static, 'stat
         .short("n")
         .long("normalize")
         .help("Normalizes problems");
+    let directed = Arg::with_name("directed")
+        .short("d")
+        .long("directed")
+        .help("Fetches problems of directed or rooted trees")
+        .long_help(indoc! {"
+            Fetches problems of directed or rooted trees instead of undirected trees.
+
+            Problems are output as oriented problems, where each configuration is of form
+                <outgoing labels>:<incoming labels>
+            For example:
+                A:BB; A:B B:A
+            They can be searched for counterexamples with find from_stdin --oriented.
+        "})
+        .conflicts_with_all(&["purge", "normalize"]);
     SubCommand::with_name("fetch_problems")
         .about("Fetch problems from LCL-classifier's database")
         .long_about(indoc! {"
@@ -234,6 +469,7 @@ fn get_subcommand_import_problems_from_lcl_classifier_db() -> App<'static, 'stat
         .args(&[
             purge,
             normalize,
+            directed,
             active_degree,
             passive_degree,
             label_count,
@@ -253,6 +489,7 @@ fn get_subcommand_from_stdin() -> App<'static, 'static> {
         Read problems from stdin.
 
         Problems have to be from same problem class.
+        Each problem is simplified by removing labels that can be replaced by another label.
 
         By defualt, uses only the problems that have no counter example yet, i.e. <graph_size> is 0.
 
@@ -269,8 +506,15 @@ fn get_subcommand_from_stdin() -> App<'static, 'static> {
             <configuration> ...; <configuration> ...
         For example:
             ABC CCC; AA BB BC
+
+        Configurations can use the condensed notation of the Round eliminator,
+        where [BC] is a group of labels and ^k repeats the previous label or group.
+        Labels with longer names are written in parentheses.
+        For example:
+            A[BC]^2 C^3; [ABC]^2
+            (in)(out)^2; [(in)(out)](out)
     "})
-        .args(&[no_ignore])
+        .args(&[no_ignore, get_arg_general(), get_arg_oriented()])
 }
 
 fn get_subcommand_generate() -> App<'static, 'static> {
@@ -306,10 +550,29 @@ fn get_subcommand_problems() -> App<'static, 'static> {
         .value_name("path")
         .short("c")
         .long("sqlite-cache");
+    let modulo = Arg::with_name("modulo")
+        .help("Only generate a subset of the problems")
+        .long_help(indoc! {"
+        Only generate a subset of the problems.
+
+        The class is split into <modulus> disjoint parts and only part <remainder> is generated.
+        This way the class can be generated on several machines.
+        The cache is not used with this option."})
+        .long("mod")
+        .takes_value(true)
+        .min_values(2)
+        .max_values(2)
+        .value_names(&["remainder", "modulus"]);
 
     SubCommand::with_name("problems")
         .about("Generate LCL problems")
-        .args(&[active_degree, passive_degree, label_count, sqlite_cache])
+        .args(&[
+            active_degree,
+            passive_degree,
+            label_count,
+            sqlite_cache,
+            modulo,
+        ])
 }
 
 fn get_subcommand_graphs() -> App<'static, 'static> {
@@ -348,6 +611,23 @@ fn get_subcommand_graphs() -> App<'static, 'static> {
         ])
 }
 
+fn get_subcommand_verify() -> App<'static, 'static> {
+    let certificates = Arg::with_name("certificates")
+        .help("Paths to certificate files")
+        .required(true)
+        .multiple(true);
+    SubCommand::with_name("verify")
+        .about("Verifies certificates of counterexamples")
+        .long_about(indoc! {"
+        Verifies certificates of counterexamples, as written by find with --certificate-dir.
+
+        Small graphs are verified by trying every labeling.
+        Larger graphs are verified by checking the proof of the certificate if it has one,
+        and otherwise by encoding the problem as a SAT instance and solving it.
+        "})
+        .args(&[certificates])
+}
+
 fn get_subcommand_create_sql_cache() -> App<'static, 'static> {
     let sqlite_cache = Arg::with_name("sqlite_cache")
         .help("Path to a new SQLite database")
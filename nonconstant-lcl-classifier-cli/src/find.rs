@@ -1,18 +1,25 @@
-use crate::from_stdin::from_stdin;
+use crate::from_stdin::{from_stdin, oriented_from_stdin};
 use clap::{value_t_or_exit, ArgMatches};
-use indicatif::{ParallelProgressIterator, ProgressFinish};
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
-use log::info;
 use nonconstant_lcl_classifier_lib::{
     caches::{GraphSqliteCache, LclProblemSqliteCache},
-    save_as_svg, BiregularGraph, DotFormat, LclProblem, SatEncoder, SatResult, SatSolver,
+    sat_encoder::encoding::encoding_from_name,
+    sat_solver::{
+        backend::{backend_from_name, SatBackend},
+        clauses_to_dimacs,
+    },
+    save_as_svg, BiregularGraph, Certificate, Classifier, DotFormat, GeneralLclProblem, LclProblem,
+    OrientedLclProblem, SatEncoder, SatLimits, SatSolver, UnknownGraph,
 };
-use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::*;
 use std::fs::{create_dir_all, File};
 use std::io::{BufWriter, Write};
-use std::{path::PathBuf, str::FromStr, time::Instant};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
     let progress = matches_find.occurrences_of("progress");
@@ -21,17 +28,47 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
 
     let sqlite_cache_path = matches_find.value_of("sqlite_cache");
 
-    let mut graph_cache = sqlite_cache_path.map(|path| {
-        GraphSqliteCache::new(
-            PathBuf::from_str(path)
-                .expect("Database at the given path does not exist")
-                .as_path(),
-        )
-    });
+    // The selected backend is also the default of the whole process, which is used for oriented problems.
+    if let Some(solver) = matches_find.value_of("solver") {
+        let command_line = matches_find.value_of("solver_command");
+        SatSolver::set_backend(backend_from_name(solver, command_line)?);
+    }
 
-    let mut problem_cache = sqlite_cache_path.map(|path| {
-        LclProblemSqliteCache::new(PathBuf::from_str(path).expect("Invalid path").as_path())
-    });
+    let sat_limits = SatLimits {
+        conflicts: parse_optional(matches_find, "max_conflicts")?,
+        propagations: parse_optional(matches_find, "max_propagations")?,
+        time: parse_optional::<f64>(matches_find, "timeout")?.map(Duration::from_secs_f64),
+    };
+
+    let mut classifier = Classifier::new(n_lower, n_upper)
+        .all_graphs(matches_find.is_present("all_graphs"))
+        .all_graph_sizes(matches_find.is_present("all_graph_sizes"))
+        .sat_limits(sat_limits)
+        .backend(SatSolver::get_backend());
+    if matches_find.is_present("incremental") {
+        #[cfg(feature = "varisat")]
+        {
+            classifier = classifier.incremental(true);
+        }
+        #[cfg(not(feature = "varisat"))]
+        return Err("--incremental requires the varisat feature".into());
+    }
+    if let Some(encoding) = matches_find.value_of("encoding") {
+        classifier = classifier.encoding(encoding_from_name(encoding)?);
+    }
+    if let Some(path) = sqlite_cache_path {
+        let path = PathBuf::from_str(path).expect("Invalid path");
+        classifier = classifier
+            .graph_cache(GraphSqliteCache::new(path.as_path()))
+            .problem_cache(LclProblemSqliteCache::new(path.as_path()));
+    }
+
+    if matches!(
+        matches_find.subcommand(),
+        (_, Some(sub_m)) if sub_m.is_present("oriented")
+    ) {
+        return find_oriented(matches_find, &mut classifier);
+    }
 
     let get_progress_bar = |n: u64, progress_level| {
         if progress >= progress_level {
@@ -63,6 +100,12 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
     pb_gen_problems.enable_steady_tick(100);
     pb_gen_problems.set_message("Defining problem(s)");
 
+    // Problems of general graphs are converted into problems with passive nodes of degree 2.
+    let general = matches!(
+        matches_find.subcommand(),
+        (_, Some(sub_m)) if sub_m.is_present("general")
+    );
+
     let now = Instant::now();
     // Read a problem or generate class of problems.
     let problems = match matches_find.subcommand() {
@@ -75,7 +118,11 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
                 .values_of("passive_configurations")
                 .expect("Parsing parameter 'p' failed.")
                 .join("\n");
-            let lcl_problem = LclProblem::new(&a, &p).expect("Parsing the LCL problem failed.");
+            let lcl_problem = if general {
+                GeneralLclProblem::new(&a, &p)?.to_lcl_problem()
+            } else {
+                LclProblem::new(&a, &p)?
+            };
             vec![lcl_problem]
         }
         ("class", Some(sub_m)) => {
@@ -83,18 +130,14 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
             let passive_degree = value_t_or_exit!(sub_m, "passive_degree", usize);
             let label_count = value_t_or_exit!(sub_m, "label_count", usize);
 
-            LclProblem::get_or_generate_normalized(
-                active_degree,
-                passive_degree,
-                label_count as u8,
-                problem_cache.as_mut(),
-            )
+            classifier.get_problem_class(active_degree, passive_degree, label_count as u8)
         }
         ("from_stdin", Some(sub_m)) => {
             let no_ignore_solved = sub_m.is_present("no_ignore");
-            let problems =
-                from_stdin(!no_ignore_solved).expect("Failed to read problems from stdin");
-            assert!(!problems.is_empty(), "No problems were given to stdin",);
+            let problems = from_stdin(!no_ignore_solved, general)?;
+            if problems.is_empty() {
+                return Err("No problems were given to stdin".into());
+            }
             problems
         }
         (_, _) => unreachable!(),
@@ -118,8 +161,6 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
         .passive
         .get_labels_per_configuration();
 
-    let mut graphs = vec![];
-
     let pb_graphs = get_progress_bar(0, 1);
     pb_graphs.set_style(get_spinner());
     pb_graphs.set_message(format!(
@@ -129,11 +170,7 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
     pb_graphs.enable_steady_tick(100);
 
     let now = Instant::now();
-    for n in n_lower..=n_upper {
-        // Get biregular graphs from cache or generate them.
-        let graphs_n = BiregularGraph::get_or_generate(n, deg_a, deg_p, graph_cache.as_mut());
-        graphs.push(graphs_n);
-    }
+    let graphs = classifier.generate_graphs(deg_a, deg_p);
     let time_graphs = now.elapsed().as_secs_f32();
     let graph_count: usize = graphs.iter().map(|x| x.len()).sum();
     pb_graphs.set_length(graph_count as u64);
@@ -143,75 +180,97 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
     ));
 
     let pb_problems = get_progress_bar(problems.len() as u64, 1);
-    pb_problems.set_style(get_progress_style().on_finish(ProgressFinish::WithMessage(
-        std::borrow::Cow::Owned("Finding lower bound proofs done!".to_string()),
-    )));
+    pb_problems.set_style(get_progress_style());
     pb_problems.set_message("Trying to find a lower bound proof for each problem...");
     if progress == 1 {
         pb_problems.enable_steady_tick(100);
     }
-    let now = Instant::now();
-    let results: Vec<(LclProblem, usize)> = problems
-        .par_iter()
-        .progress_with(pb_problems)
-        .flat_map(|problem| {
-            let mut results = vec![];
-
-            'graph_size_loop: for graphs_n in &graphs {
-                // Create SAT encoder iterator.
-                let encoders = graphs_n
-                    .iter()
-                    .enumerate()
-                    .map(|(graph_index, graph)| (graph_index, SatEncoder::new(problem, graph.clone()))); // TODO use immutable reference instead of cloning.
-
-                let mut found = 0;
-
-                // Solve SAT problems.
-                'encoder_loop: for (graph_index, encoder) in encoders {
-                    let result = SatSolver::solve(encoder.encode());
-                    if result == SatResult::Satisfiable {
-                        continue;
-                    }
-
-                    found += 1;
-
-                    let graph = encoder.get_graph();
-
-                    // Save the problem and node count.
-                    results.push((problem.clone(), graph.graph.node_count()));
-
-                    if let Some(path_dir) = matches_find.value_of("output_svg") {
-                        let dot = graph.graph.get_dot();
-                        create_dir_all(path_dir).unwrap();
-                        let mut path_buf = PathBuf::from(path_dir);
-                        let file_name = format!(
-                            "{}; n={}; G={}.svg",
-                            problem.to_string(),
-                            graph.graph.node_count(),
-                            graph_index
-                        );
-                        path_buf.push(file_name);
-                        let path = path_buf.as_path().to_str().unwrap();
-                        save_as_svg(path, &dot).expect("Failed to save graph as svg.");
-                    }
-
-                    if !matches_find.is_present("all_graphs") {
-                        break 'encoder_loop;
-                    }
-                }
-                if found > 0 && !matches_find.is_present("all_graph_sizes") {
-                    break 'graph_size_loop;
-                }
+    let pb = pb_problems.clone();
+    let backend = classifier.get_backend();
+    let classifier = classifier.on_progress(move |_| pb.inc(1));
+
+    let classification = classifier.classify_with_graphs(&problems, &graphs);
+    pb_problems.finish_with_message("Finding lower bound proofs done!");
+    let time_sat = classification.sat_time.as_secs_f32();
+
+    let constant_count = classification
+        .results
+        .iter()
+        .filter(|result| result.zero_round_witness.is_some())
+        .count();
+
+    // Each counterexample is a result, and problems without counterexamples have a node count of 0.
+    let mut results: Vec<(LclProblem, usize)> = vec![];
+    // Graphs of nonproven problems on which the solver reached a limit.
+    let mut unknown_results: Vec<(LclProblem, UnknownGraph)> = vec![];
+    for (index, result) in classification.results.iter().enumerate() {
+        if result.zero_round_witness.is_some() {
+            continue;
+        }
+        if result.counterexamples.is_empty() {
+            results.push((result.problem.clone(), 0));
+            for unknown_graph in &result.unknown_graphs {
+                unknown_results.push((result.problem.clone(), *unknown_graph));
+            }
+        }
+        for counterexample in &result.counterexamples {
+            results.push((result.problem.clone(), counterexample.get_node_count()));
+
+            if let Some(path_dir) = matches_find.value_of("output_svg") {
+                let graph = &counterexample.graph;
+                let dot = match graph.to_general_graph() {
+                    Some(general_graph) if general => general_graph.get_dot(),
+                    _ => graph.graph.get_dot(),
+                };
+                create_dir_all(path_dir).unwrap();
+                let mut path_buf = PathBuf::from(path_dir);
+                let file_name = format!(
+                    "{}; n={}; G={}.svg",
+                    result.problem.to_string(),
+                    counterexample.get_node_count(),
+                    counterexample.graph_index
+                );
+                path_buf.push(file_name);
+                let path = path_buf.as_path().to_str().unwrap();
+                save_as_svg(path, &dot).expect("Failed to save graph as svg.");
             }
 
-            if results.is_empty() {
-                results.push((problem.clone(), 0));
+            if let Some(path_dir) = matches_find.value_of("proof_dir") {
+                let name = format!(
+                    "{}; n={}; G={}",
+                    result.problem.to_string(),
+                    counterexample.get_node_count(),
+                    counterexample.graph_index
+                );
+                write_proof(path_dir, &name, &result.problem, &counterexample.graph)?;
             }
+        }
 
-            results
-        })
-        .collect();
-    let time_sat = now.elapsed().as_secs_f32();
+        if let (Some(path_dir), Some(counterexample)) = (
+            matches_find.value_of("certificate_dir"),
+            result.counterexamples.first(),
+        ) {
+            write_certificate(
+                path_dir,
+                index,
+                &result.problem,
+                &counterexample.graph,
+                matches_find.is_present("certificate_proof"),
+            )?;
+        }
+    }
+
+    if let ("single", Some(sub_m)) = matches_find.subcommand() {
+        if let Some(path_dir) = sub_m.value_of("labeling_dir") {
+            write_labelings(
+                path_dir,
+                &problems[0],
+                &graphs,
+                &sat_limits,
+                backend.as_ref(),
+            )?;
+        }
+    }
 
     let (nonproven_results, proven_results): (_, Vec<_>) =
         results.into_iter().partition(|(_, n)| *n == 0);
@@ -229,6 +288,15 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
         }
     }
 
+    if let Some(path) = matches_find.value_of("write_unknown_results") {
+        let f = File::create(path).expect("Unable to create file");
+        let mut f = BufWriter::new(f);
+        for (p, unknown_graph) in &unknown_results {
+            f.write_all(format!("{}\n", unknown_result_to_string(p, unknown_graph)).as_bytes())
+                .expect("Unable to write data");
+        }
+    }
+
     if matches_find.is_present("print_stats") {
         let new_uniques_len = if matches_find.is_present("all") {
             // This is needed to show the real unique result problem count.
@@ -253,11 +321,35 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
             time_problems + time_graphs + time_sat,
         );
 
+        eprintln!(
+            "Solved {} SAT instances and inferred {} results from other problems",
+            classification.sat_count, classification.inferred_count,
+        );
+        eprintln!(
+            "SAT instances had {} variables and {} clauses in total",
+            classification.encoding_statistics.variable_count,
+            classification.encoding_statistics.clause_count,
+        );
+
         eprintln!(
             "Found new lower bounds for {}/{} problems",
             new_uniques_len,
             nonproven_results.len() + new_uniques_len
         );
+        eprintln!(
+            "Skipped {} problems that are constant (0-round witness)",
+            constant_count
+        );
+        if classification.unknown_count > 0 {
+            eprintln!(
+                "SAT solver reached a limit on {} instances, of which {} are graphs of nonproven problems:",
+                classification.unknown_count,
+                unknown_results.len()
+            );
+            for (problem, unknown_graph) in &unknown_results {
+                eprintln!("{}", unknown_result_to_string(problem, unknown_graph));
+            }
+        }
 
         let sizes = proven_results
             .iter()
@@ -274,3 +366,217 @@ pub fn find(matches_find: &ArgMatches) -> Result<(), Box<dyn std::error::Error>>
 
     Ok(())
 }
+
+/// Finds counterexamples of oriented problems, see [`OrientedLclProblem::find_unsolvable_orientation`].
+///
+/// Problems are given with the subcommand `single` or `from_stdin`, and they should have the same degrees.
+/// Each problem with a counterexample is printed as `<node count>: <problem>` with the smallest node count,
+/// and the other problems are written to the nonproven results as `0: <problem>`.
+fn find_oriented(
+    matches_find: &ArgMatches,
+    classifier: &mut Classifier,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let unsupported = [
+        ("max_conflicts", "--max-conflicts"),
+        ("max_propagations", "--max-propagations"),
+        ("timeout", "--timeout"),
+        ("incremental", "--incremental"),
+        ("encoding", "--encoding"),
+        ("output_svg", "--svg-dir"),
+        ("proof_dir", "--proof-dir"),
+        ("certificate_dir", "--certificate-dir"),
+    ];
+    for (name, flag) in unsupported.iter() {
+        if matches_find.is_present(name) {
+            return Err(format!("{} is not supported for oriented problems", flag).into());
+        }
+    }
+
+    let problems = match matches_find.subcommand() {
+        ("single", Some(sub_m)) => {
+            let a = sub_m
+                .values_of("active_configurations")
+                .expect("Parsing parameter 'a' failed.")
+                .join("\n");
+            let p = sub_m
+                .values_of("passive_configurations")
+                .expect("Parsing parameter 'p' failed.")
+                .join("\n");
+            vec![OrientedLclProblem::new(&a, &p)?]
+        }
+        ("from_stdin", Some(sub_m)) => {
+            let problems = oriented_from_stdin(!sub_m.is_present("no_ignore"))?;
+            if problems.is_empty() {
+                return Err("No problems were given to stdin".into());
+            }
+            problems
+        }
+        (_, _) => unreachable!(),
+    };
+
+    let graphs = classifier.generate_graphs(
+        problems[0].active.get_labels_per_configuration(),
+        problems[0].passive.get_labels_per_configuration(),
+    );
+    let results = problems
+        .par_iter()
+        .map(|problem| {
+            let active_out_degrees = problem.get_active_out_degrees();
+            let passive_out_degrees = problem.get_passive_out_degrees();
+            graphs
+                .iter()
+                .flatten()
+                .find(|graph| {
+                    problem
+                        .find_unsolvable_orientation(
+                            graph,
+                            &active_out_degrees,
+                            &passive_out_degrees,
+                        )
+                        .is_some()
+                })
+                .map_or(0, |graph| graph.graph.node_count())
+        })
+        .collect::<Vec<_>>();
+
+    for (problem, n) in problems.iter().zip(&results) {
+        if *n > 0 {
+            println!("{}: {}", n, problem);
+        }
+    }
+
+    if let Some(path) = matches_find.value_of("write_nonproven_result") {
+        let mut f = BufWriter::new(File::create(path)?);
+        for (problem, _) in problems.iter().zip(&results).filter(|(_, n)| **n == 0) {
+            writeln!(f, "0: {}", problem)?;
+        }
+    }
+
+    if matches_find.is_present("print_stats") {
+        eprintln!(
+            "Found new lower bounds for {}/{} problems",
+            results.iter().filter(|n| **n > 0).count(),
+            problems.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Writes the SAT instance of `problem` on `graph`, a DRAT proof of its unsatisfiability and the graph.
+///
+/// The files are written to the directory `path_dir` as `<name>.cnf`, `<name>.drat` and `<name>.dot`.
+/// The proof is checked before it is written.
+fn write_proof(
+    path_dir: &str,
+    name: &str,
+    problem: &LclProblem,
+    graph: &BiregularGraph,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let clauses = SatEncoder::new(problem, graph.clone()).encode();
+    let proof = SatSolver::prove_unsatisfiable(&clauses)?
+        .ok_or_else(|| format!("Counterexample of {} is solvable", name))?;
+    proof.check(&clauses)?;
+
+    create_dir_all(path_dir)?;
+    let path = PathBuf::from(path_dir);
+    std::fs::write(
+        path.join(format!("{}.cnf", name)),
+        clauses_to_dimacs(&clauses),
+    )?;
+    std::fs::write(path.join(format!("{}.drat", name)), proof.to_string())?;
+    std::fs::write(path.join(format!("{}.dot", name)), graph.graph.get_dot())?;
+    Ok(())
+}
+
+/// Writes a certificate of `problem` on the counterexample `graph` to the directory `path_dir` as `<index>.json`.
+///
+/// The file is named by the index of the problem, as problems can be longer than a file name.
+/// If `with_proof` is true, the certificate includes a DRAT proof, which is checked before it is written.
+fn write_certificate(
+    path_dir: &str,
+    index: usize,
+    problem: &LclProblem,
+    graph: &BiregularGraph,
+    with_proof: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let proof = if with_proof {
+        let clauses = SatEncoder::new(problem, graph.clone()).encode();
+        let proof = SatSolver::prove_unsatisfiable(&clauses)?
+            .ok_or_else(|| format!("Counterexample of {} is solvable", problem.to_string()))?;
+        proof.check(&clauses)?;
+        Some(proof)
+    } else {
+        None
+    };
+    let certificate = Certificate::new(problem, graph, proof.as_ref());
+
+    create_dir_all(path_dir)?;
+    let path = PathBuf::from(path_dir).join(format!("{}.json", index));
+    std::fs::write(path, certificate.to_json())?;
+    Ok(())
+}
+
+/// Writes a labeling of `problem` on each graph of `graphs` on which it can be solved to the directory `path_dir`.
+///
+/// The labeling on a graph of `n` nodes with the index `index` among the graphs of its size
+/// is written as `n=<n>; G=<index>.txt` and `n=<n>; G=<index>.dot`.
+/// Graphs on which the solver reaches a limit are skipped.
+fn write_labelings(
+    path_dir: &str,
+    problem: &LclProblem,
+    graphs: &[Vec<BiregularGraph>],
+    sat_limits: &SatLimits,
+    backend: &dyn SatBackend,
+) -> Result<(), Box<dyn std::error::Error>> {
+    create_dir_all(path_dir)?;
+    let path = PathBuf::from(path_dir);
+    let label_name = |label| problem.get_label_name(label);
+    for graphs in graphs {
+        for (index, graph) in graphs.iter().enumerate() {
+            let encoder = SatEncoder::new(problem, graph.clone());
+            let result = SatSolver::solve_with_backend(encoder.encode(), sat_limits, backend);
+            let model = match result.get_model() {
+                Some(model) => model,
+                None => continue,
+            };
+            let labeling = encoder.decode(model);
+
+            let name = format!("n={}; G={}", graph.graph.node_count(), index);
+            std::fs::write(
+                path.join(format!("{}.txt", name)),
+                format!("{}\n", labeling.to_string_with_names(label_name)),
+            )?;
+            std::fs::write(
+                path.join(format!("{}.dot", name)),
+                labeling.get_dot(encoder.get_graph(), label_name),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the value of the argument `name` parsed, or `None` if it is not given.
+fn parse_optional<T: FromStr>(
+    matches: &ArgMatches,
+    name: &str,
+) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    match matches.value_of(name) {
+        Some(value) => match value.parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => Err(format!("Invalid value '{}' for {}", value, name).into()),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Returns a problem and a graph on which the SAT solver reached a limit,
+/// in the format `<problem>; n=<node count>; G=<graph index>`.
+fn unknown_result_to_string(problem: &LclProblem, unknown_graph: &UnknownGraph) -> String {
+    format!(
+        "{}; n={}; G={}",
+        problem.to_string(),
+        unknown_graph.node_count,
+        unknown_graph.graph_index
+    )
+}
@@ -2,7 +2,7 @@ use clap::{value_t_or_exit, values_t, ArgMatches};
 use itertools::Itertools;
 use nonconstant_lcl_classifier_lib::{
     lcl_problem::{Normalizable, Purgeable},
-    LclProblem,
+    LclProblem, OrientedLclProblem,
 };
 use postgres_types::{FromSql, ToSql};
 
@@ -32,6 +32,14 @@ pub fn fetch_and_print_problems(sub_m: &ArgMatches) -> Result<(), Box<dyn std::e
 
     let modulo = modulo.map(|v| (v[0], v[1]));
 
+    if sub_m.is_present("directed") {
+        let problems =
+            fetch_oriented_problems(db_path, active_degree, passive_degree, label_count, modulo)?;
+        eprintln!("Fetched {} problems", problems.len());
+        problems.iter().for_each(|p| println!("0: {}", p));
+        return Ok(());
+    }
+
     let mut problems = fetch_problems(db_path, active_degree, passive_degree, label_count, modulo)
         .unwrap_or_else(|_| {
             panic!(
@@ -79,6 +87,74 @@ pub fn fetch_problems(
     label_count: i16,
     modulo: Option<(u16, u16)>,
 ) -> Result<Vec<LclProblem>, Box<dyn std::error::Error>> {
+    let constraints = fetch_constraints(
+        database_path,
+        active_degree,
+        passive_degree,
+        label_count,
+        modulo,
+        false,
+    )?;
+
+    let mut problems = Vec::with_capacity(constraints.len());
+    for (active_constraints, passive_constraints) in constraints {
+        let active_configuration = active_constraints.join(" ");
+        let passive_configuration = passive_constraints.join(" ");
+        problems.push(LclProblem::new(
+            active_configuration.as_str(),
+            passive_configuration.as_str(),
+        )?);
+    }
+
+    Ok(problems)
+}
+
+/// Fetches all problems of directed or rooted trees with constant lower bound and non-constant upper-bound
+///
+/// Same as [`fetch_problems`] but for the problems of directed or rooted trees.
+/// The LCL-classifier writes the label of the outgoing edge (towards the parent in rooted trees)
+/// as the first label of a constraint, followed by the labels of the incoming edges.
+pub fn fetch_oriented_problems(
+    database_path: &str,
+    active_degree: i16,
+    passive_degree: i16,
+    label_count: i16,
+    modulo: Option<(u16, u16)>,
+) -> Result<Vec<OrientedLclProblem>, Box<dyn std::error::Error>> {
+    let constraints = fetch_constraints(
+        database_path,
+        active_degree,
+        passive_degree,
+        label_count,
+        modulo,
+        true,
+    )?;
+
+    let mut problems = Vec::with_capacity(constraints.len());
+    for (active_constraints, passive_constraints) in constraints {
+        let active_configuration = oriented_configuration_string(&active_constraints);
+        let passive_configuration = oriented_configuration_string(&passive_constraints);
+        problems.push(OrientedLclProblem::new(
+            active_configuration.as_str(),
+            passive_configuration.as_str(),
+        )?);
+    }
+
+    Ok(problems)
+}
+
+/// Active and passive constraints of a problem in the format of the LCL-classifier.
+type Constraints = (Vec<String>, Vec<String>);
+
+/// Fetches the active and passive constraints of the problems with constant lower bound and non-constant upper-bound.
+fn fetch_constraints(
+    database_path: &str,
+    active_degree: i16,
+    passive_degree: i16,
+    label_count: i16,
+    modulo: Option<(u16, u16)>,
+    directed_or_rooted: bool,
+) -> Result<Vec<Constraints>, Box<dyn std::error::Error>> {
     use postgres::{Client, NoTls};
     let mut client = Client::connect(database_path, NoTls)?;
 
@@ -97,7 +173,7 @@ pub fn fetch_problems(
     FROM problems
     WHERE
         is_tree = TRUE AND
-        is_directed_or_rooted = FALSE AND
+        is_directed_or_rooted = $8 AND
         det_lower_bound = $1 AND
         det_upper_bound != $2 AND
         active_degree = $3 AND
@@ -115,10 +191,11 @@ pub fn fetch_problems(
             &label_count,
             &(modulus as i32),
             &(remainder as i32),
+            &directed_or_rooted,
         ],
     )?;
 
-    let mut problems = Vec::with_capacity(query.len());
+    let mut constraints = Vec::with_capacity(query.len());
 
     for row in query {
         let _id: i32 = row.get(0);
@@ -127,19 +204,24 @@ pub fn fetch_problems(
         let _label_count: i16 = row.get(3);
         let active_constraints: Vec<String> = row.get(4);
         let passive_constraints: Vec<String> = row.get(5);
-
-        let active_configuration = active_constraints.join(" ");
-        let passive_configuration = passive_constraints.join(" ");
-        problems.push(
-            LclProblem::new(
-                active_configuration.as_str(),
-                passive_configuration.as_str(),
-            )
-            .expect("Could not parse an LCL problem from LCL classifier's database"),
-        );
+        constraints.push((active_constraints, passive_constraints));
     }
 
-    Ok(problems)
+    Ok(constraints)
+}
+
+/// Converts constraints of directed or rooted problems into the format of [`OrientedLclProblem::new`].
+///
+/// The first label of each constraint is on the outgoing edge and the rest are on the incoming edges.
+fn oriented_configuration_string(constraints: &[String]) -> String {
+    constraints
+        .iter()
+        .map(|constraint| {
+            let mut labels = constraint.chars();
+            let outgoing = labels.next().map(String::from).unwrap_or_default();
+            format!("{}:{}", outgoing, labels.as_str())
+        })
+        .join(" ")
 }
 
 fn _configuration_string_from_lcl_classifier_format(encoding: &[String]) -> String {
@@ -148,8 +230,17 @@ fn _configuration_string_from_lcl_classifier_format(encoding: &[String]) -> Stri
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use postgres::{Client, NoTls};
 
+    #[test]
+    fn test_oriented_configuration_string() {
+        let constraints = ["ABB".to_string(), "BAA".to_string()];
+        let configuration = oriented_configuration_string(&constraints);
+        assert_eq!(configuration, "A:BB B:AA");
+        assert!(OrientedLclProblem::new(&configuration, "A:B").is_ok());
+    }
+
     #[test]
     #[ignore = "Should be ran manually as db is not quaranteed"]
     fn test_db_connection() -> Result<(), Box<dyn std::error::Error>> {
@@ -1,11 +1,17 @@
-use itertools::Itertools;
-use nonconstant_lcl_classifier_lib::LclProblem;
+use nonconstant_lcl_classifier_lib::{GeneralLclProblem, LclProblem, OrientedLclProblem};
 use std::io::{self, BufRead};
 
 /// Read LCL problems from stdin.
 ///
 /// Stream is expected to have one problem per line, each problem ending in newline.
 /// Ignores all problems that have a positive `n`.
+/// Empty lines are skipped.
+/// Each problem is simplified with [`LclProblem::simplify`], which also renumbers its labels to `0..k`.
+/// If `general` is true, problems are read as node and edge configurations
+/// with [`GeneralLclProblem::new`] and converted into bipartite problems.
+///
+/// Returns an error instead of panicking if a line cannot be read or parsed.
+/// Errors in the problem itself are returned as [`nonconstant_lcl_classifier_lib::LclParseError`].
 ///
 /// Problem format:
 /// <n>: <active_configuration>; <passive_configuration>
@@ -15,33 +21,63 @@ use std::io::{self, BufRead};
 /// 2: AA AB BC CC; AC BB
 /// 0: AA AB AC BB CC; AA AB AC BB BC CC
 /// ```
-pub fn from_stdin(ignore_solved: bool) -> Result<Vec<LclProblem>, Box<dyn std::error::Error>> {
+pub fn from_stdin(
+    ignore_solved: bool,
+    general: bool,
+) -> Result<Vec<LclProblem>, Box<dyn std::error::Error>> {
+    read_problems(ignore_solved, |active, passive| {
+        let mut problem = if general {
+            GeneralLclProblem::new(active, passive)?.to_lcl_problem()
+        } else {
+            LclProblem::new(active, passive)?
+        };
+        problem.simplify();
+        Ok(problem)
+    })
+}
+
+/// Read oriented LCL problems from stdin.
+///
+/// Lines are in the same format as in [`from_stdin`],
+/// and configurations are in the format of [`OrientedLclProblem::new`],
+/// e.g. the output of `fetch_problems --directed`.
+/// Problems are not simplified.
+pub fn oriented_from_stdin(
+    ignore_solved: bool,
+) -> Result<Vec<OrientedLclProblem>, Box<dyn std::error::Error>> {
+    read_problems(ignore_solved, |active, passive| {
+        Ok(OrientedLclProblem::new(active, passive)?)
+    })
+}
+
+/// Reads lines of form `<n>: <active_configuration>; <passive_configuration>` from stdin
+/// and parses their configurations with `parse`.
+fn read_problems<T>(
+    ignore_solved: bool,
+    parse: impl Fn(&str, &str) -> Result<T, Box<dyn std::error::Error>>,
+) -> Result<Vec<T>, Box<dyn std::error::Error>> {
     let stdin = io::stdin();
-    let lines = stdin.lock().lines();
-
-    Ok(lines
-        .filter_map(|line_res| {
-            let line = line_res.expect("Could not read line");
-
-            let (n_str, problem_str) = line
-                .split(':')
-                .map(|x| x.trim())
-                .collect_tuple()
-                .expect("Line was not in correct format");
-            let n: usize = n_str.parse().expect("Graph size was not an integer");
-
-            if ignore_solved && n > 0 {
-                return None;
-            }
-
-            let (active, passive) = problem_str
-                .split(';')
-                .map(|x| x.trim())
-                .collect_tuple()
-                .expect("Problem was not in correct format");
-            let problem =
-                LclProblem::new(active, passive).expect("Could not parse the LCL problem");
-            Some(problem)
-        })
-        .collect_vec())
+    let mut problems = vec![];
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (n_str, problem_str) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Line '{}' is not of form '<n>: <problem>'", line))?;
+        let n: usize = n_str.trim().parse()?;
+
+        if ignore_solved && n > 0 {
+            continue;
+        }
+
+        // A missing separator leaves the passive partition empty, which the parser reports.
+        let (active, passive) = problem_str.split_once(';').unwrap_or((problem_str, ""));
+        problems.push(parse(active.trim(), passive.trim())?);
+    }
+
+    Ok(problems)
 }
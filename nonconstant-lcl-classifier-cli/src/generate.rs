@@ -1,5 +1,5 @@
-use clap::value_t_or_exit;
 use clap::ArgMatches;
+use clap::{value_t_or_exit, values_t_or_exit};
 use nonconstant_lcl_classifier_lib::caches::GraphSqliteCache;
 use nonconstant_lcl_classifier_lib::caches::LclProblemSqliteCache;
 use nonconstant_lcl_classifier_lib::BiregularGraph;
@@ -22,6 +22,33 @@ fn generate_problems(matches_problems: &ArgMatches) -> Result<(), Box<dyn std::e
     let passive_degree = value_t_or_exit!(matches_problems, "passive_degree", usize);
     let label_count = value_t_or_exit!(matches_problems, "label_count", usize);
     let sqlite_cache_path = matches_problems.value_of("sqlite_cache");
+    // Values that are not numbers are errors, instead of generating the whole class.
+    let modulo = if matches_problems.is_present("modulo") {
+        Some(values_t_or_exit!(matches_problems, "modulo", usize))
+    } else {
+        None
+    };
+
+    if let Some(modulo) = modulo {
+        let (remainder, modulus) = (modulo[0], modulo[1]);
+        if modulus == 0 {
+            return Err("Modulus should be positive".into());
+        }
+        if remainder >= modulus {
+            return Err(format!(
+                "Remainder ({}) should be less than modulus ({})",
+                remainder, modulus
+            )
+            .into());
+        }
+        let problems =
+            LclProblem::iter_normalized(active_degree, passive_degree, label_count as u8)
+                .shard(remainder, modulus);
+        for problem in problems {
+            println!("0: {}", problem.to_string());
+        }
+        return Ok(());
+    }
 
     let mut problem_cache = sqlite_cache_path.map(|path| {
         LclProblemSqliteCache::new(
@@ -5,11 +5,13 @@ pub mod from_lcl_classifier;
 pub mod from_stdin;
 pub mod generate;
 pub mod utils;
+pub mod verify;
 
 use crate::create_cache::create_cache;
 use crate::find::find;
 use crate::from_lcl_classifier::fetch_and_print_problems;
 use crate::generate::generate;
+use crate::verify::verify;
 use std::error::Error;
 
 pub fn run_subcommand(matches: clap::ArgMatches) -> Result<(), Box<dyn Error>> {
@@ -18,6 +20,7 @@ pub fn run_subcommand(matches: clap::ArgMatches) -> Result<(), Box<dyn Error>> {
         ("gen", Some(sub_m)) => generate(sub_m)?,
         ("create_cache", Some(sub_m)) => create_cache(sub_m)?,
         ("fetch_problems", Some(sub_m)) => fetch_and_print_problems(sub_m)?,
+        ("verify", Some(sub_m)) => verify(sub_m)?,
         (_, _) => unreachable!(),
     };
     Ok(())
@@ -0,0 +1,40 @@
+use clap::ArgMatches;
+use nonconstant_lcl_classifier_lib::{Certificate, VerificationMethod};
+
+pub fn verify(matches_verify: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let paths = matches_verify
+        .values_of("certificates")
+        .expect("Parsing parameter 'certificates' failed.");
+
+    let mut failed_count = 0;
+    for path in paths {
+        match verify_certificate(path) {
+            Ok(method) => {
+                let method = match method {
+                    VerificationMethod::ExhaustiveSearch => "exhaustive search",
+                    VerificationMethod::Proof => "proof",
+                    VerificationMethod::Solver => "SAT solver",
+                    VerificationMethod::OutdatedProof => {
+                        "SAT solver, as the proof is of another encoding version"
+                    }
+                };
+                println!("{}: verified by {}", path, method);
+            }
+            Err(error) => {
+                println!("{}: failed: {}", path, error);
+                failed_count += 1;
+            }
+        }
+    }
+
+    if failed_count > 0 {
+        return Err(format!("{} certificates failed verification", failed_count).into());
+    }
+    Ok(())
+}
+
+/// Reads the certificate at `path` and verifies it, see [`Certificate::verify`].
+pub fn verify_certificate(path: &str) -> Result<VerificationMethod, Box<dyn std::error::Error>> {
+    let json = std::fs::read_to_string(path)?;
+    Certificate::from_json(&json)?.verify()
+}
@@ -1,5 +1,10 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use nonconstant_lcl_classifier_lib::{caches::LclProblemSqliteCache, Configurations, LclProblem};
+use nonconstant_lcl_classifier_lib::{
+    caches::LclProblemSqliteCache,
+    sat_encoder::at_most_one::get_at_most_one_names,
+    sat_encoder::encoding::{encoding_from_name, get_encoding_names},
+    AtMostOneEncoding, BiregularGraph, Configurations, LclProblem, SatEncoder, SatSolver,
+};
 
 fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("Generate problems");
@@ -23,6 +28,45 @@ fn criterion_benchmark(c: &mut Criterion) {
         b.iter(|| Configurations::generate_powerset(black_box(4), black_box(3)))
     });
     group.finish();
+
+    let problem = LclProblem::new("MUU PPP", "MM PU UU").unwrap();
+    let graphs = BiregularGraph::generate(10, 3, 2);
+    let mut group = c.benchmark_group("Solve with encodings");
+    group.sample_size(10);
+    for name in get_encoding_names() {
+        let encoding = encoding_from_name(name).unwrap();
+        group.bench_function(format!("solve 3 2 n10 {}", name), |b| {
+            b.iter(|| {
+                graphs
+                    .iter()
+                    .filter(|graph| {
+                        SatSolver::solve(encoding.encode(black_box(&problem), graph))
+                            .is_satisfiable()
+                    })
+                    .count()
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("Solve with at-most-one encodings");
+    group.sample_size(10);
+    for name in get_at_most_one_names() {
+        let at_most_one = AtMostOneEncoding::from_name(name).unwrap();
+        group.bench_function(format!("solve 3 2 n10 {}", name), |b| {
+            b.iter(|| {
+                graphs
+                    .iter()
+                    .filter(|graph| {
+                        let encoder = SatEncoder::new(black_box(&problem), (*graph).clone())
+                            .with_at_most_one(at_most_one);
+                        SatSolver::solve(encoder.encode()).is_satisfiable()
+                    })
+                    .count()
+            })
+        });
+    }
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
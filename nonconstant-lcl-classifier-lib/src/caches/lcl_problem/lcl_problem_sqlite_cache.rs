@@ -1,9 +1,22 @@
 use super::LclProblemCacheParams;
 use crate::caches::Cache;
-use crate::LclProblem;
+use crate::{Configurations, LclProblem};
 use rusqlite::{params, Connection, Result};
+use serde::Deserialize;
 use std::path::Path;
 
+/// Marks cached problems that are stored together with their label names.
+///
+/// Older caches store only the configurations and start with the number of problems instead.
+const LABEL_NAMES_FORMAT: u64 = u64::from_be_bytes(*b"LCLNAMES");
+
+/// Problem as it was stored before label names were cached.
+#[derive(Deserialize)]
+struct LegacyLclProblem {
+    active: Configurations,
+    passive: Configurations,
+}
+
 pub struct LclProblemSqliteCache {
     db: Connection,
 }
@@ -34,7 +47,13 @@ impl Cache<LclProblemCacheParams, LclProblem> for LclProblemSqliteCache {
             |row| row.get(0),
         )?;
 
-        let problems: Vec<LclProblem> = bincode::deserialize(&data).unwrap();
+        let problems = match bincode::deserialize::<u64>(&data)? {
+            LABEL_NAMES_FORMAT => bincode::deserialize::<(u64, Vec<LclProblem>)>(&data)?.1,
+            _ => bincode::deserialize::<Vec<LegacyLclProblem>>(&data)?
+                .into_iter()
+                .map(|problem| LclProblem::from_configurations(problem.active, problem.passive))
+                .collect(),
+        };
 
         Ok(problems)
     }
@@ -44,7 +63,7 @@ impl Cache<LclProblemCacheParams, LclProblem> for LclProblemSqliteCache {
         params: LclProblemCacheParams,
         problems: &[LclProblem],
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let data = bincode::serialize(problems)?;
+        let data = bincode::serialize(&(LABEL_NAMES_FORMAT, problems))?;
         self.db.execute(
             "INSERT INTO problem_class (degree_a, degree_p, label_count, data) VALUES (?1, ?2, ?3, ?4)",
             params![params.degree_a, params.degree_p, params.label_count, data],
@@ -56,9 +75,66 @@ impl Cache<LclProblemCacheParams, LclProblem> for LclProblemSqliteCache {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::caches::create_sqlite_cache;
+    use serde::Serialize;
+    use std::fs;
+
+    fn create_test_cache(name: &str) -> Result<LclProblemSqliteCache, Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(name);
+        let _ = fs::remove_file(&path);
+        create_sqlite_cache(path.to_str().unwrap())?;
+        Ok(LclProblemSqliteCache::new(&path))
+    }
 
     #[test]
     fn test_nothing() -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
+
+    #[test]
+    fn test_label_names_are_cached() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cache = create_test_cache("test_label_names_are_cached.db")?;
+        let params = LclProblemCacheParams {
+            degree_a: 3,
+            degree_p: 2,
+            label_count: 3,
+        };
+        let problems = vec![LclProblem::new("MUU PPP", "MM PU UU")?];
+        cache.write(params, &problems)?;
+
+        let cached = cache.read(params)?;
+        assert_eq!(cached, problems);
+        assert_eq!(cached[0].get_label_names(), ["M", "P", "U"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_legacy_format() -> Result<(), Box<dyn std::error::Error>> {
+        #[derive(Serialize)]
+        struct Legacy<'a> {
+            active: &'a Configurations,
+            passive: &'a Configurations,
+        }
+
+        let cache = create_test_cache("test_read_legacy_format.db")?;
+        let problem = LclProblem::new("MUU PPP", "MM PU UU")?;
+        let data = bincode::serialize(&vec![Legacy {
+            active: &problem.active,
+            passive: &problem.passive,
+        }])?;
+        cache.db.execute(
+            "INSERT INTO problem_class (degree_a, degree_p, label_count, data) VALUES (?1, ?2, ?3, ?4)",
+            params![3, 2, 3, data],
+        )?;
+
+        let params = LclProblemCacheParams {
+            degree_a: 3,
+            degree_p: 2,
+            label_count: 3,
+        };
+        let cached = cache.read(params)?;
+        assert_eq!(cached, vec![problem]);
+        assert!(cached[0].get_label_names().is_empty());
+        Ok(())
+    }
 }
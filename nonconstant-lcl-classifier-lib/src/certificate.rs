@@ -0,0 +1,386 @@
+//! Self-contained certificates of counterexamples.
+//!
+//! A certificate is a JSON object of the following form:
+//! ```json
+//! {
+//!   "version": 2,
+//!   "active_configurations": "MUU PPP",
+//!   "passive_configurations": "MM PU UU",
+//!   "active_node_count": 2,
+//!   "passive_node_count": 3,
+//!   "edges": [
+//!     { "active": 0, "active_port": 0, "passive": 0, "passive_port": 0 },
+//!     ...
+//!   ],
+//!   "proof": "1 -2 0\n...\n0\n",
+//!   "encoding_version": 2,
+//!   "cnf": "p cnf 42 100\n...\n"
+//! }
+//! ```
+//! Configurations are in the format of [`LclProblem::new`].
+//! Active and passive nodes are numbered separately from 0.
+//! Each edge connects the given ports of an active and a passive node,
+//! and the ports of each node are numbered from 0 to its degree minus one.
+//! The port order is explicit so that the clauses of the encoder can be reproduced,
+//! see [`SatEncoder::with_ports`], although it does not affect solvability, because configurations are multisets.
+//! The optional proof is a DRAT proof of unsatisfiability of the clauses from [`SatEncoder::encode`],
+//! see [`DratProof`]. The clauses are included in the CNF DIMACS format with the version of the encoding,
+//! see [`ENCODING_VERSION`], so that the proof can be checked with other tools and after the encoding changes.
+//! Certificates of version 1 have no clauses.
+
+use crate::pn_solver::{brute_force::BruteForceSolver, PnSolver};
+use crate::sat_encoder::ENCODING_VERSION;
+use crate::sat_solver::{clauses_to_dimacs, drat::DratProof};
+use crate::{BiregularGraph, LclProblem, SatEncoder, SatResult, SatSolver, UndirectedGraph};
+use itertools::Itertools;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// Version of the certificate format written by [`Certificate::new`].
+pub const CERTIFICATE_VERSION: u32 = 2;
+
+/// Largest count of labelings that [`Certificate::verify`] searches exhaustively.
+const EXHAUSTIVE_SEARCH_LIMIT: f64 = 1e6;
+
+/// Edge between a port of an active node and a port of a passive node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificateEdge {
+    pub active: usize,
+    pub active_port: usize,
+    pub passive: usize,
+    pub passive_port: usize,
+}
+
+/// Certificate that an LCL problem cannot be solved on a multigraph in the PN model.
+///
+/// See the [module documentation](self) for the format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Certificate {
+    pub version: u32,
+    pub active_configurations: String,
+    pub passive_configurations: String,
+    pub active_node_count: usize,
+    pub passive_node_count: usize,
+    pub edges: Vec<CertificateEdge>,
+    pub proof: Option<String>,
+    /// Version of the encoding of the clauses of the proof, see [`ENCODING_VERSION`].
+    #[serde(default)]
+    pub encoding_version: Option<u32>,
+    /// Clauses of the proof in the CNF DIMACS format.
+    #[serde(default)]
+    pub cnf: Option<String>,
+}
+
+/// How a certificate was verified by [`Certificate::verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationMethod {
+    /// Every labeling of the graph was tried.
+    ExhaustiveSearch,
+    /// The proof of the certificate was checked against the encoding of the problem.
+    Proof,
+    /// The encoding of the problem was solved again.
+    Solver,
+    /// The proof of the certificate is of another version of the encoding,
+    /// so the encoding of the problem was solved again.
+    OutdatedProof,
+}
+
+impl Certificate {
+    /// Creates a certificate of `problem` on the counterexample `graph`.
+    ///
+    /// The ports of a node are in the order of its edges in the graph, which is the order used by [`SatEncoder`].
+    /// If there is a `proof`, the clauses from [`SatEncoder::encode`] are included with it.
+    pub fn new(problem: &LclProblem, graph: &BiregularGraph, proof: Option<&DratProof>) -> Self {
+        let node_count = graph.graph.node_count();
+        let active_indices = get_indices(&graph.partition_a, node_count);
+        let passive_indices = get_indices(&graph.partition_b, node_count);
+        let port = |node: NodeIndex, edge| {
+            graph
+                .graph
+                .edges(node)
+                .position(|incident| incident.id() == edge)
+                .unwrap()
+        };
+
+        let edges = graph
+            .graph
+            .edge_references()
+            .map(|edge| {
+                let (active, passive) = if active_indices[edge.source().index()].is_some() {
+                    (edge.source(), edge.target())
+                } else {
+                    (edge.target(), edge.source())
+                };
+                CertificateEdge {
+                    active: active_indices[active.index()].unwrap(),
+                    active_port: port(active, edge.id()),
+                    passive: passive_indices[passive.index()].unwrap(),
+                    passive_port: port(passive, edge.id()),
+                }
+            })
+            .collect();
+
+        Self {
+            version: CERTIFICATE_VERSION,
+            active_configurations: problem
+                .active
+                .to_condensed_string(|l| problem.get_label_name(l)),
+            passive_configurations: problem
+                .passive
+                .to_condensed_string(|l| problem.get_label_name(l)),
+            active_node_count: graph.partition_a.len(),
+            passive_node_count: graph.partition_b.len(),
+            edges,
+            proof: proof.map(|proof| proof.to_string()),
+            encoding_version: proof.map(|_| ENCODING_VERSION),
+            cnf: proof
+                .map(|_| clauses_to_dimacs(&SatEncoder::new(problem, graph.clone()).encode())),
+        }
+    }
+
+    /// Parses a certificate from JSON.
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Returns the certificate as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Certificate can always be serialized")
+    }
+
+    /// Parses the problem of the certificate.
+    pub fn get_problem(&self) -> Result<LclProblem, Box<dyn Error>> {
+        Ok(LclProblem::new(
+            &self.active_configurations,
+            &self.passive_configurations,
+        )?)
+    }
+
+    /// Returns the port of each edge at its active node and at its passive node, see [`SatEncoder::with_ports`].
+    pub fn get_ports(&self) -> Vec<(usize, usize)> {
+        self.edges
+            .iter()
+            .map(|edge| (edge.active_port, edge.passive_port))
+            .collect()
+    }
+
+    /// Returns the encoder of the problem on the graph of the certificate, with the ports of the certificate.
+    pub fn get_encoder(&self, problem: &LclProblem) -> SatEncoder {
+        SatEncoder::new(problem, self.get_graph()).with_ports(self.get_ports())
+    }
+
+    /// Returns the graph of the certificate.
+    ///
+    /// Active nodes come first, followed by the passive nodes.
+    /// Edges are in the order of the certificate, so the order of edges of a node may differ from its ports,
+    /// which are given by [`Certificate::get_ports`].
+    pub fn get_graph(&self) -> BiregularGraph {
+        let mut graph = UndirectedGraph::new_undirected();
+        let nodes = (0..self.active_node_count + self.passive_node_count)
+            .map(|node| graph.add_node(node as u32))
+            .collect_vec();
+        let (partition_a, partition_b) = nodes.split_at(self.active_node_count);
+        for edge in &self.edges {
+            graph.add_edge(partition_a[edge.active], partition_b[edge.passive], ());
+        }
+        let degree =
+            |nodes: &[NodeIndex]| nodes.first().map_or(0, |&node| graph.edges(node).count());
+        BiregularGraph {
+            degree_a: degree(partition_a),
+            degree_b: degree(partition_b),
+            partition_a: partition_a.to_vec(),
+            partition_b: partition_b.to_vec(),
+            graph,
+        }
+    }
+
+    /// Verifies that the problem cannot be solved on the graph.
+    ///
+    /// If the count of labelings is small, every labeling is tried with [`BruteForceSolver`] without the SAT encoder.
+    /// Otherwise the proof is checked against the encoding of the problem if there is one.
+    /// A proof of another version of the encoding cannot be checked, so then the encoding is solved again,
+    /// as it is if there is no proof.
+    ///
+    /// # Errors
+    /// Returns an error if the certificate is malformed, if its proof is invalid,
+    /// if its clauses are not the encoding of the problem or if the problem can be solved on the graph.
+    pub fn verify(&self) -> Result<VerificationMethod, Box<dyn Error>> {
+        if self.version == 0 || self.version > CERTIFICATE_VERSION {
+            return Err(format!("Unsupported certificate version {}", self.version).into());
+        }
+        let problem = self.get_problem()?;
+        self.check_ports(&problem)?;
+
+        let graph = self.get_graph();
+        if BruteForceSolver::get_labeling_count(&problem, &graph) <= EXHAUSTIVE_SEARCH_LIMIT {
+            if BruteForceSolver.is_solvable(&problem, &graph) {
+                return Err("Problem can be solved on the graph".into());
+            }
+            return Ok(VerificationMethod::ExhaustiveSearch);
+        }
+
+        let clauses = self.get_encoder(&problem).encode();
+        if let Some(proof) = &self.proof {
+            if self.encoding_version == Some(ENCODING_VERSION) {
+                if self.cnf.as_deref() != Some(clauses_to_dimacs(&clauses).as_str()) {
+                    return Err(
+                        "Clauses of the certificate are not the encoding of the problem".into(),
+                    );
+                }
+                DratProof::parse(proof)?.check(&clauses)?;
+                return Ok(VerificationMethod::Proof);
+            }
+        }
+        match SatSolver::solve(clauses) {
+            SatResult::Unsatisfiable if self.proof.is_some() => {
+                Ok(VerificationMethod::OutdatedProof)
+            }
+            SatResult::Unsatisfiable => Ok(VerificationMethod::Solver),
+            SatResult::Satisfiable(_) => Err("Problem can be solved on the graph".into()),
+            SatResult::Unknown => Err("SAT solver could not decide the problem".into()),
+        }
+    }
+
+    /// Checks that the ports of each node are numbered from 0 to the degree of the problem minus one.
+    fn check_ports(&self, problem: &LclProblem) -> Result<(), Box<dyn Error>> {
+        let check = |node_count: usize, degree: usize, ports: Vec<(usize, usize)>| {
+            let expected = (0..node_count).cartesian_product(0..degree).collect_vec();
+            if ports.into_iter().sorted().collect_vec() != expected {
+                return Err(format!(
+                    "Ports of the nodes do not match the degree {}",
+                    degree
+                ));
+            }
+            Ok(())
+        };
+        check(
+            self.active_node_count,
+            problem.active.get_labels_per_configuration(),
+            self.edges
+                .iter()
+                .map(|edge| (edge.active, edge.active_port))
+                .collect(),
+        )?;
+        check(
+            self.passive_node_count,
+            problem.passive.get_labels_per_configuration(),
+            self.edges
+                .iter()
+                .map(|edge| (edge.passive, edge.passive_port))
+                .collect(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Returns the index in `nodes` of each of the `node_count` nodes of a graph.
+fn get_indices(nodes: &[NodeIndex], node_count: usize) -> Vec<Option<usize>> {
+    let mut indices = vec![None; node_count];
+    for (index, node) in nodes.iter().enumerate() {
+        indices[node.index()] = Some(index);
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_parity_problem;
+
+    #[test]
+    fn test_json() {
+        let problem = get_parity_problem();
+        let certificate = Certificate::new(&problem, &BiregularGraph::cycle(3), None);
+        assert_eq!(certificate.active_node_count, 3);
+        assert_eq!(certificate.edges.len(), 6);
+        assert_eq!(certificate.get_problem().unwrap(), problem);
+
+        let json = certificate.to_json();
+        assert_eq!(Certificate::from_json(&json).unwrap(), certificate);
+        assert!(Certificate::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        // The count of active nodes is odd, so the labels cannot alternate.
+        let problem = get_parity_problem();
+        let certificate = Certificate::new(&problem, &BiregularGraph::cycle(3), None);
+        assert_eq!(
+            certificate.verify().unwrap(),
+            VerificationMethod::ExhaustiveSearch
+        );
+
+        let certificate = Certificate::new(&problem, &BiregularGraph::cycle(2), None);
+        assert!(certificate.verify().is_err());
+
+        let mut certificate = Certificate::new(&problem, &BiregularGraph::cycle(3), None);
+        certificate.edges[0].active_port = 5;
+        assert!(certificate.verify().is_err());
+    }
+
+    #[test]
+    fn test_verify_large() {
+        // Too many labelings for the search, so the encoding is solved.
+        let problem = get_parity_problem();
+        let certificate = Certificate::new(&problem, &BiregularGraph::cycle(21), None);
+        assert_eq!(certificate.verify().unwrap(), VerificationMethod::Solver);
+
+        // An invalid proof is an error.
+        let proof = DratProof::parse("0\n").unwrap();
+        let mut certificate = Certificate::new(&problem, &BiregularGraph::cycle(21), Some(&proof));
+        assert_eq!(certificate.proof.as_deref(), Some("0\n"));
+        assert!(certificate.verify().is_err());
+
+        // A proof of another version of the encoding cannot be checked, so the encoding is solved.
+        certificate.encoding_version = Some(ENCODING_VERSION - 1);
+        assert_eq!(
+            certificate.verify().unwrap(),
+            VerificationMethod::OutdatedProof
+        );
+
+        let certificate = Certificate::new(&problem, &BiregularGraph::cycle(22), None);
+        assert!(certificate.verify().is_err());
+    }
+
+    #[test]
+    fn test_ports() {
+        let problem = get_parity_problem();
+        let graph = BiregularGraph::cycle(3);
+        let certificate = Certificate::new(&problem, &graph, None);
+        let clauses = SatEncoder::new(&problem, graph).encode();
+
+        // Edges of the rebuilt graph are in another order, which the ports restore.
+        assert_ne!(
+            SatEncoder::new(&problem, certificate.get_graph()).encode(),
+            clauses
+        );
+        assert_eq!(certificate.get_encoder(&problem).encode(), clauses);
+    }
+
+    #[test]
+    #[ignore = "Requires the kissat executable"]
+    fn test_verify_proof() {
+        let problem = get_parity_problem();
+        let graph = BiregularGraph::cycle(21);
+        let clauses = SatEncoder::new(&problem, graph.clone()).encode();
+        let proof = SatSolver::prove_unsatisfiable(&clauses).unwrap().unwrap();
+        let certificate = Certificate::new(&problem, &graph, Some(&proof));
+        let certificate = Certificate::from_json(&certificate.to_json()).unwrap();
+        assert_eq!(certificate.verify().unwrap(), VerificationMethod::Proof);
+
+        // Swapping the ports of a node changes the clauses, so they no longer match the proof.
+        let mut swapped = certificate;
+        let (first, second) = swapped
+            .edges
+            .iter()
+            .positions(|edge| edge.active == 0)
+            .collect_tuple()
+            .unwrap();
+        let first_port = swapped.edges[first].active_port;
+        swapped.edges[first].active_port = swapped.edges[second].active_port;
+        swapped.edges[second].active_port = first_port;
+        assert!(swapped.verify().is_err());
+    }
+}
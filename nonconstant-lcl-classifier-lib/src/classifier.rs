@@ -0,0 +1,822 @@
+use crate::caches::{GraphSqliteCache, LclProblemSqliteCache};
+use crate::sat_encoder::encoding::{PermutationEncoding, SatEncoding};
+#[cfg(feature = "varisat")]
+use crate::sat_encoder::selectors::SelectorEncoder;
+use crate::sat_encoder::EncodingStatistics;
+use crate::sat_solver::backend::SatBackend;
+#[cfg(feature = "varisat")]
+use crate::sat_solver::incremental::{new_incremental_solver, IncrementalSatSolver};
+use crate::sat_solver::SatLimits;
+use crate::{
+    BiregularGraph, GraphStatus, LclProblem, ProblemLattice, SatResult, SatSolver, ZeroRoundWitness,
+};
+#[cfg(feature = "varisat")]
+use itertools::Itertools;
+use log::info;
+#[cfg(feature = "varisat")]
+use log::warn;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Graph on which a problem cannot be solved in the PN model.
+#[derive(Debug, Clone)]
+pub struct Counterexample {
+    pub graph: BiregularGraph,
+    /// Index of the graph among the generated graphs of the same size.
+    pub graph_index: usize,
+}
+
+impl Counterexample {
+    /// Returns the count of nodes in the graph.
+    pub fn get_node_count(&self) -> usize {
+        self.graph.graph.node_count()
+    }
+}
+
+/// Graph on which the SAT solver reached a limit, see [`Classifier::sat_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownGraph {
+    pub node_count: usize,
+    /// Index of the graph among the generated graphs of the same size.
+    pub graph_index: usize,
+}
+
+/// Result of searching counterexamples for a single problem.
+#[derive(Debug, Clone)]
+pub struct ClassificationResult {
+    pub problem: LclProblem,
+    /// Counterexamples in increasing order of size.
+    ///
+    /// Empty if no counterexample was found, i.e. the lower bound is not proven.
+    pub counterexamples: Vec<Counterexample>,
+    /// Graphs on which it is not known whether the problem can be solved,
+    /// because the SAT solver reached a limit.
+    pub unknown_graphs: Vec<UnknownGraph>,
+    /// Witness that the problem is constant, in which case no graphs were tried.
+    pub zero_round_witness: Option<ZeroRoundWitness>,
+    /// Time spent on the problem.
+    pub time: Duration,
+}
+
+/// Results of [`Classifier::classify`] with statistics.
+#[derive(Debug, Clone)]
+pub struct Classification {
+    /// Result of each problem in the same order as the problems.
+    pub results: Vec<ClassificationResult>,
+    /// Count of SAT instances that were solved.
+    pub sat_count: usize,
+    /// Count of results that were inferred from other problems instead of solving a SAT instance.
+    pub inferred_count: usize,
+    /// Count of SAT instances on which the solver reached a limit.
+    pub unknown_count: usize,
+    /// Total counts of variables and clauses of the SAT instances that were encoded.
+    ///
+    /// In incremental mode, each graph is counted once.
+    pub encoding_statistics: EncodingStatistics,
+    /// Time spent on generating graphs or reading them from a cache.
+    pub graph_time: Duration,
+    /// Time spent on finding counterexamples.
+    pub sat_time: Duration,
+}
+
+/// Function that is called with the result of each problem, see [`Classifier::on_progress`].
+type ProgressFn = dyn Fn(&ClassificationResult) + Send + Sync;
+
+/// Graph that is encoded once for all problems, see [`Classifier::incremental`].
+#[cfg(feature = "varisat")]
+struct IncrementalGraph {
+    encoder: SelectorEncoder,
+    solver: Box<dyn IncrementalSatSolver>,
+    statistics: EncodingStatistics,
+}
+
+#[cfg(feature = "varisat")]
+impl IncrementalGraph {
+    fn new(graph: &BiregularGraph, label_count: usize) -> Self {
+        let encoder = SelectorEncoder::new(graph.clone(), label_count);
+        let mut solver = new_incremental_solver();
+        let clauses = encoder.encode();
+        let statistics = EncodingStatistics::from_clauses(&clauses);
+        for clause in clauses {
+            solver.add_clause(&clause);
+        }
+        Self {
+            encoder,
+            solver,
+            statistics,
+        }
+    }
+
+    /// Solves `problem` on the graph under assumptions,
+    /// or returns `None` if the problem is not in the class of the encoder.
+    fn solve(&mut self, problem: &LclProblem) -> Option<SatResult> {
+        let assumptions = self.encoder.get_assumptions(problem)?;
+        let result = self
+            .solver
+            .solve_with_assumptions(&assumptions)
+            .expect("SAT solver failed");
+        Some(result)
+    }
+}
+
+/// Searches counterexample graphs that prove non-constant lower bounds for LCL problems.
+///
+/// For each problem, biregular multigraphs are tried in increasing order of size.
+/// By default the search stops at the first counterexample,
+/// see [`Classifier::all_graphs`] and [`Classifier::all_graph_sizes`].
+/// Problems with a 0-round witness are constant, so they are skipped.
+///
+/// # Example
+/// Graphs are generated with the `genbg` executable of nauty, which has to be in `PATH`:
+/// ```no_run
+/// # use nonconstant_lcl_classifier_lib::{Classifier, LclProblem};
+/// let problem = LclProblem::new("MUU PPP", "MM PU UU")?;
+/// let classification = Classifier::new(1, 10).classify(&[problem]);
+/// // The problem is solvable on every graph of less than 10 nodes.
+/// assert!(!classification.results[0].counterexamples.is_empty());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// The graphs can also be given, e.g. cycles of 4 and 6 nodes:
+/// ```
+/// # use nonconstant_lcl_classifier_lib::{BiregularGraph, Classifier, LclProblem};
+/// // Each active node has different labels and each passive node equal labels on its edges.
+/// let problem = LclProblem::new("AB", "AA BB")?;
+/// let graphs = vec![vec![BiregularGraph::cycle(2)], vec![BiregularGraph::cycle(3)]];
+/// let classification = Classifier::new(4, 6).classify_with_graphs(&[problem], &graphs);
+/// // Only the cycle with an odd count of active nodes is a counterexample.
+/// assert_eq!(classification.results[0].counterexamples.len(), 1);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Classifier {
+    min_nodes: usize,
+    max_nodes: usize,
+    all_graphs: bool,
+    all_graph_sizes: bool,
+    parallel: bool,
+    #[cfg(feature = "varisat")]
+    incremental: bool,
+    encoding: Box<dyn SatEncoding>,
+    sat_limits: SatLimits,
+    backend: Option<Arc<dyn SatBackend>>,
+    graph_cache: Option<GraphSqliteCache>,
+    problem_cache: Option<LclProblemSqliteCache>,
+    progress: Option<Box<ProgressFn>>,
+}
+
+impl Classifier {
+    /// Creates a classifier that tries graphs of `min_nodes..=max_nodes` nodes.
+    pub fn new(min_nodes: usize, max_nodes: usize) -> Self {
+        Self {
+            min_nodes,
+            max_nodes,
+            all_graphs: false,
+            all_graph_sizes: false,
+            parallel: true,
+            #[cfg(feature = "varisat")]
+            incremental: false,
+            encoding: Box::new(PermutationEncoding),
+            sat_limits: SatLimits::default(),
+            backend: None,
+            graph_cache: None,
+            problem_cache: None,
+            progress: None,
+        }
+    }
+
+    /// Sets whether all counterexamples of the first size with counterexamples are found.
+    pub fn all_graphs(mut self, all_graphs: bool) -> Self {
+        self.all_graphs = all_graphs;
+        self
+    }
+
+    /// Sets whether counterexamples are searched from every size instead of stopping at the first size with counterexamples.
+    pub fn all_graph_sizes(mut self, all_graph_sizes: bool) -> Self {
+        self.all_graph_sizes = all_graph_sizes;
+        self
+    }
+
+    /// Sets whether problems are classified in parallel. This is the default.
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Sets whether each graph is encoded once for all problems and solved incrementally.
+    ///
+    /// Graphs are tried one at a time for all problems instead of problems one at a time.
+    /// Each graph is encoded with [`SelectorEncoder`] into a Varisat solver,
+    /// which solves the problems under assumptions that disable the configurations they do not allow,
+    /// and the solver is dropped when the problems are done with the graph.
+    /// Limits and the encoding of [`Classifier::encoding`] are not used for these graphs.
+    ///
+    /// Requires the `varisat` feature.
+    #[cfg(feature = "varisat")]
+    pub fn incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Sets the encoding of SAT instances. [`PermutationEncoding`] is the default.
+    ///
+    /// In incremental mode, the encoding is used only for problems that are not in the class of [`SelectorEncoder`],
+    /// i.e. problems with other degrees than the graphs.
+    pub fn encoding(mut self, encoding: Box<dyn SatEncoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the limits of each SAT instance. There are no limits by default.
+    ///
+    /// Graphs on which the solver reaches a limit are neither counterexamples nor solvable,
+    /// see [`ClassificationResult::unknown_graphs`].
+    pub fn sat_limits(mut self, sat_limits: SatLimits) -> Self {
+        self.sat_limits = sat_limits;
+        self
+    }
+
+    /// Sets the SAT solver backend.
+    ///
+    /// By default the backend selected with [`SatSolver::set_backend`] is used.
+    /// In incremental mode, the backend is used only for problems that are not in the class of [`SelectorEncoder`].
+    pub fn backend(mut self, backend: Arc<dyn SatBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Returns the SAT solver backend, see [`Classifier::backend`].
+    pub fn get_backend(&self) -> Arc<dyn SatBackend> {
+        self.backend.clone().unwrap_or_else(SatSolver::get_backend)
+    }
+
+    /// Sets the cache of generated graphs.
+    pub fn graph_cache(mut self, cache: GraphSqliteCache) -> Self {
+        self.graph_cache = Some(cache);
+        self
+    }
+
+    /// Sets the cache of generated problem classes, see [`Classifier::get_problem_class`].
+    pub fn problem_cache(mut self, cache: LclProblemSqliteCache) -> Self {
+        self.problem_cache = Some(cache);
+        self
+    }
+
+    /// Sets a function that is called with the result of each problem when it is ready.
+    ///
+    /// When problems are classified in parallel, the function is called from multiple threads.
+    pub fn on_progress<F>(mut self, progress: F) -> Self
+    where
+        F: Fn(&ClassificationResult) + Send + Sync + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Returns the normalized problems of a class from the problem cache or generates them.
+    ///
+    /// See [`LclProblem::get_or_generate_normalized`].
+    pub fn get_problem_class(
+        &mut self,
+        active_degree: usize,
+        passive_degree: usize,
+        label_count: u8,
+    ) -> Vec<LclProblem> {
+        LclProblem::get_or_generate_normalized(
+            active_degree,
+            passive_degree,
+            label_count,
+            self.problem_cache.as_mut(),
+        )
+    }
+
+    /// Returns the graphs of each size from the graph cache or generates them.
+    ///
+    /// The graphs of size `min_nodes + i` are at index `i`.
+    pub fn generate_graphs(
+        &mut self,
+        degree_a: usize,
+        degree_b: usize,
+    ) -> Vec<Vec<BiregularGraph>> {
+        (self.min_nodes..=self.max_nodes)
+            .map(|n| {
+                BiregularGraph::get_or_generate(n, degree_a, degree_b, self.graph_cache.as_mut())
+            })
+            .collect()
+    }
+
+    /// Searches counterexamples for `problems`.
+    ///
+    /// Graphs are generated for the degrees of the first problem,
+    /// so all problems should have the same degrees.
+    pub fn classify(&mut self, problems: &[LclProblem]) -> Classification {
+        let now = Instant::now();
+        let graphs = match problems.first() {
+            Some(problem) => self.generate_graphs(
+                problem.active.get_labels_per_configuration(),
+                problem.passive.get_labels_per_configuration(),
+            ),
+            None => vec![],
+        };
+        let graph_time = now.elapsed();
+
+        let mut classification = self.classify_with_graphs(problems, &graphs);
+        classification.graph_time = graph_time;
+        classification
+    }
+
+    /// Searches counterexamples for `problems` from already generated `graphs`.
+    ///
+    /// `graphs` contains the graphs of each size in increasing order of size, see [`Classifier::generate_graphs`].
+    /// Results of SAT are shared between problems that are restrictions of each other,
+    /// see [`ProblemLattice`].
+    pub fn classify_with_graphs(
+        &self,
+        problems: &[LclProblem],
+        graphs: &[Vec<BiregularGraph>],
+    ) -> Classification {
+        #[cfg(feature = "varisat")]
+        if self.incremental {
+            return self.classify_incremental(problems, graphs);
+        }
+
+        let lattice = Mutex::new(ProblemLattice::new(problems));
+        let sat_count = AtomicUsize::new(0);
+        let inferred_count = AtomicUsize::new(0);
+        let unknown_count = AtomicUsize::new(0);
+        let variable_count = AtomicUsize::new(0);
+        let clause_count = AtomicUsize::new(0);
+        let add_statistics = |statistics: EncodingStatistics| {
+            variable_count.fetch_add(statistics.variable_count, Ordering::Relaxed);
+            clause_count.fetch_add(statistics.clause_count, Ordering::Relaxed);
+        };
+        let sat_limits = &self.sat_limits;
+        let backend = self.get_backend();
+        let encoding = &self.encoding;
+        let all_graphs = self.all_graphs;
+        let all_graph_sizes = self.all_graph_sizes;
+        let progress = &self.progress;
+
+        let classify_problem = |(problem_index, problem): (usize, &LclProblem)| {
+            let now = Instant::now();
+            let mut counterexamples = vec![];
+            let mut unknown_graphs = vec![];
+
+            // Problems with a 0-round witness are constant, so they have no counterexamples.
+            let zero_round_witness = problem.find_zero_round_witness();
+            if let Some(witness) = &zero_round_witness {
+                info!(
+                    "constant (0-round witness): {} with {:?}",
+                    problem.to_string(),
+                    witness
+                );
+            } else {
+                'graph_size_loop: for (size_index, graphs_n) in graphs.iter().enumerate() {
+                    let mut found = false;
+
+                    'graph_loop: for (graph_index, graph) in graphs_n.iter().enumerate() {
+                        let graph_key = (size_index, graph_index);
+                        let status = lattice
+                            .lock()
+                            .unwrap()
+                            .get_status(problem_index, &graph_key);
+
+                        let status = match status {
+                            Some(status) => {
+                                inferred_count.fetch_add(1, Ordering::Relaxed);
+                                status
+                            }
+                            None => {
+                                let clauses = encoding.encode(problem, graph);
+                                add_statistics(EncodingStatistics::from_clauses(&clauses));
+                                let result = SatSolver::solve_with_backend(
+                                    clauses,
+                                    sat_limits,
+                                    backend.as_ref(),
+                                );
+                                sat_count.fetch_add(1, Ordering::Relaxed);
+                                let status = match result {
+                                    SatResult::Satisfiable(_) => GraphStatus::Solvable,
+                                    SatResult::Unsatisfiable => GraphStatus::Unsolvable,
+                                    SatResult::Unknown => {
+                                        unknown_count.fetch_add(1, Ordering::Relaxed);
+                                        unknown_graphs.push(UnknownGraph {
+                                            node_count: graph.graph.node_count(),
+                                            graph_index,
+                                        });
+                                        continue;
+                                    }
+                                };
+                                lattice.lock().unwrap().set_status(
+                                    problem_index,
+                                    graph_key,
+                                    status,
+                                );
+                                status
+                            }
+                        };
+                        if status == GraphStatus::Solvable {
+                            continue;
+                        }
+
+                        found = true;
+                        counterexamples.push(Counterexample {
+                            graph: graph.clone(),
+                            graph_index,
+                        });
+
+                        if !all_graphs {
+                            break 'graph_loop;
+                        }
+                    }
+                    if found && !all_graph_sizes {
+                        break 'graph_size_loop;
+                    }
+                }
+            }
+
+            let result = ClassificationResult {
+                problem: problem.clone(),
+                counterexamples,
+                unknown_graphs,
+                zero_round_witness,
+                time: now.elapsed(),
+            };
+            if let Some(progress) = progress {
+                progress(&result);
+            }
+            result
+        };
+
+        let now = Instant::now();
+        let results = if self.parallel {
+            problems
+                .par_iter()
+                .enumerate()
+                .map(&classify_problem)
+                .collect()
+        } else {
+            problems.iter().enumerate().map(&classify_problem).collect()
+        };
+        let sat_time = now.elapsed();
+
+        Classification {
+            results,
+            sat_count: sat_count.into_inner(),
+            inferred_count: inferred_count.into_inner(),
+            unknown_count: unknown_count.into_inner(),
+            encoding_statistics: EncodingStatistics {
+                variable_count: variable_count.into_inner(),
+                clause_count: clause_count.into_inner(),
+            },
+            graph_time: Duration::default(),
+            sat_time,
+        }
+    }
+
+    /// Searches counterexamples for `problems` from `graphs` one graph at a time, see [`Classifier::incremental`].
+    ///
+    /// The graphs of each size are tried in parallel, each for the problems without counterexamples
+    /// among the smaller graphs, so there is at most one solver for each thread at a time.
+    #[cfg(feature = "varisat")]
+    fn classify_incremental(
+        &self,
+        problems: &[LclProblem],
+        graphs: &[Vec<BiregularGraph>],
+    ) -> Classification {
+        if self.sat_limits != SatLimits::default() {
+            warn!("Limits are not supported in incremental mode, they are ignored");
+        }
+
+        let now = Instant::now();
+        let lattice = Mutex::new(ProblemLattice::new(problems));
+        let sat_count = AtomicUsize::new(0);
+        let inferred_count = AtomicUsize::new(0);
+        let unknown_count = AtomicUsize::new(0);
+        let variable_count = AtomicUsize::new(0);
+        let clause_count = AtomicUsize::new(0);
+        let add_statistics = |statistics: EncodingStatistics| {
+            variable_count.fetch_add(statistics.variable_count, Ordering::Relaxed);
+            clause_count.fetch_add(statistics.clause_count, Ordering::Relaxed);
+        };
+        let sat_limits = &self.sat_limits;
+        let backend = self.get_backend();
+        let encoding = &self.encoding;
+        let all_graphs = self.all_graphs;
+        let report_progress = |result: &ClassificationResult| {
+            if let Some(progress) = &self.progress {
+                progress(result);
+            }
+        };
+
+        // Each graph is encoded for the class of problems with the labels of every problem.
+        let label_count = problems
+            .iter()
+            .flat_map(|problem| {
+                let mut labels = problem.active.get_labels();
+                labels.extend(problem.passive.get_labels());
+                labels
+            })
+            .max()
+            .map_or(0, |label| label as usize + 1);
+
+        let mut results = problems
+            .iter()
+            .map(|problem| ClassificationResult {
+                problem: problem.clone(),
+                counterexamples: vec![],
+                unknown_graphs: vec![],
+                zero_round_witness: problem.find_zero_round_witness(),
+                time: Duration::default(),
+            })
+            .collect_vec();
+        // Problems with a 0-round witness are constant, so they have no counterexamples.
+        for result in &results {
+            if let Some(witness) = &result.zero_round_witness {
+                info!(
+                    "constant (0-round witness): {} with {:?}",
+                    result.problem.to_string(),
+                    witness
+                );
+                report_progress(result);
+            }
+        }
+        let mut open_problems = (0..problems.len())
+            .filter(|&problem_index| results[problem_index].zero_round_witness.is_none())
+            .collect_vec();
+
+        for (size_index, graphs_n) in graphs.iter().enumerate() {
+            if open_problems.is_empty() {
+                break;
+            }
+            // Index of the first counterexample of each problem among the graphs of this size.
+            let first_counterexamples = problems
+                .iter()
+                .map(|_| AtomicUsize::new(usize::MAX))
+                .collect_vec();
+
+            // Returns the status of the graph for each open problem that needs it, or `None` if it is unknown.
+            let classify_graph = |(graph_index, graph): (usize, &BiregularGraph)| {
+                let mut incremental_graph = None;
+                let mut statuses = vec![];
+                for &problem_index in &open_problems {
+                    if !all_graphs
+                        && first_counterexamples[problem_index].load(Ordering::Relaxed)
+                            < graph_index
+                    {
+                        continue;
+                    }
+                    let now = Instant::now();
+                    let graph_key = (size_index, graph_index);
+                    let status = lattice
+                        .lock()
+                        .unwrap()
+                        .get_status(problem_index, &graph_key);
+
+                    let status = match status {
+                        Some(status) => {
+                            inferred_count.fetch_add(1, Ordering::Relaxed);
+                            Some(status)
+                        }
+                        None => {
+                            let problem = &problems[problem_index];
+                            let result = incremental_graph
+                                .get_or_insert_with(|| {
+                                    let incremental_graph =
+                                        IncrementalGraph::new(graph, label_count);
+                                    add_statistics(incremental_graph.statistics);
+                                    incremental_graph
+                                })
+                                .solve(problem)
+                                .unwrap_or_else(|| {
+                                    let clauses = encoding.encode(problem, graph);
+                                    add_statistics(EncodingStatistics::from_clauses(&clauses));
+                                    SatSolver::solve_with_backend(
+                                        clauses,
+                                        sat_limits,
+                                        backend.as_ref(),
+                                    )
+                                });
+                            sat_count.fetch_add(1, Ordering::Relaxed);
+                            let status = match result {
+                                SatResult::Satisfiable(_) => Some(GraphStatus::Solvable),
+                                SatResult::Unsatisfiable => Some(GraphStatus::Unsolvable),
+                                SatResult::Unknown => {
+                                    unknown_count.fetch_add(1, Ordering::Relaxed);
+                                    None
+                                }
+                            };
+                            if let Some(status) = status {
+                                lattice.lock().unwrap().set_status(
+                                    problem_index,
+                                    graph_key,
+                                    status,
+                                );
+                            }
+                            status
+                        }
+                    };
+                    if status == Some(GraphStatus::Unsolvable) {
+                        first_counterexamples[problem_index]
+                            .fetch_min(graph_index, Ordering::Relaxed);
+                    }
+                    statuses.push((problem_index, status, now.elapsed()));
+                }
+                statuses
+            };
+
+            let statuses_n: Vec<_> = if self.parallel {
+                graphs_n
+                    .par_iter()
+                    .enumerate()
+                    .map(&classify_graph)
+                    .collect()
+            } else {
+                graphs_n.iter().enumerate().map(&classify_graph).collect()
+            };
+
+            // Results are collected in the order of the graphs, as when problems are classified one at a time.
+            for (graph_index, statuses) in statuses_n.into_iter().enumerate() {
+                for (problem_index, status, time) in statuses {
+                    let result = &mut results[problem_index];
+                    result.time += time;
+                    if !all_graphs
+                        && first_counterexamples[problem_index].load(Ordering::Relaxed)
+                            < graph_index
+                    {
+                        continue;
+                    }
+                    let graph = &graphs_n[graph_index];
+                    match status {
+                        Some(GraphStatus::Solvable) => {}
+                        Some(GraphStatus::Unsolvable) => {
+                            result.counterexamples.push(Counterexample {
+                                graph: graph.clone(),
+                                graph_index,
+                            })
+                        }
+                        None => result.unknown_graphs.push(UnknownGraph {
+                            node_count: graph.graph.node_count(),
+                            graph_index,
+                        }),
+                    }
+                }
+            }
+
+            if !self.all_graph_sizes {
+                open_problems.retain(|&problem_index| {
+                    let result = &results[problem_index];
+                    let found = !result.counterexamples.is_empty();
+                    if found {
+                        report_progress(result);
+                    }
+                    !found
+                });
+            }
+        }
+        for &problem_index in &open_problems {
+            report_progress(&results[problem_index]);
+        }
+
+        Classification {
+            results,
+            sat_count: sat_count.into_inner(),
+            inferred_count: inferred_count.into_inner(),
+            unknown_count: unknown_count.into_inner(),
+            encoding_statistics: EncodingStatistics {
+                variable_count: variable_count.into_inner(),
+                clause_count: clause_count.into_inner(),
+            },
+            graph_time: Duration::default(),
+            sat_time: now.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_parity_problem;
+
+    #[test]
+    fn test_classify_with_graphs() {
+        // Graphs of 4 and 6 nodes, with two copies of the 6-cycle.
+        let graphs = vec![
+            vec![BiregularGraph::cycle(2)],
+            vec![BiregularGraph::cycle(3), BiregularGraph::cycle(3)],
+        ];
+        let problems = vec![
+            // Solvable only if the count of active nodes is even.
+            get_parity_problem(),
+            // Solvable on every graph by labeling all edges with A.
+            LclProblem::new("AA", "AA").unwrap(),
+            // Solvable on every cycle by labeling the edges of each active node in the same order.
+            LclProblem::new("AB", "AB").unwrap(),
+        ];
+
+        let classification = Classifier::new(4, 6)
+            .parallel(false)
+            .classify_with_graphs(&problems, &graphs);
+        let results = &classification.results;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].counterexamples.len(), 1);
+        assert_eq!(results[0].counterexamples[0].get_node_count(), 6);
+        assert_eq!(results[0].counterexamples[0].graph_index, 0);
+        assert!(results[1].zero_round_witness.is_some());
+        assert!(results[1].counterexamples.is_empty());
+        assert!(results[2].counterexamples.is_empty());
+
+        let classification = Classifier::new(4, 6)
+            .all_graphs(true)
+            .classify_with_graphs(&problems, &graphs);
+        assert_eq!(classification.results[0].counterexamples.len(), 2);
+
+        let classification = Classifier::new(4, 6)
+            .encoding(Box::new(crate::sat_encoder::encoding::CountingEncoding))
+            .classify_with_graphs(&problems, &graphs);
+        assert_eq!(classification.results[0].counterexamples.len(), 1);
+        assert!(classification.results[2].counterexamples.is_empty());
+    }
+
+    /// Backend that gives up on every SAT instance.
+    struct UnknownBackend;
+
+    impl SatBackend for UnknownBackend {
+        fn get_name(&self) -> String {
+            "unknown".to_string()
+        }
+
+        fn solve(
+            &self,
+            _clauses: crate::sat_encoder::Clauses,
+            _limits: &SatLimits,
+        ) -> Result<SatResult, Box<dyn std::error::Error>> {
+            Ok(SatResult::Unknown)
+        }
+    }
+
+    #[test]
+    fn test_backend() {
+        let graphs = vec![
+            vec![BiregularGraph::cycle(2)],
+            vec![BiregularGraph::cycle(3), BiregularGraph::cycle(3)],
+        ];
+        let problems = vec![get_parity_problem()];
+
+        let classification = Classifier::new(4, 6)
+            .backend(Arc::new(UnknownBackend))
+            .classify_with_graphs(&problems, &graphs);
+        assert!(classification.results[0].counterexamples.is_empty());
+        assert_eq!(classification.results[0].unknown_graphs.len(), 3);
+        assert_eq!(classification.unknown_count, 3);
+
+        // Other classifiers still use the default backend.
+        let classification = Classifier::new(4, 6).classify_with_graphs(&problems, &graphs);
+        assert_eq!(classification.results[0].counterexamples.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "varisat")]
+    fn test_classify_incremental() {
+        let graphs = vec![
+            vec![BiregularGraph::cycle(2)],
+            vec![BiregularGraph::cycle(3), BiregularGraph::cycle(3)],
+        ];
+        let problems = LclProblem::generate_normalized(2, 2, 3);
+
+        for (all_graphs, all_graph_sizes) in [(false, false), (true, false), (true, true)] {
+            let classification = Classifier::new(4, 6)
+                .all_graphs(all_graphs)
+                .all_graph_sizes(all_graph_sizes)
+                .classify_with_graphs(&problems, &graphs);
+            let incremental = Classifier::new(4, 6)
+                .all_graphs(all_graphs)
+                .all_graph_sizes(all_graph_sizes)
+                .incremental(true)
+                .classify_with_graphs(&problems, &graphs);
+            for (result, incremental_result) in
+                classification.results.iter().zip(&incremental.results)
+            {
+                let get_graphs = |result: &ClassificationResult| {
+                    result
+                        .counterexamples
+                        .iter()
+                        .map(|counterexample| {
+                            (counterexample.get_node_count(), counterexample.graph_index)
+                        })
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(get_graphs(result), get_graphs(incremental_result));
+            }
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let problem = LclProblem::new("MUU PPP", "MM PU UU").unwrap();
+        let classification = Classifier::new(1, 10).classify(&[problem]);
+        assert_eq!(classification.results.len(), 1);
+        assert!(!classification.results[0].counterexamples.is_empty());
+    }
+}
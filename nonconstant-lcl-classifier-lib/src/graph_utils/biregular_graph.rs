@@ -4,11 +4,15 @@ use super::{
     partition_is_regular, UndirectedGraph,
 };
 use crate::caches::{Cache, GraphCacheParams};
+use crate::lcl_problem::oriented::EdgeDirection;
 use itertools::Itertools;
 use log::info;
 use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
 
 /// Container for biregular graph.
 ///
@@ -144,6 +148,231 @@ impl BiregularGraph {
             .flatten()
             .collect()
     }
+
+    /// Generates nonisomorphic bipartite multigraphs whose partitions have the given degrees.
+    ///
+    /// `degrees_a` and `degrees_b` are the multisets of degrees of the nodes in `partition_a` and `partition_b`,
+    /// e.g. `[3, 1, 1, 1]` for an inner node with three leaves.
+    /// Graphs are generated with the maximum degrees of each partition,
+    /// and only the graphs with exactly the given degrees are kept.
+    /// The lower bounds of the degrees are 1 like in [`Self::generate`],
+    /// because genbg counts the degrees without parallel edges.
+    ///
+    /// The fields `degree_a` and `degree_b` of the results are the maximum degrees of the partitions.
+    ///
+    /// # Errors
+    /// Returns an error if the output of genbg and multig cannot be read as graphs of the given size.
+    pub fn generate_with_degrees(
+        degrees_a: &[usize],
+        degrees_b: &[usize],
+    ) -> Result<Vec<Self>, Box<dyn Error>> {
+        let (n1, n2) = (degrees_a.len(), degrees_b.len());
+        let edges: usize = degrees_a.iter().sum();
+        if n1 == 0 || n2 == 0 || edges != degrees_b.iter().sum::<usize>() {
+            return Ok(vec![]);
+        }
+        let max_a = *degrees_a.iter().max().unwrap();
+        let max_b = *degrees_b.iter().max().unwrap();
+        let max_degree = std::cmp::max(max_a, max_b);
+        let sorted_a = degrees_a.iter().copied().sorted().collect_vec();
+        let sorted_b = degrees_b.iter().copied().sorted().collect_vec();
+        let parts = num_cpus::get();
+
+        let graphs = (0usize..parts)
+            .into_par_iter()
+            .map(|i| -> Result<Vec<Self>, String> {
+                let graphs = generate_bipartite_multigraphs(
+                    n1, n2, 1, 1, max_a, max_b, i, parts, max_degree, edges, max_degree,
+                );
+                let graphs = multigraph_string_to_petgraph(graphs)
+                    .map_err(|error| format!("Invalid output of multig: {}", error))?;
+                let mut result = vec![];
+                for graph in graphs {
+                    if graph.node_count() != n1 + n2 {
+                        return Err(format!(
+                            "Multig returned a graph of {} nodes instead of {}",
+                            graph.node_count(),
+                            n1 + n2
+                        ));
+                    }
+                    let (partition_a, partition_b) = get_partitions(&graph, n1, n2);
+                    let graph = Self {
+                        graph,
+                        partition_a,
+                        partition_b,
+                        degree_a: max_a,
+                        degree_b: max_b,
+                    };
+                    if graph.get_degrees(&graph.partition_a) == sorted_a
+                        && graph.get_degrees(&graph.partition_b) == sorted_b
+                    {
+                        result.push(graph);
+                    }
+                }
+                Ok(result)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(graphs.into_iter().flatten().collect())
+    }
+
+    /// Returns the cycle of `k` active and `k` passive nodes of degree 2.
+    ///
+    /// Nodes alternate along the cycle, so that the active nodes are the even nodes `0, 2, ...`
+    /// and the passive nodes are the odd nodes `1, 3, ...`.
+    /// The cycle of one active and one passive node is a double edge.
+    pub fn cycle(k: usize) -> Self {
+        let edges = (0..2 * k).map(|i| (i as u32, ((i + 1) % (2 * k)) as u32));
+        let graph: UndirectedGraph = petgraph::graph::UnGraph::from_edges(edges);
+        Self {
+            graph,
+            partition_a: (0..2 * k).step_by(2).map(NodeIndex::new).collect(),
+            partition_b: (1..2 * k).step_by(2).map(NodeIndex::new).collect(),
+            degree_a: 2,
+            degree_b: 2,
+        }
+    }
+
+    /// Returns every orientation of the edges where each node has an allowed count of outgoing edges.
+    ///
+    /// An orientation gives the direction of each edge, indexed by the edge, with respect to its active node.
+    /// Active nodes have a count of outgoing edges in `active_out_degrees`,
+    /// and passive nodes in `passive_out_degrees`.
+    /// Orientations are not reduced by isomorphism, so their count grows exponentially with the count of edges.
+    pub fn get_orientations(
+        &self,
+        active_out_degrees: &[usize],
+        passive_out_degrees: &[usize],
+    ) -> Vec<Vec<EdgeDirection>> {
+        let active_nodes: HashSet<NodeIndex> = self.partition_a.iter().copied().collect();
+        // Active and passive endpoint of each edge.
+        let edges = self
+            .graph
+            .edge_references()
+            .map(|edge| {
+                if active_nodes.contains(&edge.source()) {
+                    (edge.source().index(), edge.target().index())
+                } else {
+                    (edge.target().index(), edge.source().index())
+                }
+            })
+            .collect_vec();
+
+        let node_count = self.graph.node_count();
+        let allowed = |node: usize| {
+            if active_nodes.contains(&NodeIndex::new(node)) {
+                active_out_degrees
+            } else {
+                passive_out_degrees
+            }
+        };
+        let mut remaining = vec![0; node_count];
+        for &(active, passive) in &edges {
+            remaining[active] += 1;
+            remaining[passive] += 1;
+        }
+
+        let mut orientations = vec![];
+        let mut orientation = vec![];
+        let mut out_degrees = vec![0; node_count];
+        orient_edges(
+            &edges,
+            &allowed,
+            &mut remaining,
+            &mut out_degrees,
+            &mut orientation,
+            &mut orientations,
+        );
+        orientations
+    }
+
+    /// Returns the graph where each passive node is replaced by an edge between its two neighbors.
+    ///
+    /// This is the reverse of the conversion of [`crate::GeneralLclProblem`] into a bipartite problem.
+    /// Active nodes keep their weights and are in the same order as in `partition_a`.
+    /// A passive node with both of its edges to the same active node becomes a self-loop.
+    ///
+    /// Returns `None` if the passive nodes do not have degree 2.
+    pub fn to_general_graph(&self) -> Option<UndirectedGraph> {
+        if self.degree_b != 2 {
+            return None;
+        }
+        let mut graph = UndirectedGraph::new_undirected();
+        let nodes: HashMap<NodeIndex, NodeIndex> = self
+            .partition_a
+            .iter()
+            .map(|&node| (node, graph.add_node(self.graph[node])))
+            .collect();
+        for &passive in &self.partition_b {
+            let neighbors = self.graph.neighbors(passive).collect_vec();
+            if neighbors.len() != 2 {
+                return None;
+            }
+            graph.add_edge(nodes[&neighbors[0]], nodes[&neighbors[1]], ());
+        }
+        Some(graph)
+    }
+
+    /// Returns the sorted degrees of `nodes`.
+    fn get_degrees(&self, nodes: &[NodeIndex]) -> Vec<usize> {
+        nodes
+            .iter()
+            .map(|&node| self.graph.edges(node).count())
+            .sorted()
+            .collect()
+    }
+}
+
+/// Orients the rest of `edges` in every way where each node ends up with an allowed count of outgoing edges.
+///
+/// `remaining` and `out_degrees` are the counts of unoriented and outgoing edges of each node.
+fn orient_edges<'a, F>(
+    edges: &[(usize, usize)],
+    allowed: &F,
+    remaining: &mut Vec<usize>,
+    out_degrees: &mut Vec<usize>,
+    orientation: &mut Vec<EdgeDirection>,
+    orientations: &mut Vec<Vec<EdgeDirection>>,
+) where
+    F: Fn(usize) -> &'a [usize],
+{
+    // A node is feasible if some allowed count is between the outgoing edges now and after orienting the rest.
+    let feasible = |node: usize, out_degrees: &Vec<usize>, remaining: &Vec<usize>| {
+        allowed(node).iter().any(|&allowed| {
+            out_degrees[node] <= allowed && allowed <= out_degrees[node] + remaining[node]
+        })
+    };
+
+    let (active, passive) = match edges.get(orientation.len()) {
+        Some(&edge) => edge,
+        None => {
+            orientations.push(orientation.clone());
+            return;
+        }
+    };
+    remaining[active] -= 1;
+    remaining[passive] -= 1;
+    for &direction in &[EdgeDirection::Outgoing, EdgeDirection::Incoming] {
+        let tail = match direction {
+            EdgeDirection::Outgoing => active,
+            EdgeDirection::Incoming => passive,
+        };
+        out_degrees[tail] += 1;
+        if feasible(active, out_degrees, remaining) && feasible(passive, out_degrees, remaining) {
+            orientation.push(direction);
+            orient_edges(
+                edges,
+                allowed,
+                remaining,
+                out_degrees,
+                orientation,
+                orientations,
+            );
+            orientation.pop();
+        }
+        out_degrees[tail] -= 1;
+    }
+    remaining[active] += 1;
+    remaining[passive] += 1;
 }
 
 #[cfg(test)]
@@ -175,6 +404,103 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generating_graphs_with_degrees() {
+        // An inner node of degree 3 and three leaves, joined by passive nodes of degree 2.
+        let graphs = BiregularGraph::generate_with_degrees(&[3, 1, 1, 1], &[2, 2, 2]).unwrap();
+        assert_eq!(graphs.len(), 1);
+        assert_eq!(graphs[0].degree_a, 3);
+
+        assert!(BiregularGraph::generate_with_degrees(&[3, 1], &[2, 2, 2])
+            .unwrap()
+            .is_empty());
+
+        // The complete bipartite graph K_{2,3}, and a graph with parallel edges
+        // where each active node has a double edge to its own passive node.
+        let graphs = BiregularGraph::generate_with_degrees(&[3, 3], &[2, 2, 2]).unwrap();
+        assert_eq!(graphs.len(), 2);
+        let has_parallel_edges = |graph: &BiregularGraph| {
+            graph
+                .partition_a
+                .iter()
+                .any(|&node| graph.graph.neighbors(node).unique().count() < 3)
+        };
+        assert_eq!(
+            graphs
+                .iter()
+                .filter(|graph| has_parallel_edges(graph))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        for k in 1..=4 {
+            let cycle = BiregularGraph::cycle(k);
+            assert_eq!(cycle.partition_a.len(), k);
+            assert_eq!(cycle.partition_b.len(), k);
+            assert_eq!(cycle.graph.edge_count(), 2 * k);
+            for node in cycle.partition_a.iter().chain(&cycle.partition_b) {
+                assert_eq!(cycle.graph.edges(*node).count(), 2);
+            }
+            assert_eq!(petgraph::algo::connected_components(&cycle.graph), 1);
+        }
+    }
+
+    #[test]
+    fn test_to_general_graph() {
+        // A triangle with a double edge, whose edges are passive nodes 3..=5.
+        let graph: UndirectedGraph = petgraph::graph::UnGraph::from_edges([
+            (0, 3),
+            (1, 3),
+            (1, 4),
+            (2, 4),
+            (2, 5),
+            (0, 5),
+            (0, 6),
+            (0, 6),
+        ]);
+        let graph = BiregularGraph {
+            graph,
+            partition_a: (0..3).map(NodeIndex::new).collect(),
+            partition_b: (3..7).map(NodeIndex::new).collect(),
+            degree_a: 2,
+            degree_b: 2,
+        };
+        let general = graph.to_general_graph().unwrap();
+        assert_eq!(general.node_count(), 3);
+        assert_eq!(general.edge_count(), 4);
+        assert!(general.contains_edge(NodeIndex::new(0), NodeIndex::new(0)));
+        assert!(general.contains_edge(NodeIndex::new(1), NodeIndex::new(2)));
+    }
+
+    #[test]
+    fn test_orientations() {
+        // A double edge between two nodes of degree 2.
+        let graph: UndirectedGraph = petgraph::graph::UnGraph::from_edges([(0, 1), (0, 1)]);
+        let graph = BiregularGraph {
+            graph,
+            partition_a: vec![NodeIndex::new(0)],
+            partition_b: vec![NodeIndex::new(1)],
+            degree_a: 2,
+            degree_b: 2,
+        };
+        assert_eq!(graph.get_orientations(&[0, 1, 2], &[0, 1, 2]).len(), 4);
+        assert_eq!(
+            graph.get_orientations(&[1], &[1]),
+            vec![
+                vec![EdgeDirection::Outgoing, EdgeDirection::Incoming],
+                vec![EdgeDirection::Incoming, EdgeDirection::Outgoing],
+            ]
+        );
+        assert_eq!(
+            graph.get_orientations(&[2], &[0]),
+            vec![vec![EdgeDirection::Outgoing, EdgeDirection::Outgoing]]
+        );
+        assert!(graph.get_orientations(&[2], &[1]).is_empty());
+    }
+
     /// The idea is from: https://github.com/petgraph/petgraph/issues/199#issuecomment-484077775
     fn _graph_eq<N, E, Ty, Ix>(
         a: &petgraph::Graph<N, E, Ty, Ix>,
@@ -0,0 +1,177 @@
+//! Canonical labeling of LCL problems.
+//!
+//! The canonical form of a problem is the lexicographically smallest pair
+//! (sorted active configurations, sorted passive configurations) over all relabelings.
+//! Instead of trying every permutation of the labels, values `0, 1, ...` are given
+//! to the labels one at a time and a branch is cut as soon as its lower bound
+//! cannot beat the best complete labeling found so far.
+//! Labels that can be swapped without changing the problem are tried only once per value.
+
+use itertools::Itertools;
+
+type Key = (Vec<Vec<u8>>, Vec<Vec<u8>>);
+
+/// Returns a permutation that maps the problem to its canonical form.
+///
+/// Label `l` is mapped to `permutation[l]`, just like in
+/// [`Configurations::map_labels`](super::configurations::Configurations::map_labels).
+/// The permutation covers labels `0..=label_max`.
+/// Used labels get values `0..u`, and unused labels get the rest in increasing order.
+pub(crate) fn canonical_permutation(active: &[Vec<u8>], passive: &[Vec<u8>]) -> Vec<u8> {
+    let mut search = Search::new(active, passive);
+    let used = search.used.clone();
+    let mut values = vec![0; search.label_count];
+    search.search(&mut values, 0, &used);
+
+    let mut permutation = search.best.map(|(_, values)| values).unwrap_or_default();
+    permutation.resize(search.label_count, 0);
+    let unused = (0..search.label_count).filter(|&l| !used.contains(&(l as u8)));
+    for (value, label) in (used.len()..).zip(unused) {
+        permutation[label] = value as u8;
+    }
+    permutation
+}
+
+struct Search<'a> {
+    active: &'a [Vec<u8>],
+    passive: &'a [Vec<u8>],
+    label_count: usize,
+    /// Labels that appear in some configuration, in increasing order.
+    used: Vec<u8>,
+    /// Smallest label that is interchangeable with the label.
+    class: Vec<u8>,
+    best: Option<(Key, Vec<u8>)>,
+}
+
+impl<'a> Search<'a> {
+    fn new(active: &'a [Vec<u8>], passive: &'a [Vec<u8>]) -> Self {
+        let used = active
+            .iter()
+            .chain(passive.iter())
+            .flatten()
+            .copied()
+            .sorted()
+            .dedup()
+            .collect_vec();
+        let label_count = used.last().map_or(0, |&l| l as usize + 1);
+
+        let mut search = Self {
+            active,
+            passive,
+            label_count,
+            used,
+            class: (0..label_count).map(|l| l as u8).collect(),
+            best: None,
+        };
+        search.find_interchangeable_labels();
+        search
+    }
+
+    /// Maps the labels with `values` and sorts the result.
+    fn key(&self, values: &[u8]) -> Key {
+        let map = |data: &[Vec<u8>]| {
+            data.iter()
+                .map(|configuration| {
+                    configuration
+                        .iter()
+                        .map(|&l| values[l as usize])
+                        .sorted_unstable()
+                        .collect_vec()
+                })
+                .sorted_unstable()
+                .collect_vec()
+        };
+        (map(self.active), map(self.passive))
+    }
+
+    /// Groups labels that can be swapped without changing the problem.
+    ///
+    /// Only labels with the same occurrence signature are compared.
+    /// If both `(a b)` and `(b c)` keep the problem unchanged, so does `(a c)`,
+    /// thus every class can be represented by its smallest label.
+    fn find_interchangeable_labels(&mut self) {
+        let signatures = self
+            .used
+            .iter()
+            .map(|&label| {
+                let counts = |data: &[Vec<u8>]| {
+                    data.iter()
+                        .map(|c| c.iter().filter(|&&l| l == label).count())
+                        .sorted_unstable()
+                        .collect_vec()
+                };
+                (counts(self.active), counts(self.passive))
+            })
+            .collect_vec();
+
+        let identity = (0..self.label_count).map(|l| l as u8).collect_vec();
+        let original = self.key(&identity);
+
+        for (i, &a) in self.used.iter().enumerate() {
+            if self.class[a as usize] != a {
+                continue;
+            }
+            for (j, &b) in self.used.iter().enumerate().skip(i + 1) {
+                if self.class[b as usize] != b || signatures[i] != signatures[j] {
+                    continue;
+                }
+                let mut swapped = identity.clone();
+                swapped.swap(a as usize, b as usize);
+                if self.key(&swapped) == original {
+                    self.class[b as usize] = a;
+                }
+            }
+        }
+    }
+
+    /// Gives value `next` to each label of `unassigned` in turn and continues the search.
+    ///
+    /// Unassigned labels are mapped to the smallest value they can still get,
+    /// which gives a lower bound for every completion of the branch.
+    fn search(&mut self, values: &mut Vec<u8>, next: u8, unassigned: &[u8]) {
+        if unassigned.is_empty() {
+            let key = self.key(values);
+            let improves = match &self.best {
+                Some((best, _)) => key < *best,
+                None => true,
+            };
+            if improves {
+                self.best = Some((key, values.clone()));
+            }
+            return;
+        }
+
+        let mut branches = vec![];
+        let mut tried_classes = vec![];
+        for &label in unassigned {
+            let class = self.class[label as usize];
+            if tried_classes.contains(&class) {
+                continue;
+            }
+            tried_classes.push(class);
+
+            for &l in unassigned {
+                values[l as usize] = next.saturating_add(1);
+            }
+            values[label as usize] = next;
+            branches.push((self.key(values), label));
+        }
+        branches.sort_unstable();
+
+        for (bound, label) in branches {
+            if matches!(&self.best, Some((best, _)) if bound >= *best) {
+                break;
+            }
+            let rest = unassigned
+                .iter()
+                .copied()
+                .filter(|&l| l != label)
+                .collect_vec();
+            for &l in rest.iter() {
+                values[l as usize] = next.saturating_add(1);
+            }
+            values[label as usize] = next;
+            self.search(values, next.saturating_add(1), &rest);
+        }
+    }
+}
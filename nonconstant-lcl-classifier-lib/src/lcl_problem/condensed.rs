@@ -0,0 +1,376 @@
+//! Condensed configuration notation in the style of the Round eliminator.
+//!
+//! A configuration is a sequence of atoms.
+//! An atom is either a single label `A` or a group of labels `[BC]`,
+//! and it can be followed by an exponent `^2`.
+//! A label is a single alphanumeric character, or a longer name in parentheses such as `(out)`.
+//! A label with exponent `k` stands for `k` copies of the label.
+//! A group with exponent `k` stands for every multiset of size `k` over the labels in the group.
+//! Whitespace between atoms is ignored.
+//! On a single line whitespace may also separate configurations, see [`super::configurations::Configurations::from_string`].
+//!
+//! For example `A[BC]^2C` expands to configurations `ABBC`, `ABCC` and `ACCC`.
+
+use super::parse_error::LclParseError;
+use itertools::Itertools;
+use std::{collections::HashMap, convert::TryFrom, iter::Peekable, str::Chars};
+
+struct Atom {
+    labels: Vec<u8>,
+    exponent: usize,
+}
+
+/// Parses one condensed configuration and expands it into plain configurations.
+///
+/// Labels are mapped with `label_map` and new labels are added to it.
+/// If `width` is given, the configuration must have exactly that many labels.
+/// `position` is only used for error reporting.
+pub(crate) fn expand_configuration(
+    token: &str,
+    position: usize,
+    width: Option<usize>,
+    label_map: &mut HashMap<String, u8>,
+) -> Result<Vec<Vec<u8>>, LclParseError> {
+    let atoms = parse_atoms(token, position, label_map)?;
+
+    let found = atoms.iter().map(|atom| atom.exponent).sum();
+    let expected = width.unwrap_or(found);
+    if found != expected {
+        return Err(LclParseError::MixedWidths {
+            token: token.to_string(),
+            position,
+            expected,
+            found,
+        });
+    }
+
+    Ok(atoms
+        .iter()
+        .map(|atom| {
+            atom.labels
+                .iter()
+                .copied()
+                .combinations_with_replacement(atom.exponent)
+                .collect_vec()
+        })
+        .multi_cartesian_product()
+        .map(|parts| parts.concat())
+        .collect_vec())
+}
+
+/// Returns the number of labels in the condensed configuration `token`.
+///
+/// `position` is only used for error reporting.
+pub(crate) fn get_width(token: &str, position: usize) -> Result<usize, LclParseError> {
+    let atoms = parse_atoms(token, position, &mut HashMap::new())?;
+    Ok(atoms.iter().map(|atom| atom.exponent).sum())
+}
+
+fn parse_atoms(
+    token: &str,
+    position: usize,
+    label_map: &mut HashMap<String, u8>,
+) -> Result<Vec<Atom>, LclParseError> {
+    let bad_character = |character| LclParseError::BadCharacter {
+        token: token.to_string(),
+        position,
+        character,
+    };
+
+    let mut atoms = vec![];
+    let mut chars = token.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let labels = match c {
+            c if c.is_whitespace() => continue,
+            '[' => {
+                let mut labels = vec![];
+                loop {
+                    match chars.next() {
+                        Some(']') if !labels.is_empty() => break,
+                        Some(c) if c.is_whitespace() => continue,
+                        Some(c) => {
+                            let name = parse_label_name(c, &mut chars).map_err(bad_character)?;
+                            let label = map_label(name, token, position, label_map)?;
+                            if !labels.contains(&label) {
+                                labels.push(label);
+                            }
+                        }
+                        None => return Err(bad_character('[')),
+                    }
+                }
+                labels
+            }
+            c => {
+                let name = parse_label_name(c, &mut chars).map_err(bad_character)?;
+                vec![map_label(name, token, position, label_map)?]
+            }
+        };
+
+        let exponent = if chars.peek() == Some(&'^') {
+            chars.next();
+            parse_exponent(&mut chars).map_err(bad_character)?
+        } else {
+            1
+        };
+
+        atoms.push(Atom { labels, exponent });
+    }
+
+    Ok(atoms)
+}
+
+/// Reads a label name starting with `first`. On failure returns the offending character.
+///
+/// A name is either a single alphanumeric character,
+/// or alphanumeric characters and underscores in parentheses.
+fn parse_label_name(first: char, chars: &mut Peekable<Chars>) -> Result<String, char> {
+    if first.is_alphanumeric() {
+        return Ok(first.to_string());
+    }
+    if first != '(' {
+        return Err(first);
+    }
+
+    let mut name = String::new();
+    loop {
+        match chars.next() {
+            Some(')') if !name.is_empty() => return Ok(name),
+            Some(c) if c.is_alphanumeric() || c == '_' => name.push(c),
+            Some(c) => return Err(c),
+            None => return Err('('),
+        }
+    }
+}
+
+/// Reads the digits of an exponent. On failure returns the offending character.
+fn parse_exponent(chars: &mut Peekable<Chars>) -> Result<usize, char> {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+
+    match digits.parse::<usize>() {
+        Ok(0) => Err('0'),
+        Ok(exponent) => Ok(exponent),
+        Err(_) => Err('^'),
+    }
+}
+
+fn map_label(
+    label: String,
+    token: &str,
+    position: usize,
+    label_map: &mut HashMap<String, u8>,
+) -> Result<u8, LclParseError> {
+    if let Some(value) = label_map.get(&label) {
+        return Ok(*value);
+    }
+
+    let new_value = u8::try_from(label_map.len()).map_err(|_| LclParseError::TooManyLabels {
+        token: token.to_string(),
+        position,
+    })?;
+    label_map.insert(label, new_value);
+    Ok(new_value)
+}
+
+/// Groups configurations into condensed rows.
+///
+/// Each row is a sequence of slots and each slot is a group of labels.
+/// Consecutive rows are merged when they differ in exactly one slot and every slot before it
+/// is a single label. This way expanding the rows in order gives back exactly `data`.
+fn condense(data: &[Vec<u8>]) -> Vec<Vec<Vec<u8>>> {
+    let mut rows = data
+        .iter()
+        .map(|configuration| configuration.iter().map(|&label| vec![label]).collect_vec())
+        .collect_vec();
+
+    loop {
+        let mut merged: Vec<Vec<Vec<u8>>> = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
+            if let Some(last) = merged.last_mut() {
+                if let Some(slot) = mergeable_slot(last, row) {
+                    last[slot].extend_from_slice(&row[slot]);
+                    continue;
+                }
+            }
+            merged.push(row.clone());
+        }
+
+        if merged.len() == rows.len() {
+            return merged;
+        }
+        rows = merged;
+    }
+}
+
+fn mergeable_slot(first: &[Vec<u8>], second: &[Vec<u8>]) -> Option<usize> {
+    if first.len() != second.len() {
+        return None;
+    }
+
+    let (slot,) = (0..first.len())
+        .filter(|&i| first[i] != second[i])
+        .collect_tuple()?;
+
+    let singletons_before = first[..slot].iter().all(|group| group.len() == 1);
+    let disjoint = second[slot]
+        .iter()
+        .all(|label| !first[slot].contains(label));
+
+    if singletons_before && disjoint {
+        Some(slot)
+    } else {
+        None
+    }
+}
+
+/// Formats configurations in the condensed notation on a single line.
+///
+/// Configurations are separated with a space.
+/// `label_name` returns the name of a label.
+/// Names longer than one character are written in parentheses.
+pub(crate) fn format_configurations<F>(data: &[Vec<u8>], label_name: F) -> String
+where
+    F: Fn(u8) -> String,
+{
+    let label_name = |label| {
+        let name = label_name(label);
+        if name.chars().count() == 1 {
+            name
+        } else {
+            format!("({})", name)
+        }
+    };
+
+    condense(data)
+        .iter()
+        .map(|row| {
+            row.iter()
+                .group_by(|group| *group)
+                .into_iter()
+                .map(|(group, run)| {
+                    let count = run.count();
+                    if group.len() > 1 {
+                        let group_string =
+                            format!("[{}]", group.iter().map(|&l| label_name(l)).join(""));
+                        return group_string.repeat(count);
+                    }
+                    let name = label_name(group[0]);
+                    let exponent_string = format!("{}^{}", name, count);
+                    if exponent_string.len() < name.len() * count {
+                        exponent_string
+                    } else {
+                        name.repeat(count)
+                    }
+                })
+                .join("")
+        })
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(token: &str) -> Result<Vec<Vec<u8>>, LclParseError> {
+        let mut label_map = HashMap::new();
+        expand_configuration(token, 0, None, &mut label_map)
+    }
+
+    #[test]
+    fn test_expand_configuration() {
+        assert_eq!(expand("AB^3").unwrap(), vec![vec![0, 1, 1, 1]]);
+        assert_eq!(
+            expand("A [BC]^2 C").unwrap(),
+            vec![vec![0, 1, 1, 2], vec![0, 1, 2, 2], vec![0, 2, 2, 2]]
+        );
+        assert_eq!(
+            expand("[AB][AB]").unwrap(),
+            vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![1, 1]]
+        );
+    }
+
+    #[test]
+    fn test_expand_configuration_with_long_names() {
+        let mut label_map = HashMap::new();
+        let expanded =
+            expand_configuration("(M1)[(out)A]^2 (M1)", 0, None, &mut label_map).unwrap();
+        assert_eq!(
+            expanded,
+            vec![vec![0, 1, 1, 0], vec![0, 1, 2, 0], vec![0, 2, 2, 0]]
+        );
+        assert_eq!(label_map["M1"], 0);
+        assert_eq!(label_map["out"], 1);
+        assert_eq!(label_map["A"], 2);
+
+        let names = ["M1", "out", "A"];
+        assert_eq!(
+            format_configurations(&expanded, |l| names[l as usize].to_string()),
+            "(M1)(out)[(out)A](M1) (M1)AA(M1)"
+        );
+    }
+
+    #[test]
+    fn test_expand_configuration_errors() {
+        assert!(matches!(
+            expand("A[BC"),
+            Err(LclParseError::BadCharacter { character: '[', .. })
+        ));
+        assert!(matches!(
+            expand("A[]"),
+            Err(LclParseError::BadCharacter { character: ']', .. })
+        ));
+        assert!(matches!(
+            expand("A^"),
+            Err(LclParseError::BadCharacter { character: '^', .. })
+        ));
+        assert!(matches!(
+            expand("A^0"),
+            Err(LclParseError::BadCharacter { character: '0', .. })
+        ));
+        assert!(matches!(
+            expand("A(out"),
+            Err(LclParseError::BadCharacter { character: '(', .. })
+        ));
+        assert!(matches!(
+            expand("A(o-t)"),
+            Err(LclParseError::BadCharacter { character: '-', .. })
+        ));
+    }
+
+    #[test]
+    fn test_format_configurations() {
+        let name = |l: u8| ((b'A' + l) as char).to_string();
+        let data = vec![vec![0, 0, 1], vec![0, 0, 2], vec![1, 1, 1, 1]];
+        assert_eq!(format_configurations(&data, name), "AA[BC] B^4");
+
+        let data = vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![1, 1]];
+        assert_eq!(format_configurations(&data, name), "[AB][AB]");
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let name = |l: u8| ((b'A' + l) as char).to_string();
+        let data = vec![
+            vec![0, 1, 1],
+            vec![0, 1, 2],
+            vec![0, 2, 2],
+            vec![2, 0, 1],
+            vec![2, 2, 2],
+        ];
+        let formatted = format_configurations(&data, name);
+
+        let mut label_map = HashMap::new();
+        let parsed = formatted
+            .split_ascii_whitespace()
+            .map(|token| expand_configuration(token, 0, None, &mut label_map).unwrap())
+            .concat();
+        assert_eq!(parsed, data);
+    }
+}
@@ -1,11 +1,15 @@
+use super::{condensed, parse_error::LclParseError};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
-    error::Error,
+    collections::{HashMap, HashSet, VecDeque},
     iter::FromIterator,
+    ops::Range,
 };
 
+/// Set of labels where label `l` belongs to the set if bit `l` is set.
+pub type LabelSet = u64;
+
 /// A container for set of configurations that are used to define an LCL problem.
 ///
 /// A configuration is a multiset of labels.
@@ -21,46 +25,58 @@ pub struct Configurations {
 impl Configurations {
     /// Creates Configuration instance from given `encoding` using given `label_map`.
     ///
-    /// Encoding is formated as a multirow string where each configuration is separated with linebreak and each label is separated with space.
+    /// Encoding is either a single line where configurations are separated with whitespace,
+    /// or a multirow string where each configuration is on its own line.
+    /// In the multirow form, whitespace inside a configuration is ignored.
     /// Each configuration has to be equally long.
     ///
+    /// On a single line in the condensed notation, whitespace separates configurations only
+    /// where the configurations are equally long, and otherwise it is ignored.
+    /// The configurations are made as short as possible, so `A [BC]^2 C` is a single configuration
+    /// and `A B^3` is the configuration `ABBB`, while `A^2 BB` is the configurations `AA` and `BB`.
+    ///
+    /// Configurations can be written in the condensed notation of the Round eliminator:
+    /// `[BC]` is a group of labels and `^k` repeats the previous label or group `k` times.
+    /// A label is a single alphanumeric character, or a longer name in parentheses, e.g. `(M1)`.
+    /// A condensed configuration expands into every configuration it stands for,
+    /// e.g. `A[BC]^2` is the same as `ABB ABC ACC` and `AB^3` is the same as `ABBB`.
+    ///
     /// Internally each label is mapped to unsigned integers and then saved in vector as `u8`.
     /// By default, labels increase starting from 0.
+    /// The keys of `label_map` are label names without parentheses.
     /// A label_map is supposed to be given if it is desired to have multiple [`Configurations`] instances using same mapping of labels.
     ///
+    /// # Errors
+    /// Returns [`LclParseError`] if the encoding has no configurations, the configurations differ in width,
+    /// a configuration is malformed or there are more labels than fit in `u8`.
     ///
     /// # Example
     /// ```
     /// use std::collections::HashMap;
     /// # use nonconstant_lcl_classifier_lib::Configurations;
-    /// let mut label_map = HashMap::<char, u8>::new();
+    /// let mut label_map = HashMap::<String, u8>::new();
     /// let configurations = Configurations::from_string("ABC AAB CCC", &mut label_map).unwrap();
+    ///
+    /// let mut label_map = HashMap::<String, u8>::new();
+    /// let condensed = Configurations::from_string("A [BC]^2\nC^3", &mut label_map).unwrap();
+    /// assert_eq!(condensed.get_configuration_count(), 4);
     /// ```
     pub fn from_string(
         encoding: &str,
-        label_map: &mut HashMap<char, u8>,
-    ) -> Result<Self, Box<dyn Error>> {
-        let configurations_vec_str = encoding.split_ascii_whitespace().collect_vec();
-        let width = configurations_vec_str.first().unwrap().len();
-
-        let all_same_length = configurations_vec_str.iter().all(|l| l.len() == width);
-        assert!(all_same_length);
+        label_map: &mut HashMap<String, u8>,
+    ) -> Result<Self, LclParseError> {
+        let configurations_vec_str = split_equal_width_configurations(encoding)?;
+        if configurations_vec_str.is_empty() {
+            return Err(LclParseError::EmptyPartition);
+        }
 
+        let mut width = None;
         let mut configurations = vec![];
-        for configuration_str in configurations_vec_str {
-            let mut configuration = Vec::<u8>::new();
-            for label in configuration_str.chars() {
-                //TODO add support for compact notation from the Round eliminator
-                let value = if label_map.contains_key(&label) {
-                    *label_map.get(&label).unwrap()
-                } else {
-                    let new_value = label_map.len() as u8;
-                    label_map.insert(label, new_value);
-                    new_value
-                };
-                configuration.push(value)
-            }
-            configurations.push(configuration);
+        for (position, configuration_str) in configurations_vec_str.into_iter().enumerate() {
+            let expanded =
+                condensed::expand_configuration(configuration_str, position, width, label_map)?;
+            width = expanded.first().map(|configuration| configuration.len());
+            configurations.extend(expanded);
         }
 
         Ok(Configurations {
@@ -68,15 +84,78 @@ impl Configurations {
         })
     }
 
-    pub fn from_configuration_data(
+    /// Creates a Configurations instance for each degree from `encoding` using given `label_map`.
+    ///
+    /// The format is the same as in [`Configurations::from_string`],
+    /// except that configurations may have different widths.
+    /// The configurations are grouped by their width, and the groups are returned in increasing order of width.
+    ///
+    /// # Errors
+    /// Returns [`LclParseError`] if the encoding has no configurations, a configuration is malformed
+    /// or there are more labels than fit in `u8`.
+    pub fn from_string_by_degree(
+        encoding: &str,
+        label_map: &mut HashMap<String, u8>,
+    ) -> Result<Vec<Self>, LclParseError> {
+        let mut configurations = vec![];
+        for (position, configuration_str) in split_configurations(encoding).into_iter().enumerate()
+        {
+            configurations.extend(condensed::expand_configuration(
+                configuration_str,
+                position,
+                None,
+                label_map,
+            )?);
+        }
+        Self::from_configuration_data_by_degree(configurations)
+    }
+
+    /// Creates a Configurations instance for each degree from already mapped labels.
+    ///
+    /// The configurations are grouped by their width, and the groups are returned in increasing order of width.
+    ///
+    /// # Errors
+    /// Returns [`LclParseError`] if `configuration_data` is empty or contains an empty configuration.
+    pub fn from_configuration_data_by_degree(
         configuration_data: Vec<Vec<u8>>,
-    ) -> Result<Self, Box<dyn Error>> {
-        assert!(!configuration_data.is_empty());
-        assert!(!configuration_data[0].is_empty());
+    ) -> Result<Vec<Self>, LclParseError> {
+        if configuration_data.is_empty() || configuration_data.iter().any(|c| c.is_empty()) {
+            return Err(LclParseError::EmptyPartition);
+        }
+        Ok(configuration_data
+            .into_iter()
+            .into_group_map_by(|configuration| configuration.len())
+            .into_iter()
+            .sorted_by_key(|(width, _)| *width)
+            .map(|(_, data)| Configurations { data })
+            .collect())
+    }
 
-        let width = configuration_data[0].len();
-        let all_same_length = configuration_data.iter().all(|l| l.len() == width);
-        assert!(all_same_length);
+    /// Creates Configuration instance from already mapped labels.
+    ///
+    /// # Errors
+    /// Returns [`LclParseError`] if `configuration_data` is empty, its first configuration is empty
+    /// or the configurations differ in width.
+    pub fn from_configuration_data(
+        configuration_data: Vec<Vec<u8>>,
+    ) -> Result<Self, LclParseError> {
+        let width = configuration_data
+            .first()
+            .map(|configuration| configuration.len())
+            .filter(|&width| width > 0)
+            .ok_or(LclParseError::EmptyPartition)?;
+
+        if let Some((position, configuration)) = configuration_data
+            .iter()
+            .find_position(|configuration| configuration.len() != width)
+        {
+            return Err(LclParseError::MixedWidths {
+                token: configuration.iter().join(" "),
+                position,
+                expected: width,
+                found: configuration.len(),
+            });
+        }
 
         Ok(Configurations {
             data: configuration_data,
@@ -109,6 +188,18 @@ impl Configurations {
         &mut self.data
     }
 
+    /// Returns the configurations in the condensed notation on a single line.
+    ///
+    /// `label_name` returns the name of a label.
+    /// Parsing the result with [`Configurations::from_string`] gives back the same configurations
+    /// in the same order, provided that the names map back to the same labels.
+    pub fn to_condensed_string<F>(&self, label_name: F) -> String
+    where
+        F: Fn(u8) -> String,
+    {
+        condensed::format_configurations(&self.data, label_name)
+    }
+
     /// Returns all unique permutations of labels, in each configuration.
     ///
     /// # Example
@@ -135,7 +226,7 @@ impl Configurations {
     /// ```
     /// use std::collections::HashMap;
     /// # use nonconstant_lcl_classifier_lib::Configurations;
-    /// let mut label_map = HashMap::<char, u8>::new();
+    /// let mut label_map = HashMap::<String, u8>::new();
     /// let configurations = Configurations::from_string("ABC", &mut label_map).unwrap();
     /// let permutations = configurations.get_permutations();
     /// let correct = vec![
@@ -204,6 +295,105 @@ impl Configurations {
         });
     }
 
+    /// Checks if `label` can be replaced by `dominating` in every configuration.
+    ///
+    /// That is, replacing every occurrence of `label` with `dominating` in a configuration
+    /// containing `label` gives another configuration of this set.
+    pub fn is_label_dominated(&self, label: u8, dominating: u8) -> bool {
+        let sorted = |configuration: Vec<u8>| configuration.into_iter().sorted().collect_vec();
+        let configurations: HashSet<Vec<u8>> = self.data.iter().cloned().map(sorted).collect();
+
+        self.data
+            .iter()
+            .filter(|configuration| configuration.contains(&label))
+            .all(|configuration| {
+                let replaced = configuration
+                    .iter()
+                    .map(|&l| if l == label { dominating } else { l })
+                    .collect_vec();
+                configurations.contains(&sorted(replaced))
+            })
+    }
+
+    /// Returns the maximal configurations of label sets where every choice of labels is a configuration.
+    ///
+    /// A configuration of sets `S_1 ... S_d` is included if picking any label from each `S_i`
+    /// gives a configuration of this set, and no `S_i` can be extended with another label.
+    /// This is the universal step of round elimination.
+    ///
+    /// Sets are found by combining two known configurations of sets, so that one pair of sets is united
+    /// and the other pairs are intersected, until no new maximal configuration is found.
+    /// Each returned configuration is sorted.
+    ///
+    /// # Panics
+    /// Panics if a label does not fit in [`LabelSet`].
+    pub fn get_maximal_set_configurations(&self) -> Vec<Vec<LabelSet>> {
+        assert!(
+            self.get_labels()
+                .iter()
+                .all(|&label| (label as u32) < LabelSet::BITS),
+            "Labels should be less than {}",
+            LabelSet::BITS
+        );
+        let degree = self.get_labels_per_configuration();
+
+        let mut maximal: Vec<Vec<LabelSet>> = vec![];
+        let mut queue = VecDeque::new();
+        for configuration in &self.data {
+            let sets = configuration
+                .iter()
+                .map(|&label| 1 << label)
+                .sorted()
+                .collect_vec();
+            if add_if_maximal(&mut maximal, sets.clone()) {
+                queue.push_back(sets);
+            }
+        }
+
+        // Each new configuration is combined with the known ones in both orders.
+        // Combining with a dominated configuration gives nothing new, so those can be skipped.
+        while let Some(x) = queue.pop_front() {
+            if !maximal.contains(&x) {
+                continue;
+            }
+            for y in maximal.clone() {
+                for (x, y) in [(&x, &y), (&y, &x)].iter() {
+                    for permutation in (0..degree).permutations(degree) {
+                        let intersections =
+                            (0..degree).map(|k| x[k] & y[permutation[k]]).collect_vec();
+                        for united in 0..degree {
+                            let mut sets = intersections.clone();
+                            sets[united] = x[united] | y[permutation[united]];
+                            if sets.contains(&0) {
+                                continue;
+                            }
+                            sets.sort_unstable();
+                            if add_if_maximal(&mut maximal, sets.clone()) {
+                                queue.push_back(sets);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        maximal.sort();
+        maximal
+    }
+
+    /// Checks if a configuration can be formed by picking one label from each of `sets`.
+    ///
+    /// This is the existential step of round elimination.
+    pub fn contains_choice(&self, sets: &[LabelSet]) -> bool {
+        self.data.iter().any(|configuration| {
+            configuration.len() == sets.len()
+                && has_perfect_matching(sets.len(), |i, j| {
+                    (configuration[i] as u32) < LabelSet::BITS
+                        && sets[j] & (1 << configuration[i]) != 0
+                })
+        })
+    }
+
     /// Generate powerset of configurations with specified degree and alphabet.
     pub fn generate_powerset(degree: usize, alphabet_length: u8) -> Vec<Configurations> {
         let alphabet = (0..alphabet_length).collect_vec();
@@ -232,6 +422,144 @@ impl Configurations {
     }
 }
 
+/// Splits `encoding` into configurations, see [`Configurations::from_string`].
+///
+/// On a single line each token is a configuration.
+/// Tokens are separated with whitespace that is not inside a group of labels.
+pub(crate) fn split_configurations(encoding: &str) -> Vec<&str> {
+    if encoding.trim().contains('\n') {
+        encoding
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect_vec()
+    } else {
+        split_tokens(encoding)
+            .into_iter()
+            .map(|range| &encoding[range])
+            .collect_vec()
+    }
+}
+
+/// Splits `encoding` into configurations of equal width, see [`Configurations::from_string`].
+///
+/// On a single line in the condensed notation, consecutive tokens are joined into configurations.
+/// The configurations get the smallest width that is possible,
+/// so tokens of equal width stay separate, while e.g. `A [BC]^2 C` is a single configuration.
+/// Otherwise this is the same as [`split_configurations`].
+///
+/// # Errors
+/// Returns [`LclParseError`] if a token is malformed.
+pub(crate) fn split_equal_width_configurations(encoding: &str) -> Result<Vec<&str>, LclParseError> {
+    if encoding.trim().contains('\n') || !encoding.contains(&['[', '^'][..]) {
+        return Ok(split_configurations(encoding));
+    }
+
+    let tokens = split_tokens(encoding);
+    let mut ends = Vec::with_capacity(tokens.len());
+    let mut total = 0;
+    for (position, range) in tokens.iter().enumerate() {
+        total += condensed::get_width(&encoding[range.clone()], position)?;
+        ends.push(total);
+    }
+
+    // The widths of the tokens are positive, so `ends` is increasing and ends with `total`.
+    let width = ends
+        .iter()
+        .copied()
+        .find(|&width| {
+            total % width == 0
+                && ends.iter().filter(|&end| end % width == 0).count() == total / width
+        })
+        .unwrap_or(total);
+
+    let mut configurations = vec![];
+    let mut start = 0;
+    for (i, end) in ends.into_iter().enumerate() {
+        if end % width == 0 {
+            configurations.push(&encoding[tokens[start].start..tokens[i].end]);
+            start = i + 1;
+        }
+    }
+    Ok(configurations)
+}
+
+/// Returns the byte ranges of the tokens of `line`,
+/// which are separated with whitespace that is not inside a group of labels.
+fn split_tokens(line: &str) -> Vec<Range<usize>> {
+    let mut tokens = vec![];
+    let mut start = None;
+    let mut in_group = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '[' => in_group = true,
+            ']' => in_group = false,
+            c if c.is_whitespace() && !in_group => {
+                if let Some(start) = start.take() {
+                    tokens.push(start..i);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(start) = start {
+        tokens.push(start..line.len());
+    }
+    tokens
+}
+
+/// Adds `sets` to `maximal` and removes the configurations it dominates, unless it is dominated itself.
+///
+/// Returns `true` if `sets` was added.
+fn add_if_maximal(maximal: &mut Vec<Vec<LabelSet>>, sets: Vec<LabelSet>) -> bool {
+    if maximal.iter().any(|other| is_dominated(&sets, other)) {
+        return false;
+    }
+    maximal.retain(|other| !is_dominated(other, &sets));
+    maximal.push(sets);
+    true
+}
+
+/// Checks if the sets of `sets` are subsets of the sets of `other` in some order.
+fn is_dominated(sets: &[LabelSet], other: &[LabelSet]) -> bool {
+    has_perfect_matching(sets.len(), |i, j| sets[i] & !other[j] == 0)
+}
+
+/// Checks if every left vertex of a bipartite graph with `size` vertices on both sides
+/// can be matched to a distinct right vertex, where `edge(i, j)` tells if `i` and `j` are adjacent.
+fn has_perfect_matching<F>(size: usize, edge: F) -> bool
+where
+    F: Fn(usize, usize) -> bool,
+{
+    fn augment<F: Fn(usize, usize) -> bool>(
+        left: usize,
+        edge: &F,
+        matched: &mut Vec<Option<usize>>,
+        visited: &mut Vec<bool>,
+    ) -> bool {
+        for right in 0..matched.len() {
+            if visited[right] || !edge(left, right) {
+                continue;
+            }
+            visited[right] = true;
+            let free = match matched[right] {
+                None => true,
+                Some(other) => augment(other, edge, matched, visited),
+            };
+            if free {
+                matched[right] = Some(left);
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut matched = vec![None; size];
+    (0..size).all(|left| augment(left, &edge, &mut matched, &mut vec![false; size]))
+}
+
 //impl PartialEq for Configurations {
 //   fn eq(&self, other: &Self) -> bool {
 //       self.data == other.data
@@ -257,9 +585,9 @@ mod tests {
     #[test]
     fn test_eq() {
         let mut label_map = HashMap::new();
-        label_map.insert('A', 0u8);
-        label_map.insert('B', 1u8);
-        label_map.insert('C', 2u8);
+        label_map.insert("A".to_string(), 0u8);
+        label_map.insert("B".to_string(), 1u8);
+        label_map.insert("C".to_string(), 2u8);
 
         let c0 = Configurations::from_string("ABB CCC", &mut label_map).unwrap();
         let c1 = Configurations::from_string("AB BC CC", &mut label_map).unwrap();
@@ -272,9 +600,9 @@ mod tests {
     #[test]
     fn test_sort() {
         let mut label_map = HashMap::new();
-        label_map.insert('M', 0u8);
-        label_map.insert('U', 1u8);
-        label_map.insert('P', 2u8);
+        label_map.insert("M".to_string(), 0u8);
+        label_map.insert("U".to_string(), 1u8);
+        label_map.insert("P".to_string(), 2u8);
 
         let mut c0 = Configurations::from_string("MUU PPP", &mut label_map).unwrap();
         let mut c1 = Configurations::from_string("UMU PPP", &mut label_map).unwrap();
@@ -304,4 +632,151 @@ mod tests {
         assert_ne!(c3, c1);
         assert_ne!(c3, c2);
     }
+
+    #[test]
+    fn test_is_label_dominated() {
+        let c = Configurations::from_configuration_data(vec![vec![0, 1], vec![0, 0], vec![2, 0]])
+            .unwrap();
+        assert!(c.is_label_dominated(1, 0));
+        assert!(c.is_label_dominated(2, 1));
+        assert!(!c.is_label_dominated(0, 1));
+        assert!(!c.is_label_dominated(0, 2));
+        // A label that does not appear is dominated by any label.
+        assert!(c.is_label_dominated(3, 2));
+    }
+
+    #[test]
+    fn test_maximal_set_configurations() {
+        let c = Configurations::from_configuration_data(vec![vec![0, 0], vec![0, 1]]).unwrap();
+        assert_eq!(c.get_maximal_set_configurations(), vec![vec![0b01, 0b11]]);
+
+        // Sinkless orientation on the active side: at least one outgoing edge.
+        let c = Configurations::from_configuration_data(vec![
+            vec![0, 1, 1],
+            vec![0, 0, 1],
+            vec![0, 0, 0],
+        ])
+        .unwrap();
+        assert_eq!(
+            c.get_maximal_set_configurations(),
+            vec![vec![0b01, 0b11, 0b11]]
+        );
+
+        let c = Configurations::from_configuration_data(vec![vec![0, 1], vec![2, 2]]).unwrap();
+        assert_eq!(
+            c.get_maximal_set_configurations(),
+            vec![vec![0b001, 0b010], vec![0b100, 0b100]]
+        );
+    }
+
+    #[test]
+    fn test_contains_choice() {
+        let c =
+            Configurations::from_configuration_data(vec![vec![0, 1, 1], vec![2, 2, 2]]).unwrap();
+        assert!(c.contains_choice(&[0b010, 0b011, 0b110]));
+        assert!(c.contains_choice(&[0b100, 0b100, 0b111]));
+        assert!(!c.contains_choice(&[0b001, 0b001, 0b110]));
+        assert!(!c.contains_choice(&[0b001, 0b010]));
+    }
+
+    #[test]
+    fn test_from_string_joins_condensed_tokens() {
+        let mut label_map = HashMap::new();
+        let configurations = Configurations::from_string("A [BC]^2 C", &mut label_map).unwrap();
+        assert_eq!(
+            configurations.get_configurations(),
+            &vec![vec![0, 1, 1, 2], vec![0, 1, 2, 2], vec![0, 2, 2, 2]]
+        );
+
+        let mut label_map = HashMap::new();
+        let configurations = Configurations::from_string("A^2 [A B]B", &mut label_map).unwrap();
+        assert_eq!(
+            configurations.get_configurations(),
+            &vec![vec![0, 0], vec![0, 1], vec![1, 1]]
+        );
+
+        assert_eq!(
+            split_equal_width_configurations("A [BC]^2 C AB^3 [AB]^2 C^2").unwrap(),
+            vec!["A [BC]^2 C", "AB^3", "[AB]^2 C^2"]
+        );
+        assert_eq!(
+            split_equal_width_configurations("A^2 B C").unwrap(),
+            vec!["A^2", "B C"]
+        );
+    }
+
+    #[test]
+    fn test_from_string_by_degree() {
+        let mut label_map = HashMap::new();
+        let by_degree =
+            Configurations::from_string_by_degree("A[BC]^2 B A", &mut label_map).unwrap();
+        assert_eq!(by_degree.len(), 2);
+        assert_eq!(by_degree[0].get_configurations(), &vec![vec![1], vec![0]]);
+        assert_eq!(by_degree[1].get_labels_per_configuration(), 3);
+        assert_eq!(by_degree[1].get_configuration_count(), 3);
+
+        assert_eq!(
+            Configurations::from_string_by_degree(" ", &mut label_map),
+            Err(LclParseError::EmptyPartition)
+        );
+    }
+
+    #[test]
+    fn test_from_string_errors() {
+        let mut label_map = HashMap::new();
+        assert_eq!(
+            Configurations::from_string("  ", &mut label_map),
+            Err(LclParseError::EmptyPartition)
+        );
+        assert_eq!(
+            Configurations::from_string("AB BC C", &mut label_map),
+            Err(LclParseError::MixedWidths {
+                token: "C".to_string(),
+                position: 2,
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            Configurations::from_string("AB B;", &mut label_map),
+            Err(LclParseError::BadCharacter {
+                token: "B;".to_string(),
+                position: 1,
+                character: ';',
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_string_too_many_labels() {
+        let mut label_map: HashMap<String, u8> = ('\u{100}'..)
+            .take(256)
+            .enumerate()
+            .map(|(i, c)| (c.to_string(), i as u8))
+            .collect();
+        assert_eq!(
+            Configurations::from_string("\u{100}\u{101} AB", &mut label_map),
+            Err(LclParseError::TooManyLabels {
+                token: "AB".to_string(),
+                position: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_configuration_data_errors() {
+        assert_eq!(
+            Configurations::from_configuration_data(vec![]),
+            Err(LclParseError::EmptyPartition)
+        );
+        assert_eq!(
+            Configurations::from_configuration_data(vec![vec![0, 1], vec![1]]),
+            Err(LclParseError::MixedWidths {
+                token: "1".to_string(),
+                position: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+    }
 }
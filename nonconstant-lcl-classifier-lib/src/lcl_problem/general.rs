@@ -0,0 +1,190 @@
+use super::{
+    configurations::{split_equal_width_configurations, Configurations},
+    default_label_name,
+    parse_error::LclParseError,
+    LclProblem,
+};
+use itertools::Itertools;
+use std::fmt;
+
+/// Locally Checkable Labeling problem for regular graphs, given as node and edge constraints.
+///
+/// Each node labels its incident half-edges, so a node configuration has a label for each incident edge,
+/// and an edge configuration has the labels of both halves of the edge.
+/// For example proper 3-coloring on 3-regular graphs has node configurations `AAA BBB CCC`
+/// and edge configurations `AB AC BC`.
+///
+/// The problem is the same as the bipartite [`LclProblem`] where the nodes are active
+/// and the edges are passive nodes of degree 2, see [`GeneralLclProblem::to_lcl_problem`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneralLclProblem {
+    /// Node configurations.
+    pub node: Configurations,
+    /// Edge configurations, each with 2 labels.
+    pub edge: Configurations,
+    label_names: Vec<String>,
+}
+
+impl GeneralLclProblem {
+    /// Parses a problem from node configurations `node` and edge configurations `edge`.
+    ///
+    /// The format is the same as in [`LclProblem::new`].
+    ///
+    /// # Errors
+    /// Returns [`LclParseError`] if the configurations cannot be parsed
+    /// or if the edge configurations do not have 2 labels.
+    pub fn new(node: &str, edge: &str) -> Result<Self, LclParseError> {
+        let problem = LclProblem::new(node, edge)?;
+        let found = problem.passive.get_labels_per_configuration();
+        if found != 2 {
+            // All edge configurations have the same width, so the first one is wrong.
+            return Err(LclParseError::MixedWidths {
+                token: split_equal_width_configurations(edge)?[0].to_string(),
+                position: 0,
+                expected: 2,
+                found,
+            });
+        }
+        Ok(Self::from_lcl_problem(&problem).unwrap())
+    }
+
+    /// Returns the degree of the nodes.
+    pub fn get_degree(&self) -> usize {
+        self.node.get_labels_per_configuration()
+    }
+
+    /// Returns the name of `label`.
+    ///
+    /// Labels without a name get a default name, see [`default_label_name`].
+    pub fn get_label_name(&self, label: u8) -> String {
+        self.label_names
+            .get(label as usize)
+            .cloned()
+            .unwrap_or_else(|| default_label_name(label))
+    }
+
+    /// Converts the problem into a bipartite problem.
+    ///
+    /// Node configurations become active configurations, and edge configurations become passive configurations.
+    /// Labels and their names stay the same.
+    pub fn to_lcl_problem(&self) -> LclProblem {
+        let mut problem = LclProblem::from_configurations(self.node.clone(), self.edge.clone());
+        problem.label_names = self.label_names.clone();
+        problem
+    }
+
+    /// Converts a bipartite problem back into a problem with node and edge constraints.
+    ///
+    /// This is the reverse of [`GeneralLclProblem::to_lcl_problem`],
+    /// e.g. for reporting a simplified or normalized problem in terms of the original graph.
+    ///
+    /// Returns `None` if the passive configurations do not have 2 labels.
+    pub fn from_lcl_problem(problem: &LclProblem) -> Option<Self> {
+        if problem.passive.get_labels_per_configuration() != 2 {
+            return None;
+        }
+        Some(Self {
+            node: problem.active.clone(),
+            edge: problem.passive.clone(),
+            label_names: problem.label_names.clone(),
+        })
+    }
+}
+
+impl From<GeneralLclProblem> for LclProblem {
+    fn from(problem: GeneralLclProblem) -> Self {
+        problem.to_lcl_problem()
+    }
+}
+
+impl fmt::Display for GeneralLclProblem {
+    /// Writes the node and edge configurations in the format accepted by [`GeneralLclProblem::new`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = [&self.node, &self.edge]
+            .iter()
+            .map(|configurations| configurations.to_condensed_string(|l| self.get_label_name(l)))
+            .join("; ");
+        write!(f, "{}", problem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BiregularGraph, SatEncoder, SatResult, SatSolver, UndirectedGraph};
+
+    /// Complete graph of 4 nodes as a bipartite graph, where each edge is a passive node.
+    fn get_k4() -> BiregularGraph {
+        let mut graph = UndirectedGraph::new_undirected();
+        let nodes = (0..4).map(|i| graph.add_node(i)).collect_vec();
+        let edges = nodes
+            .iter()
+            .tuple_combinations()
+            .map(|(&u, &v)| {
+                let edge = graph.add_node(0);
+                graph.add_edge(u, edge, ());
+                graph.add_edge(v, edge, ());
+                edge
+            })
+            .collect_vec();
+        BiregularGraph {
+            graph,
+            partition_a: nodes,
+            partition_b: edges,
+            degree_a: 3,
+            degree_b: 2,
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let problem = GeneralLclProblem::new("AAA BBB CCC", "AB AC BC").unwrap();
+        assert_eq!(problem.get_degree(), 3);
+        assert_eq!(problem.to_string(), "AAA BBB CCC; A[BC] BC");
+        assert_eq!(
+            problem.to_lcl_problem(),
+            LclProblem::new("AAA BBB CCC", "AB AC BC").unwrap()
+        );
+        assert_eq!(
+            GeneralLclProblem::new("AA", "AAA"),
+            Err(LclParseError::MixedWidths {
+                token: "AAA".to_string(),
+                position: 0,
+                expected: 2,
+                found: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_lcl_problem() {
+        let problem = LclProblem::new("(in)(out)^2", "(in)(out)").unwrap();
+        let general = GeneralLclProblem::from_lcl_problem(&problem).unwrap();
+        assert_eq!(general.to_string(), "(in)(out)^2; (in)(out)");
+        assert_eq!(LclProblem::from(general), problem);
+
+        let problem = LclProblem::new("AA", "AAA").unwrap();
+        assert_eq!(GeneralLclProblem::from_lcl_problem(&problem), None);
+    }
+
+    #[test]
+    fn test_coloring_k4() {
+        let graph = get_k4();
+        let coloring = |colors: &str| {
+            let nodes = colors.chars().map(|c| c.to_string().repeat(3)).join(" ");
+            let edges = colors
+                .chars()
+                .tuple_combinations()
+                .map(|(a, b)| format!("{}{}", a, b))
+                .join(" ");
+            GeneralLclProblem::new(&nodes, &edges)
+                .unwrap()
+                .to_lcl_problem()
+        };
+
+        let encoder = SatEncoder::new(&coloring("ABC"), graph.clone());
+        assert_eq!(SatSolver::solve(encoder.encode()), SatResult::Unsatisfiable);
+        let encoder = SatEncoder::new(&coloring("ABCD"), graph);
+        assert!(SatSolver::solve(encoder.encode()).is_satisfiable());
+    }
+}
@@ -0,0 +1,269 @@
+use super::{
+    configurations::Configurations, default_label_name, number_labels_by_name,
+    parse_error::LclParseError, LclProblem,
+};
+use crate::{BiregularGraph, SatEncoder, SatResult, SatSolver};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// Locally Checkable Labeling problem where nodes are given input labels.
+///
+/// Each input label of the active partition has its own set of allowed active configurations,
+/// and similarly for the passive partition.
+/// All configurations of a partition have the same degree, and all inputs share the same output labels.
+/// A problem without inputs has a single input in both partitions, see [`From<LclProblem>`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LclProblemWithInputs {
+    /// Allowed active configurations, indexed by the input of the active node.
+    pub active: Vec<Configurations>,
+    /// Allowed passive configurations, indexed by the input of the passive node.
+    pub passive: Vec<Configurations>,
+    active_input_names: Vec<String>,
+    passive_input_names: Vec<String>,
+    label_names: Vec<String>,
+}
+
+/// Input label of each node of a [`BiregularGraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputAssignment {
+    /// Input of each node in `partition_a`, in the same order.
+    pub active: Vec<u8>,
+    /// Input of each node in `partition_b`, in the same order.
+    pub passive: Vec<u8>,
+}
+
+impl LclProblemWithInputs {
+    /// Parses a problem from named inputs and their configurations.
+    ///
+    /// `active` and `passive` contain pairs of an input name and the configurations allowed with that input,
+    /// in the format of [`Configurations::from_string`].
+    /// Inputs are numbered in the given order and output labels in the order of their names,
+    /// as in [`LclProblem::new`].
+    ///
+    /// # Errors
+    /// Returns [`LclParseError`] if some configurations cannot be parsed,
+    /// a partition has no inputs or the inputs of a partition have different degrees.
+    /// In the last case the token is the configurations of the input and the position is the index of the input.
+    pub fn new(active: &[(&str, &str)], passive: &[(&str, &str)]) -> Result<Self, LclParseError> {
+        let mut label_map: HashMap<String, u8> = HashMap::new();
+        let mut parse = |inputs: &[(&str, &str)]| -> Result<Vec<Configurations>, LclParseError> {
+            let configurations = inputs
+                .iter()
+                .map(|(_, encoding)| Configurations::from_string(encoding, &mut label_map))
+                .collect::<Result<Vec<_>, _>>()?;
+            check_degrees(inputs, &configurations)?;
+            Ok(configurations)
+        };
+        let active_configurations = parse(active)?;
+        let passive_configurations = parse(passive)?;
+        let (permutation, label_names) = number_labels_by_name(label_map);
+
+        let names = |inputs: &[(&str, &str)]| {
+            inputs
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect_vec()
+        };
+        Ok(Self {
+            active: active_configurations
+                .iter()
+                .map(|configurations| configurations.map_labels(&permutation))
+                .collect(),
+            passive: passive_configurations
+                .iter()
+                .map(|configurations| configurations.map_labels(&permutation))
+                .collect(),
+            active_input_names: names(active),
+            passive_input_names: names(passive),
+            label_names,
+        })
+    }
+
+    /// Returns the names of the active inputs indexed by the input.
+    pub fn get_active_input_names(&self) -> &[String] {
+        &self.active_input_names
+    }
+
+    /// Returns the names of the passive inputs indexed by the input.
+    pub fn get_passive_input_names(&self) -> &[String] {
+        &self.passive_input_names
+    }
+
+    /// Returns the name of output `label`.
+    ///
+    /// Labels without a name get a default name, see [`default_label_name`].
+    pub fn get_label_name(&self, label: u8) -> String {
+        self.label_names
+            .get(label as usize)
+            .cloned()
+            .unwrap_or_else(|| default_label_name(label))
+    }
+
+    /// Returns the problem that nodes with the given inputs have to solve.
+    pub fn get_problem_for_inputs(&self, active_input: u8, passive_input: u8) -> LclProblem {
+        let mut problem = LclProblem::from_configurations(
+            self.active[active_input as usize].clone(),
+            self.passive[passive_input as usize].clone(),
+        );
+        problem.label_names = self.label_names.clone();
+        problem
+    }
+
+    /// Finds an assignment of inputs that makes the problem unsolvable on `graph`.
+    ///
+    /// Every assignment of inputs to the nodes of the graph is tried in turn
+    /// and each is encoded with [`SatEncoder::with_inputs`].
+    /// The count of assignments grows exponentially with the size of the graph,
+    /// but only one assignment is kept in memory at a time.
+    ///
+    /// Returns `None` if the problem can be solved on the graph with every assignment.
+    pub fn find_unsolvable_input_assignment(
+        &self,
+        graph: &BiregularGraph,
+    ) -> Option<InputAssignment> {
+        // Assignments are generated lazily, and the search stops at the first unsolvable one.
+        let active_count = graph.partition_a.len();
+        let active_inputs = (0..active_count).map(|_| 0..self.active.len() as u8);
+        let passive_inputs = (0..graph.partition_b.len()).map(|_| 0..self.passive.len() as u8);
+        active_inputs
+            .chain(passive_inputs)
+            .multi_cartesian_product()
+            .map(|mut inputs| {
+                let passive = inputs.split_off(active_count);
+                InputAssignment {
+                    active: inputs,
+                    passive,
+                }
+            })
+            .find(|assignment| {
+                let encoder = SatEncoder::with_inputs(self, graph.clone(), assignment);
+                SatSolver::solve(encoder.encode()) == SatResult::Unsatisfiable
+            })
+    }
+}
+
+impl From<LclProblem> for LclProblemWithInputs {
+    /// Creates a problem where every node has the same input.
+    fn from(problem: LclProblem) -> Self {
+        Self {
+            active: vec![problem.active],
+            passive: vec![problem.passive],
+            active_input_names: vec![String::new()],
+            passive_input_names: vec![String::new()],
+            label_names: problem.label_names,
+        }
+    }
+}
+
+/// Checks that `inputs` exist and that their `configurations` have the same degree.
+fn check_degrees(
+    inputs: &[(&str, &str)],
+    configurations: &[Configurations],
+) -> Result<(), LclParseError> {
+    let expected = configurations
+        .first()
+        .ok_or(LclParseError::EmptyPartition)?
+        .get_labels_per_configuration();
+    if let Some((position, found)) = configurations
+        .iter()
+        .map(|configurations| configurations.get_labels_per_configuration())
+        .find_position(|&degree| degree != expected)
+    {
+        return Err(LclParseError::MixedWidths {
+            token: inputs[position].1.to_string(),
+            position,
+            expected,
+            found,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UndirectedGraph;
+
+    /// Two active nodes of degree 1 connected to the same passive node of degree 2.
+    fn get_path() -> BiregularGraph {
+        let mut graph = UndirectedGraph::new_undirected();
+        let a0 = graph.add_node(0);
+        let a1 = graph.add_node(1);
+        let p = graph.add_node(2);
+        graph.add_edge(a0, p, ());
+        graph.add_edge(a1, p, ());
+        BiregularGraph {
+            graph,
+            partition_a: vec![a0, a1],
+            partition_b: vec![p],
+            degree_a: 1,
+            degree_b: 2,
+        }
+    }
+
+    /// Active nodes output their input, and passive nodes require equal outputs.
+    fn get_copy_problem() -> LclProblemWithInputs {
+        LclProblemWithInputs::new(&[("x", "A"), ("y", "B")], &[("", "AA BB")]).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let problem = get_copy_problem();
+        assert_eq!(problem.get_active_input_names(), ["x", "y"]);
+        assert_eq!(problem.get_passive_input_names(), [""]);
+        assert_eq!(problem.active[1].get_configurations(), &vec![vec![1]]);
+        assert_eq!(problem.get_problem_for_inputs(0, 0).to_string(), "A; AA BB");
+
+        assert_eq!(
+            LclProblemWithInputs::new(&[("x", "A"), ("y", "BB")], &[("", "AA")]),
+            Err(LclParseError::MixedWidths {
+                token: "BB".to_string(),
+                position: 1,
+                expected: 1,
+                found: 2,
+            })
+        );
+        assert_eq!(
+            LclProblemWithInputs::new(&[], &[("", "AA")]),
+            Err(LclParseError::EmptyPartition)
+        );
+    }
+
+    #[test]
+    fn test_with_inputs() {
+        let problem = get_copy_problem();
+        let graph = get_path();
+
+        let same = InputAssignment {
+            active: vec![1, 1],
+            passive: vec![0],
+        };
+        let encoder = SatEncoder::with_inputs(&problem, graph.clone(), &same);
+        assert!(SatSolver::solve(encoder.encode()).is_satisfiable());
+
+        let different = InputAssignment {
+            active: vec![0, 1],
+            passive: vec![0],
+        };
+        let encoder = SatEncoder::with_inputs(&problem, graph, &different);
+        assert_eq!(SatSolver::solve(encoder.encode()), SatResult::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_find_unsolvable_input_assignment() {
+        let graph = get_path();
+        assert_eq!(
+            get_copy_problem().find_unsolvable_input_assignment(&graph),
+            Some(InputAssignment {
+                active: vec![0, 1],
+                passive: vec![0],
+            })
+        );
+
+        let problem = LclProblem::new("A B", "AA BB").unwrap();
+        assert_eq!(
+            LclProblemWithInputs::from(problem).find_unsolvable_input_assignment(&graph),
+            None
+        );
+    }
+}
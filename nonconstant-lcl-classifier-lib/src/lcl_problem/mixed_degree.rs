@@ -0,0 +1,152 @@
+use super::{
+    configurations::Configurations, default_label_name, number_labels_by_name,
+    parse_error::LclParseError, LclProblem,
+};
+use itertools::Itertools;
+use std::{collections::HashMap, fmt};
+
+/// Locally Checkable Labeling problem for graphs where nodes of a partition have different degrees.
+///
+/// Both partitions have configurations for one or more degrees,
+/// e.g. degree 3 for inner nodes and degree 1 for the leaves of a tree.
+/// A node uses the configurations of its own degree.
+/// A problem for biregular graphs has a single degree in both partitions, see [`From<LclProblem>`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixedDegreeLclProblem {
+    /// Active configurations of each degree, in increasing order of degree.
+    pub active: Vec<Configurations>,
+    /// Passive configurations of each degree, in increasing order of degree.
+    pub passive: Vec<Configurations>,
+    label_names: Vec<String>,
+}
+
+impl MixedDegreeLclProblem {
+    /// Parses a problem from active configurations `a` and passive configurations `p` of any degrees.
+    ///
+    /// See [`Configurations::from_string_by_degree`] for the format.
+    /// Labels are numbered in the order of their names, as in [`LclProblem::new`].
+    pub fn new(a: &str, p: &str) -> Result<Self, LclParseError> {
+        let mut label_map: HashMap<String, u8> = HashMap::new();
+        let active = Configurations::from_string_by_degree(a, &mut label_map)?;
+        let passive = Configurations::from_string_by_degree(p, &mut label_map)?;
+        let (permutation, label_names) = number_labels_by_name(label_map);
+
+        let map_labels = |by_degree: Vec<Configurations>| {
+            by_degree
+                .iter()
+                .map(|configurations| configurations.map_labels(&permutation))
+                .collect_vec()
+        };
+        Ok(Self {
+            active: map_labels(active),
+            passive: map_labels(passive),
+            label_names,
+        })
+    }
+
+    /// Returns the degrees of the active nodes in increasing order.
+    pub fn get_active_degrees(&self) -> Vec<usize> {
+        get_degrees(&self.active)
+    }
+
+    /// Returns the degrees of the passive nodes in increasing order.
+    pub fn get_passive_degrees(&self) -> Vec<usize> {
+        get_degrees(&self.passive)
+    }
+
+    /// Returns the name of `label`.
+    ///
+    /// Labels without a name get a default name, see [`default_label_name`].
+    pub fn get_label_name(&self, label: u8) -> String {
+        self.label_names
+            .get(label as usize)
+            .cloned()
+            .unwrap_or_else(|| default_label_name(label))
+    }
+}
+
+/// Returns the degree of each group of configurations.
+fn get_degrees(by_degree: &[Configurations]) -> Vec<usize> {
+    by_degree
+        .iter()
+        .map(|configurations| configurations.get_labels_per_configuration())
+        .collect()
+}
+
+impl From<LclProblem> for MixedDegreeLclProblem {
+    fn from(problem: LclProblem) -> Self {
+        Self {
+            active: vec![problem.active],
+            passive: vec![problem.passive],
+            label_names: problem.label_names,
+        }
+    }
+}
+
+impl fmt::Display for MixedDegreeLclProblem {
+    /// Writes the problem in the same format as [`LclProblem::to_string`].
+    ///
+    /// Configurations are written in increasing order of degree.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = [&self.active, &self.passive]
+            .iter()
+            .map(|by_degree| {
+                by_degree
+                    .iter()
+                    .map(|configurations| {
+                        configurations.to_condensed_string(|l| self.get_label_name(l))
+                    })
+                    .join(" ")
+            })
+            .join("; ");
+        write!(f, "{}", problem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BiregularGraph, SatEncoder, SatResult, SatSolver, UndirectedGraph};
+
+    /// Tree with an active node of degree 3 whose neighbors have a leaf each.
+    fn get_tree() -> BiregularGraph {
+        let mut graph = UndirectedGraph::new_undirected();
+        let center = graph.add_node(0);
+        let leaves = (1..=3).map(|i| graph.add_node(i)).collect_vec();
+        let passive = (4..=6).map(|i| graph.add_node(i)).collect_vec();
+        for (&leaf, &node) in leaves.iter().zip(&passive) {
+            graph.add_edge(center, node, ());
+            graph.add_edge(leaf, node, ());
+        }
+        BiregularGraph {
+            graph,
+            partition_a: [vec![center], leaves].concat(),
+            partition_b: passive,
+            degree_a: 3,
+            degree_b: 2,
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let problem = MixedDegreeLclProblem::new("AAA B", "AB").unwrap();
+        assert_eq!(problem.get_active_degrees(), vec![1, 3]);
+        assert_eq!(problem.get_passive_degrees(), vec![2]);
+        assert_eq!(problem.to_string(), "B AAA; AB");
+
+        let reparsed = MixedDegreeLclProblem::new("B AAA", "AB").unwrap();
+        assert_eq!(reparsed, problem);
+    }
+
+    #[test]
+    fn test_encode_tree() {
+        let problem = MixedDegreeLclProblem::new("AAA B", "AB").unwrap();
+        let encoder = SatEncoder::with_mixed_degrees(&problem, get_tree());
+        assert!(SatSolver::solve(encoder.encode()).is_satisfiable());
+
+        // Leaves must use the same label as the center, which the passive nodes do not allow.
+        let problem = MixedDegreeLclProblem::new("AAA A", "AB").unwrap();
+        let encoder = SatEncoder::with_mixed_degrees(&problem, get_tree());
+        assert_eq!(SatSolver::solve(encoder.encode()), SatResult::Unsatisfiable);
+    }
+}
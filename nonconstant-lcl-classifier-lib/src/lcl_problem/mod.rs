@@ -1,8 +1,21 @@
+mod canonical;
+mod condensed;
 pub mod configurations;
+pub mod general;
+pub mod inputs;
+pub mod mixed_degree;
+pub mod normalized_problems;
+pub mod oriented;
+pub mod parse_error;
+pub mod problem_lattice;
+pub mod round_elimination;
+pub mod zero_round;
 
 use configurations::Configurations;
 use itertools::Itertools;
 use log::info;
+use normalized_problems::NormalizedProblems;
+use parse_error::LclParseError;
 use serde::{Deserialize, Serialize};
 use std::string::ToString;
 use std::{
@@ -19,10 +32,15 @@ use crate::caches::{lcl_problem::LclProblemCacheParams, Cache};
 /// Locally Checkable Labeling problem for biregular graphs.
 ///
 /// Contains configurations for active nodes and passive nodes.
+///
+/// A problem parsed from a string remembers the names of its labels.
+/// The names are only used for printing, so they do not affect equality, ordering or hashing.
 #[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 pub struct LclProblem {
     pub active: Configurations,
     pub passive: Configurations,
+    /// Name of each label, indexed by the label. Empty if the labels have no names.
+    label_names: Vec<String>,
 }
 
 impl Hash for LclProblem {
@@ -33,16 +51,68 @@ impl Hash for LclProblem {
 }
 
 impl LclProblem {
-    pub fn new(a: &str, p: &str) -> Result<LclProblem, Box<dyn std::error::Error>> {
-        let mut label_map: HashMap<char, u8> = HashMap::new();
+    /// Parses an LCL problem from active configurations `a` and passive configurations `p`.
+    ///
+    /// Both partitions share the same mapping of labels.
+    /// See [`Configurations::from_string`] for the format.
+    /// The names of the labels are kept and used when the problem is printed.
+    ///
+    /// Labels are numbered `0..k` in the order of their names, see [`label_name_order`].
+    /// Thus parsing the string of a problem gives back an equal problem,
+    /// as long as the problem uses labels `0..k` and its names are in that order.
+    /// Purging, simplifying and normalizing leave no gaps in the labels,
+    /// so this holds for every such problem without names,
+    /// and for every parsed problem that has only been purged or simplified since.
+    pub fn new(a: &str, p: &str) -> Result<LclProblem, LclParseError> {
+        let mut label_map: HashMap<String, u8> = HashMap::new();
+        let active = Configurations::from_string(a, &mut label_map)?;
+        let passive = Configurations::from_string(p, &mut label_map)?;
+        let (permutation, label_names) = number_labels_by_name(label_map);
+
         Ok(LclProblem {
-            active: Configurations::from_string(a, &mut label_map)?,
-            passive: Configurations::from_string(p, &mut label_map)?,
+            active: active.map_labels(&permutation),
+            passive: passive.map_labels(&permutation),
+            label_names,
         })
     }
 
-    fn from_configurations(active: Configurations, passive: Configurations) -> Self {
-        Self { active, passive }
+    pub(crate) fn from_configurations(active: Configurations, passive: Configurations) -> Self {
+        Self {
+            active,
+            passive,
+            label_names: vec![],
+        }
+    }
+
+    /// Returns the name of `label`.
+    ///
+    /// Labels without a name get a default name, see [`default_label_name`].
+    pub fn get_label_name(&self, label: u8) -> String {
+        self.label_names
+            .get(label as usize)
+            .cloned()
+            .unwrap_or_else(|| default_label_name(label))
+    }
+
+    /// Returns the names of the labels indexed by the label.
+    ///
+    /// Empty if the problem was not parsed from a string.
+    pub fn get_label_names(&self) -> &[String] {
+        &self.label_names
+    }
+
+    /// Renames the labels after they have been mapped with `permutation`.
+    ///
+    /// Label `l` was mapped to `permutation[l]`.
+    fn permute_label_names(&mut self, permutation: &[u8]) {
+        if self.label_names.is_empty() {
+            return;
+        }
+        let mut label_names = vec![String::new(); self.label_names.len().max(permutation.len())];
+        for (old, &new) in permutation.iter().enumerate() {
+            label_names[new as usize] = self.get_label_name(old as u8);
+        }
+        self.label_names = label_names;
     }
 
     /// Checks if either active or passive partition is empty.
@@ -54,9 +124,16 @@ impl LclProblem {
     ///
     /// Configurations, that contain some label l such that l is not in any configuration
     /// on the other configuration set, are considered redundant.
+    /// The remaining labels are renumbered to `0..k` in their order, keeping their names.
     ///
     /// Adapted from <https://github.com/AleksTeresh/lcl-classifier/blob/be5d0196b02dad33ee19657af6b16457f59780e9/src/server/problem/problem.py#L378>
     pub fn purge(&mut self) {
+        self.remove_redundant_configurations();
+        self.renumber_labels();
+    }
+
+    /// Removes redundant configurations like [`Self::purge`], but keeps the labels.
+    fn remove_redundant_configurations(&mut self) {
         let mut active_labels = self.active.get_labels_set();
         let mut passive_labels = self.passive.get_labels_set();
 
@@ -86,30 +163,135 @@ impl LclProblem {
         }
     }
 
+    /// Removes labels that can be replaced by another label.
+    ///
+    /// Label `a` is dominated by label `b` if replacing every `a` with `b` in a configuration
+    /// containing `a` gives an allowed configuration, on both sides.
+    /// Then any solution can be changed to not use `a` at all,
+    /// so the configurations containing `a` can be removed without changing solvability in the PN model.
+    /// Labels that dominate each other are interchangeable and only one of them is kept.
+    ///
+    /// Purges the problem and removes dominated labels one at a time until none are left.
+    /// Like purging, this renumbers the remaining labels to `0..k`.
+    pub fn simplify(&mut self) {
+        self.purge();
+        while let Some(label) = self.find_dominated_label() {
+            self.active.remove_configurations_containing_label(&[label]);
+            self.passive
+                .remove_configurations_containing_label(&[label]);
+            self.purge();
+        }
+    }
+
+    /// Renumbers the labels to `0..k` in their order, so that removed labels leave no gaps.
+    fn renumber_labels(&mut self) {
+        let labels = self
+            .active
+            .get_labels_set()
+            .union(&self.passive.get_labels_set())
+            .copied()
+            .sorted()
+            .collect_vec();
+        let label_count = match labels.last() {
+            Some(&label) => label as usize + 1,
+            None => return,
+        };
+        let mut permutation = vec![0; label_count];
+        for (new, &old) in labels.iter().enumerate() {
+            permutation[old as usize] = new as u8;
+        }
+
+        self.active = self.active.map_labels(&permutation);
+        self.passive = self.passive.map_labels(&permutation);
+        if !self.label_names.is_empty() {
+            self.label_names = labels
+                .iter()
+                .map(|&label| self.get_label_name(label))
+                .collect();
+        }
+    }
+
+    /// Checks if no label is dominated by another label, see [`Self::simplify`].
+    pub fn is_simplified(&self) -> bool {
+        self.find_dominated_label().is_none()
+    }
+
+    /// Returns the smallest label that is dominated by another label.
+    fn find_dominated_label(&self) -> Option<u8> {
+        let labels = self
+            .active
+            .get_labels_set()
+            .union(&self.passive.get_labels_set())
+            .copied()
+            .sorted()
+            .collect_vec();
+
+        labels.iter().copied().find(|&label| {
+            labels.iter().any(|&dominating| {
+                label != dominating
+                    && self.active.is_label_dominated(label, dominating)
+                    && self.passive.is_label_dominated(label, dominating)
+            })
+        })
+    }
+
+    /// Relabels the problem to the representative of its class.
+    ///
+    /// The representative is the relabeling with the lexicographically smallest
+    /// sorted active configurations, and then sorted passive configurations.
+    /// It is found with a canonical labeling search instead of trying every permutation,
+    /// see [`canonical::canonical_permutation`].
+    /// Label names follow their labels.
     pub fn normalize(&mut self) {
-        let mut problems = self.get_all_permutations();
+        let permutation = canonical::canonical_permutation(
+            self.active.get_configurations(),
+            self.passive.get_configurations(),
+        );
 
-        problems.iter_mut().for_each(|(a, p)| {
-            a.sort();
-            p.sort();
-        });
+        self.active = self.active.map_labels(&permutation);
+        self.passive = self.passive.map_labels(&permutation);
+        self.active.sort();
+        self.passive.sort();
+        self.permute_label_names(&permutation);
+    }
 
-        // Pick the lexicographically first problem.
-        let mut first = problems
-            .into_iter()
-            .min_by(|(a0, p0), (a1, p1)| a0.cmp(a1).then_with(|| p0.cmp(p1)))
-            .unwrap();
+    /// Swaps the active and the passive configurations.
+    pub fn swap_partitions(&mut self) {
+        std::mem::swap(&mut self.active, &mut self.passive);
+    }
+
+    /// Normalizes the problem so that mirror images get the same representative.
+    ///
+    /// If both partitions have the same degree, the problem with active and passive
+    /// configurations swapped is normalized as well, and the smaller of the two is kept.
+    /// Otherwise this is the same as [`Self::normalize`].
+    ///
+    /// Returns the orientation of the representative with respect to the original problem.
+    /// The original problem is recovered from a [`Orientation::Swapped`] representative
+    /// with [`Self::swap_partitions`], up to renaming of labels.
+    pub fn normalize_up_to_swap(&mut self) -> Orientation {
+        self.normalize();
+        if self.active.get_labels_per_configuration() != self.passive.get_labels_per_configuration()
+        {
+            return Orientation::Original;
+        }
 
-        // Swap configurations with the first problem.
-        std::mem::swap(&mut first.0, &mut self.active);
-        std::mem::swap(&mut first.1, &mut self.passive);
+        let mut swapped = self.clone();
+        swapped.swap_partitions();
+        swapped.normalize();
+        if swapped < *self {
+            *self = swapped;
+            return Orientation::Swapped;
+        }
+        Orientation::Original
     }
 
     /// Generate all unique problems of a class (cached).
     ///
     /// TODO this is not really cached, and should be named accordingly.
-    /// Uses `Self::purge` for each generated problem and
-    /// removes problems with empty partition from the result.
+    /// Removes redundant configurations of each generated problem like `Self::purge`,
+    /// but keeps the labels, so problems that differ only by their labels stay distinct.
+    /// Removes problems with empty partition from the result.
     pub fn get_or_generate(
         active_degree: usize,
         passive_degree: usize,
@@ -137,7 +319,7 @@ impl LclProblem {
         cartesian_product
             .filter_map(|(active, passive)| {
                 let mut problem = LclProblem::from_configurations(active.clone(), passive.clone());
-                problem.purge();
+                problem.remove_redundant_configurations();
                 if !problem.contains_empty_partition() {
                     return Some(problem);
                 }
@@ -147,42 +329,32 @@ impl LclProblem {
             .collect_vec()
     }
 
-    /// Generates all unique normalized problems of a class.
-    ///
-    /// Intermediate results (powersets) are cached using parameter `cache`.
+    /// Generates all unique normalized problems of a class that cannot be simplified.
     ///
-    /// Generates problems with `Self::generate` and then normalizes them.
-    /// Returns only unique problems.
+    /// Simplifying and then normalizing a problem of the class gives one of these problems,
+    /// see [`Self::simplify`]. So these are the problems of [`Self::iter_normalized`]
+    /// that are already simplified.
     pub fn generate_normalized(
         active_degree: usize,
         passive_degree: usize,
         label_count: u8,
     ) -> Vec<Self> {
-        let mut problems = Self::get_or_generate(active_degree, passive_degree, label_count);
-        problems.iter_mut().for_each(|p| p.normalize());
-        problems.into_iter().unique().collect_vec()
+        Self::iter_normalized(active_degree, passive_degree, label_count)
+            .filter(|problem| problem.is_simplified())
+            .collect_vec()
     }
 
-    fn get_all_permutations(&self) -> Vec<(Configurations, Configurations)> {
-        let label_max = self
-            .active
-            .get_labels()
-            .into_iter()
-            .chain(self.passive.get_labels().into_iter())
-            .max()
-            .unwrap();
-        let labels = 0..=label_max;
-
-        let permutations = labels.permutations(label_max as usize + 1).collect_vec();
-
-        let a = permutations.into_iter().map(|perm| {
-            assert!(!perm.is_empty());
-            let active = self.active.map_labels(&perm);
-            let passive = self.passive.map_labels(&perm);
-            (active, passive)
-        });
-
-        a.collect_vec()
+    /// Iterates over all unique normalized problems of a class.
+    ///
+    /// These are the problems of [`Self::get_or_generate`] after normalization,
+    /// but they are generated one by one without keeping the whole class in memory.
+    /// Use [`NormalizedProblems::shard`] to generate only a part of the class.
+    pub fn iter_normalized(
+        active_degree: usize,
+        passive_degree: usize,
+        label_count: u8,
+    ) -> NormalizedProblems {
+        NormalizedProblems::new(active_degree, passive_degree, label_count)
     }
 
     /// Generate all unique normalized problems of a class (cached).
@@ -205,7 +377,11 @@ impl LclProblem {
                     "Read the problems (deg_active={}, deg_passive={}, labels={}) from cache",
                     active_degree, passive_degree, alphabet_length
                 );
-                return result;
+                // Caches written before simplification was added contain every problem of the class.
+                return result
+                    .into_iter()
+                    .filter(|problem| problem.is_simplified())
+                    .collect_vec();
             }
         }
 
@@ -248,33 +424,74 @@ impl LclProblem {
     }
 }
 
+/// Renumbers the labels of `label_map` in the order of their names, see [`label_name_order`].
+///
+/// Returns the permutation from the old labels to the new ones and the names indexed by the new labels.
+pub(crate) fn number_labels_by_name(label_map: HashMap<String, u8>) -> (Vec<u8>, Vec<String>) {
+    let mut names = label_map.into_iter().collect_vec();
+    names.sort_by(|(name0, _), (name1, _)| label_name_order(name0, name1));
+
+    let mut permutation = vec![0; names.len()];
+    for (new, (_, old)) in names.iter().enumerate() {
+        permutation[*old as usize] = new as u8;
+    }
+    (
+        permutation,
+        names.into_iter().map(|(name, _)| name).collect(),
+    )
+}
+
+const DEFAULT_LABEL_NAMES: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Returns the name used for `label` when the problem has no label names.
+///
+/// The first 62 labels are named `A..Z`, `a..z` and `0..9`.
+/// The rest are named by their number, which is written in parentheses, e.g. `(62)`.
+pub fn default_label_name(label: u8) -> String {
+    DEFAULT_LABEL_NAMES
+        .chars()
+        .nth(label as usize)
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| label.to_string())
+}
+
+/// Returns the label that has `name` as its default name.
+fn default_label(name: &str) -> Option<u8> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => DEFAULT_LABEL_NAMES.find(c).map(|i| i as u8),
+        _ => name
+            .parse::<u8>()
+            .ok()
+            .filter(|&label| default_label_name(label) == name),
+    }
+}
+
+/// Order of label names used when numbering parsed labels.
+///
+/// Default names come first in the order of their labels, see [`default_label_name`].
+/// Other names come after them in lexicographic order.
+pub fn label_name_order(name0: &str, name1: &str) -> Ordering {
+    let key = |name| {
+        let label = default_label(name);
+        (label.is_none(), label, name)
+    };
+    key(name0).cmp(&key(name1))
+}
+
 impl ToString for LclProblem {
     /// Returns a string representation of the problem.
     ///
-    /// Supports up to 26 different labels.
-    /// The labels are the 26 letters in the English alphabet.
+    /// Labels are written with their names, see [`LclProblem::get_label_name`].
+    /// Configurations are written in the condensed notation, see [`Configurations::from_string`].
     ///
     /// An example of a problem:
-    /// ```AAB AAC; AB AC```
+    /// ```AA[BC]; A[BC]```
     fn to_string(&self) -> String {
-        let labels = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-        let configurations = [&self.active, &self.passive];
-        let configurations_string = configurations
+        [&self.active, &self.passive]
             .iter()
-            .map(|problem_set| {
-                let mut conf = problem_set
-                    .get_configurations()
-                    .iter()
-                    .map(|configuration| {
-                        configuration
-                            .iter()
-                            .map(|&l| labels.chars().nth(l as usize).unwrap())
-                            .join("")
-                    });
-                conf.join(" ")
-            })
-            .collect_vec();
-        configurations_string.join("; ")
+            .map(|configurations| configurations.to_condensed_string(|l| self.get_label_name(l)))
+            .join("; ")
     }
 }
 
@@ -302,6 +519,15 @@ impl Ord for LclProblem {
     }
 }
 
+/// Orientation of a normalized representative with respect to the problem it was made from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Orientation {
+    /// Active and passive configurations are on the same sides as in the problem.
+    Original,
+    /// Active and passive configurations of the problem are swapped.
+    Swapped,
+}
+
 pub trait Purgeable<T> {
     fn purge(self) -> Vec<T>;
 }
@@ -310,6 +536,14 @@ pub trait Normalizable<T> {
     fn normalize(self) -> Vec<T>;
 }
 
+/// Extension of [`Normalizable`] that also treats mirror images as duplicates.
+pub trait SwapNormalizable<T>: Normalizable<T> {
+    /// Normalizes and removes duplicates, also treating mirror images as duplicates.
+    ///
+    /// Each representative is returned with the orientations of the problems it represents.
+    fn normalize_up_to_swap(self) -> Vec<(T, Vec<Orientation>)>;
+}
+
 impl Purgeable<LclProblem> for Vec<LclProblem> {
     fn purge(self) -> Vec<LclProblem> {
         self.into_iter()
@@ -334,9 +568,33 @@ impl Normalizable<LclProblem> for Vec<LclProblem> {
     }
 }
 
+impl SwapNormalizable<LclProblem> for Vec<LclProblem> {
+    fn normalize_up_to_swap(self) -> Vec<(LclProblem, Vec<Orientation>)> {
+        let mut representatives: Vec<(LclProblem, Vec<Orientation>)> = vec![];
+        let mut indices: HashMap<LclProblem, usize> = HashMap::new();
+        for mut problem in self {
+            let orientation = problem.normalize_up_to_swap();
+            match indices.get(&problem) {
+                Some(&index) => {
+                    let orientations = &mut representatives[index].1;
+                    if !orientations.contains(&orientation) {
+                        orientations.push(orientation);
+                    }
+                }
+                None => {
+                    indices.insert(problem.clone(), representatives.len());
+                    representatives.push((problem, vec![orientation]));
+                }
+            }
+        }
+        representatives
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::caches::LclProblemSqliteCache;
+    use proptest::{collection::vec, prelude::*, sample::select};
 
     use super::*;
 
@@ -350,6 +608,239 @@ mod tests {
         assert_eq!(problem.is_ok(), true);
     }
 
+    #[test]
+    fn test_new_lcl_problem_errors() {
+        assert_eq!(
+            LclProblem::new("MUU PPP", ""),
+            Err(LclParseError::EmptyPartition)
+        );
+        assert!(matches!(
+            LclProblem::new("MUU PP", "MM PU UU"),
+            Err(LclParseError::MixedWidths { position: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_condensed_notation() {
+        let problem = LclProblem::new("AAB AAC", "AB AC").unwrap();
+        assert_eq!(problem.to_string(), "AA[BC]; A[BC]");
+
+        let condensed = LclProblem::new("AA[BC]", "A[BC]").unwrap();
+        assert_eq!(condensed, problem);
+
+        // On a single line, whitespace only separates configurations of equal width.
+        assert_eq!(
+            LclProblem::new("A B^3", "AB").unwrap(),
+            LclProblem::new("AB^3", "AB").unwrap()
+        );
+        let problem = LclProblem::new("A [BC]^2 C A B^3", "A B B^2").unwrap();
+        assert_eq!(
+            problem,
+            LclProblem::new("A [BC]^2 C\nA B^3", "A B\nB B").unwrap()
+        );
+        assert_eq!(problem, reparse(&problem));
+
+        let problem = LclProblem::new("A[BC]^2 C^3", "[ABC]^2").unwrap();
+        assert_eq!(problem.active.get_configuration_count(), 4);
+        assert_eq!(problem.passive.get_configuration_count(), 6);
+        assert_eq!(
+            LclProblem::new(
+                &problem.active.to_condensed_string(|l| l.to_string()),
+                "012"
+            )
+            .unwrap()
+            .active,
+            problem.active
+        );
+    }
+
+    /// Parses the string representation of `problem`.
+    fn reparse(problem: &LclProblem) -> LclProblem {
+        let string = problem.to_string();
+        let (a, p) = string.split_once(';').unwrap();
+        LclProblem::new(a, p).unwrap()
+    }
+
+    /// Strategy for problems without names that use some of the labels `0..label_count`.
+    ///
+    /// The labels may have gaps, like after removing configurations.
+    /// Passive configurations use only labels of active configurations, so that purging keeps some of them.
+    fn unnamed_problems(label_count: u8) -> impl Strategy<Value = LclProblem> {
+        (1..5usize, 1..5usize)
+            .prop_flat_map(move |(deg_a, deg_p)| {
+                (vec(vec(0..=label_count - 1, deg_a), 1..8), Just(deg_p))
+            })
+            .prop_flat_map(|(active, deg_p)| {
+                let labels = active
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .sorted()
+                    .dedup()
+                    .collect_vec();
+                (Just(active), vec(vec(select(labels), deg_p), 1..8))
+            })
+            .prop_map(|(active, passive)| {
+                LclProblem::from_configurations(
+                    Configurations::from_configuration_data(active).unwrap(),
+                    Configurations::from_configuration_data(passive).unwrap(),
+                )
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_to_string_round_trip(problem in unnamed_problems(u8::MAX)) {
+            let mut purged = problem.clone();
+            purged.purge();
+            let mut simplified = problem;
+            simplified.simplify();
+            prop_assume!(!purged.contains_empty_partition());
+            prop_assert_eq!(reparse(&purged), purged);
+            prop_assert_eq!(reparse(&simplified), simplified);
+        }
+    }
+
+    #[test]
+    fn test_to_string_with_all_labels() {
+        let configurations = (0..=u8::MAX).map(|l| vec![l, l]).collect_vec();
+        let configurations = Configurations::from_configuration_data(configurations).unwrap();
+        let problem = LclProblem::from_configurations(configurations.clone(), configurations);
+
+        let string = problem.to_string();
+        assert!(string.starts_with("AA BB"));
+        assert!(string.contains("zz 00 11"));
+        assert!(string.contains("99 (62)^2 (63)^2"));
+        assert!(string.contains("(255)^2;"));
+        assert_eq!(reparse(&problem), problem);
+    }
+
+    #[test]
+    fn test_normalized_to_string_round_trip() {
+        for problem in LclProblem::generate_normalized(2, 2, 3) {
+            assert_eq!(reparse(&problem), problem);
+        }
+    }
+
+    #[test]
+    fn test_named_to_string_round_trip() {
+        let problem = LclProblem::new("AC BC", "(x1)(x1) A(x1)").unwrap();
+        assert_eq!(problem.to_string(), "[AB]C; [(x1)A](x1)");
+        assert_eq!(reparse(&problem), problem);
+        assert_eq!(reparse(&problem).get_label_names(), ["A", "B", "C", "x1"]);
+    }
+
+    #[test]
+    fn test_label_names() {
+        let problem = LclProblem::new("MUU PPP", "MM PU UU").unwrap();
+        assert_eq!(problem.get_label_names(), ["M", "P", "U"]);
+        assert_eq!(problem.to_string(), "MUU PPP; MM [PU]U");
+
+        let problem = LclProblem::new("(in)(out)^2", "(in)(out) (out)(out)").unwrap();
+        assert_eq!(problem.get_label_name(1), "out");
+        assert_eq!(problem.to_string(), "(in)(out)^2; [(in)(out)](out)");
+        assert_eq!(
+            LclProblem::new("AB^2", "AB BB").unwrap(),
+            problem,
+            "Names must not affect equality"
+        );
+
+        let unnamed = LclProblem::from_configurations(problem.active, problem.passive);
+        assert_eq!(unnamed.to_string(), "ABB; [AB]B");
+    }
+
+    #[test]
+    fn test_normalize_keeps_label_names() {
+        let mut problem = LclProblem::new("XXX UUM", "MM XU UU").unwrap();
+        problem.normalize();
+
+        let mut expected = LclProblem::new("MUU PPP", "MM PU UU").unwrap();
+        expected.normalize();
+        assert_eq!(problem, expected);
+
+        // Each renamed label still means the same as before normalization.
+        let reparsed = problem.to_string();
+        let (a, p) = reparsed.split_once("; ").unwrap();
+        let mut reparsed = LclProblem::new(a, p).unwrap();
+        reparsed.normalize();
+        assert_eq!(reparsed, expected);
+        let mut names = problem.get_label_names().to_vec();
+        names.sort();
+        assert_eq!(names, ["M", "U", "X"]);
+        assert!(problem.to_string().contains("XXX"));
+    }
+
+    /// Normalizes `problem` by trying every permutation of its labels.
+    fn normalize_with_all_permutations(problem: &LclProblem) -> LclProblem {
+        let label_max = problem
+            .active
+            .get_labels()
+            .into_iter()
+            .chain(problem.passive.get_labels().into_iter())
+            .max()
+            .unwrap();
+
+        (0..=label_max)
+            .permutations(label_max as usize + 1)
+            .map(|perm| {
+                let mut active = problem.active.map_labels(&perm);
+                let mut passive = problem.passive.map_labels(&perm);
+                active.sort();
+                passive.sort();
+                LclProblem::from_configurations(active, passive)
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_normalize_matches_all_permutations() {
+        for mut problem in LclProblem::get_or_generate(3, 2, 3) {
+            let expected = normalize_with_all_permutations(&problem);
+            problem.normalize();
+            assert_eq!(problem, expected);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_normalize_matches_all_permutations(problem in unnamed_problems(6)) {
+            let mut normalized = problem.clone();
+            normalized.normalize();
+            prop_assert_eq!(reparse(&normalized), normalized.clone());
+            prop_assert_eq!(normalized, normalize_with_all_permutations(&problem));
+        }
+    }
+
+    #[test]
+    fn test_normalize_gapped_labels() {
+        let mut problem = LclProblem::from_configurations(
+            Configurations::from_configuration_data(vec![vec![4, 4], vec![2, 4]]).unwrap(),
+            Configurations::from_configuration_data(vec![vec![2, 2]]).unwrap(),
+        );
+        let expected = normalize_with_all_permutations(&problem);
+        problem.normalize();
+        assert_eq!(problem, expected);
+        assert_eq!(problem.to_string(), "A[AB]; BB");
+    }
+
+    #[test]
+    fn test_normalize_many_labels() {
+        // Every label of the ring is interchangeable with its rotations only,
+        // so trying all 12! permutations would not be feasible.
+        let active = (0..12u8).map(|l| vec![l, (l + 1) % 12]).collect_vec();
+        let passive = (0..12u8).map(|l| vec![l, l]).collect_vec();
+        let mut problem = LclProblem::from_configurations(
+            Configurations::from_configuration_data(active).unwrap(),
+            Configurations::from_configuration_data(passive).unwrap(),
+        );
+        problem.normalize();
+        assert_eq!(
+            problem.to_string(),
+            "A[BC] BD CE DF EG FH GI HJ IK [JK]L; AA BB CC DD EE FF GG HH II JJ KK LL"
+        );
+    }
+
     #[test]
     fn test_normalize() {
         const A0: &'static str = "MUU PPP";
@@ -378,6 +869,112 @@ mod tests {
         assert_ne!(problem1, problem2);
     }
 
+    #[test]
+    fn test_normalize_up_to_swap() {
+        let mut problem = LclProblem::new("AA BB", "AB").unwrap();
+        let mut mirror = LclProblem::new("AB", "BB AA").unwrap();
+
+        assert_eq!(
+            problem.clone().normalize_up_to_swap(),
+            Orientation::Original
+        );
+        let mut normalized = problem.clone();
+        normalized.normalize();
+        assert_eq!(problem.normalize_up_to_swap(), Orientation::Original);
+        assert_eq!(problem, normalized);
+
+        assert_eq!(mirror.normalize_up_to_swap(), Orientation::Swapped);
+        assert_eq!(mirror, problem);
+
+        // Problems of different degrees are never swapped.
+        let mut problem = LclProblem::new("AAB", "AB").unwrap();
+        let mut normalized = problem.clone();
+        normalized.normalize();
+        assert_eq!(problem.normalize_up_to_swap(), Orientation::Original);
+        assert_eq!(problem, normalized);
+    }
+
+    #[test]
+    fn test_normalizable_up_to_swap() {
+        let problems = vec![
+            LclProblem::new("AA BB", "AB").unwrap(),
+            LclProblem::new("XY", "XX YY").unwrap(),
+            LclProblem::new("AA", "AA").unwrap(),
+        ];
+        let representatives = problems.normalize_up_to_swap();
+        assert_eq!(representatives.len(), 2);
+        assert_eq!(
+            representatives[0].1,
+            vec![Orientation::Original, Orientation::Swapped]
+        );
+        assert_eq!(representatives[1].1, vec![Orientation::Original]);
+
+        // Every representative maps back to the problems it represents.
+        let class = LclProblem::generate_normalized(2, 2, 2);
+        let representatives = class.clone().normalize_up_to_swap();
+        assert!(representatives.len() < class.len());
+        for (representative, orientations) in representatives {
+            for orientation in orientations {
+                let mut problem = representative.clone();
+                if orientation == Orientation::Swapped {
+                    problem.swap_partitions();
+                }
+                problem.normalize();
+                assert!(class.contains(&problem));
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify() {
+        // B can be replaced by A everywhere.
+        let mut problem = LclProblem::new("AA AB", "AA AB BB").unwrap();
+        assert!(!problem.is_simplified());
+        problem.simplify();
+        assert_eq!(problem.to_string(), "AA; AA");
+        assert!(problem.is_simplified());
+
+        // A and B are interchangeable, so only one of them is kept.
+        let mut problem = LclProblem::new("AA BB", "AA BB").unwrap();
+        problem.simplify();
+        assert_eq!(problem.to_string(), "BB; BB");
+        assert_eq!(problem.active.get_labels(), [0], "Labels must have no gaps");
+
+        // A can be replaced by B, and after that C can be replaced by B.
+        let mut problem = LclProblem::new("AC BC BB", "AB BB CC").unwrap();
+        problem.simplify();
+        assert_eq!(problem.to_string(), "BB; BB");
+
+        let problem = LclProblem::new("MUU PPP", "MM PU UU").unwrap();
+        assert!(problem.is_simplified());
+    }
+
+    #[test]
+    fn test_purge_renumbers_labels() {
+        // Purging removes A, and B and C are renumbered to 0 and 1.
+        let mut problem = LclProblem::new("AA BC CC", "BB BC").unwrap();
+        problem.purge();
+        assert_eq!(problem.active.get_labels(), [0, 1]);
+        assert_eq!(problem.to_string(), "[BC]C; B[BC]");
+        assert_eq!(reparse(&problem), problem);
+
+        let unnamed = LclProblem::from_configurations(problem.active, problem.passive);
+        assert_eq!(unnamed.to_string(), "[AB]B; A[AB]");
+    }
+
+    #[test]
+    fn test_simplified_class() {
+        let class = LclProblem::generate_normalized(3, 2, 3);
+        let all = LclProblem::iter_normalized(3, 2, 3).collect_vec();
+        assert!(class.len() < all.len());
+
+        for mut problem in all {
+            problem.simplify();
+            problem.normalize();
+            assert!(class.contains(&problem));
+        }
+    }
+
     #[test]
     fn test_problems_count() {
         let problems = LclProblem::get_or_generate(3, 2, 3);
@@ -388,14 +985,14 @@ mod tests {
     fn test_normalized_problems_count_0() {
         let problems =
             LclProblem::get_or_generate_normalized::<LclProblemSqliteCache>(2, 1, 2, None);
-        assert_eq!(problems.len(), 5);
+        assert_eq!(problems.len(), 2);
     }
 
     #[test]
     fn test_normalized_problems_count_1() {
         let problems =
             LclProblem::get_or_generate_normalized::<LclProblemSqliteCache>(3, 2, 3, None);
-        assert_eq!(problems.len(), 7735);
+        assert_eq!(problems.len(), 4305);
     }
 
     pub fn generate(
@@ -457,16 +1054,32 @@ mod tests {
     #[test]
     fn test_gen_normalized() {
         assert_eq!(LclProblem::generate_normalized(1, 1, 1).len(), 1);
-        assert_eq!(LclProblem::generate_normalized(1, 1, 2).len(), 2);
-        assert_eq!(LclProblem::generate_normalized(2, 1, 2).len(), 5);
-        assert_eq!(LclProblem::generate_normalized(2, 2, 2).len(), 18);
-        assert_eq!(LclProblem::generate_normalized(3, 2, 2).len(), 38);
-        assert_eq!(LclProblem::generate_normalized(4, 2, 2).len(), 84);
-        assert_eq!(LclProblem::generate_normalized(4, 3, 2).len(), 200);
-        assert_eq!(LclProblem::generate_normalized(4, 4, 2).len(), 446);
-        assert_eq!(LclProblem::generate_normalized(6, 6, 2).len(), 7926);
-        assert_eq!(LclProblem::generate_normalized(2, 2, 3).len(), 419);
-        assert_eq!(LclProblem::generate_normalized(3, 2, 3).len(), 7735);
-        //assert_eq!(LclProblem::generate_normalized(3,3,3).len(), 162299);
+        assert_eq!(LclProblem::generate_normalized(1, 1, 2).len(), 1);
+        assert_eq!(LclProblem::generate_normalized(2, 1, 2).len(), 2);
+        assert_eq!(LclProblem::generate_normalized(2, 2, 2).len(), 9);
+        assert_eq!(LclProblem::generate_normalized(3, 2, 2).len(), 19);
+        assert_eq!(LclProblem::generate_normalized(4, 2, 2).len(), 43);
+        assert_eq!(LclProblem::generate_normalized(4, 3, 2).len(), 107);
+        assert_eq!(LclProblem::generate_normalized(4, 4, 2).len(), 245);
+        assert_eq!(LclProblem::generate_normalized(6, 6, 2).len(), 4437);
+        assert_eq!(LclProblem::generate_normalized(2, 2, 3).len(), 163);
+        assert_eq!(LclProblem::generate_normalized(3, 2, 3).len(), 4305);
+    }
+
+    #[test]
+    fn test_iter_normalized_counts() {
+        let count = |a, p, l| LclProblem::iter_normalized(a, p, l).count();
+        assert_eq!(count(1, 1, 1), 1);
+        assert_eq!(count(1, 1, 2), 2);
+        assert_eq!(count(2, 1, 2), 5);
+        assert_eq!(count(2, 2, 2), 18);
+        assert_eq!(count(3, 2, 2), 38);
+        assert_eq!(count(4, 2, 2), 84);
+        assert_eq!(count(4, 3, 2), 200);
+        assert_eq!(count(4, 4, 2), 446);
+        assert_eq!(count(6, 6, 2), 7926);
+        assert_eq!(count(2, 2, 3), 419);
+        assert_eq!(count(3, 2, 3), 7735);
+        //assert_eq!(count(3, 3, 3), 162299);
     }
 }
@@ -0,0 +1,260 @@
+use super::{canonical, configurations::Configurations, LclProblem};
+use itertools::Itertools;
+
+/// Iterator over the normalized problems of a class.
+///
+/// Yields every purged problem of the class after normalization, each exactly once,
+/// without building the cartesian product of the configuration powersets.
+///
+/// The problems are generated by canonical augmentation, see [`CanonicalSubsets`].
+/// Sets of active configurations are built by adding one configuration at a time,
+/// and a set is extended only if it is normalized by itself.
+/// Then each such set is extended in the same way with passive configurations over its labels,
+/// and only the extensions where the whole problem is normalized are kept.
+///
+/// Created with [`LclProblem::iter_normalized`].
+pub struct NormalizedProblems {
+    active_degree: usize,
+    passive_degree: usize,
+    label_count: u8,
+    shard: (usize, usize),
+    problems: Option<Box<dyn Iterator<Item = LclProblem> + Send>>,
+}
+
+impl NormalizedProblems {
+    pub(crate) fn new(active_degree: usize, passive_degree: usize, label_count: u8) -> Self {
+        Self {
+            active_degree,
+            passive_degree,
+            label_count,
+            shard: (0, 1),
+            problems: None,
+        }
+    }
+
+    /// Yields only part `index` of `count` disjoint parts of the class.
+    ///
+    /// Normalized sets of active configurations are divided by their position in the enumeration modulo `count`,
+    /// and each part extends only its own sets with passive configurations.
+    /// Every part still enumerates the normalized sets of active configurations, which are few compared to the problems.
+    /// Together the parts yield the whole class, so each part can be generated on a different machine.
+    ///
+    /// # Panics
+    /// Panics if `index` is not less than `count`, or if the iteration has already started.
+    pub fn shard(mut self, index: usize, count: usize) -> Self {
+        assert!(
+            index < count,
+            "Shard index ({}) should be less than shard count ({})",
+            index,
+            count
+        );
+        assert!(
+            self.problems.is_none(),
+            "Cannot shard an iteration that has already started"
+        );
+        self.shard = (index, count);
+        self
+    }
+
+    fn generate(&self) -> Box<dyn Iterator<Item = LclProblem> + Send> {
+        let (index, count) = self.shard;
+        let passive_degree = self.passive_degree;
+
+        let active_configurations = (0..self.label_count)
+            .combinations_with_replacement(self.active_degree)
+            .collect_vec();
+
+        let problems = CanonicalSubsets::new(active_configurations, |active: &[Vec<u8>]| {
+            is_normalized(active, &[])
+        })
+        .enumerate()
+        .filter(move |(i, _)| i % count == index)
+        .flat_map(move |(_, active)| {
+            // The labels of a normalized set are 0..k.
+            let used_label_count = active.iter().flatten().unique().count();
+            let passive_configurations = (0..used_label_count as u8)
+                .combinations_with_replacement(passive_degree)
+                .collect_vec();
+
+            let problem_active = active.clone();
+            CanonicalSubsets::new(passive_configurations, move |passive: &[Vec<u8>]| {
+                is_normalized(&active, passive)
+            })
+            .filter(move |passive| passive.iter().flatten().unique().count() == used_label_count)
+            .map(move |passive| {
+                LclProblem::from_configurations(
+                    Configurations::from_configuration_data(problem_active.clone())
+                        .expect("Configurations have the same degree"),
+                    Configurations::from_configuration_data(passive)
+                        .expect("Configurations have the same degree"),
+                )
+            })
+        });
+
+        Box::new(problems)
+    }
+}
+
+/// Checks if the problem with the sorted `active` and `passive` configurations is equal to its normalized form.
+fn is_normalized(active: &[Vec<u8>], passive: &[Vec<u8>]) -> bool {
+    let permutation = canonical::canonical_permutation(active, passive);
+    let normalized = |configurations: &[Vec<u8>]| {
+        let mut configurations = configurations
+            .iter()
+            .map(|configuration| {
+                configuration
+                    .iter()
+                    .map(|&label| permutation[label as usize])
+                    .sorted()
+                    .collect_vec()
+            })
+            .collect_vec();
+        configurations.sort();
+        configurations
+    };
+    normalized(active) == active && normalized(passive) == passive
+}
+
+/// Iterator over the canonical nonempty subsets of `elements`, where a subset is canonical if `is_canonical` accepts it.
+///
+/// Subsets are built in depth-first order by adding elements after the last element of the subset,
+/// and the subsets of a subset that is not canonical are not visited at all.
+/// So each subset is a list of elements in their order.
+///
+/// This finds every canonical subset if removing the last element of a canonical subset leaves a canonical subset.
+/// That holds when the canonical subset is the lexicographically smallest image of the subset
+/// under a group of permutations of the elements, such as relabelings of configurations
+/// that are sorted like in [`LclProblem::normalize`].
+struct CanonicalSubsets<F> {
+    elements: Vec<Vec<u8>>,
+    is_canonical: F,
+    /// Indices of the elements of the current subset.
+    subset: Vec<usize>,
+    /// Index of the element to be added next.
+    next: usize,
+}
+
+impl<F: Fn(&[Vec<u8>]) -> bool> CanonicalSubsets<F> {
+    fn new(elements: Vec<Vec<u8>>, is_canonical: F) -> Self {
+        Self {
+            elements,
+            is_canonical,
+            subset: vec![],
+            next: 0,
+        }
+    }
+}
+
+impl<F: Fn(&[Vec<u8>]) -> bool> Iterator for CanonicalSubsets<F> {
+    type Item = Vec<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.next == self.elements.len() {
+                // Every extension of the subset has been visited.
+                self.next = self.subset.pop()? + 1;
+                continue;
+            }
+
+            self.subset.push(self.next);
+            self.next += 1;
+            let subset = self
+                .subset
+                .iter()
+                .map(|&i| self.elements[i].clone())
+                .collect_vec();
+            if (self.is_canonical)(&subset) {
+                return Some(subset);
+            }
+            self.subset.pop();
+        }
+    }
+}
+
+impl Iterator for NormalizedProblems {
+    type Item = LclProblem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.problems.is_none() {
+            self.problems = Some(self.generate());
+        }
+        self.problems.as_mut().unwrap().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Normalizes every purged problem of the class.
+    fn normalize_all(
+        active_degree: usize,
+        passive_degree: usize,
+        label_count: u8,
+    ) -> HashSet<LclProblem> {
+        LclProblem::get_or_generate(active_degree, passive_degree, label_count)
+            .into_iter()
+            .update(|problem| problem.normalize())
+            .collect()
+    }
+
+    #[test]
+    fn test_iter_normalized() {
+        for &(deg_a, deg_p, labels) in &[(1, 1, 2), (2, 1, 2), (3, 2, 2), (2, 2, 3), (3, 2, 3)] {
+            let problems = LclProblem::iter_normalized(deg_a, deg_p, labels).collect_vec();
+            let unique: HashSet<_> = problems.iter().cloned().collect();
+            assert_eq!(
+                problems.len(),
+                unique.len(),
+                "Problems must be yielded once"
+            );
+            assert_eq!(unique, normalize_all(deg_a, deg_p, labels));
+        }
+    }
+
+    #[test]
+    fn test_canonical_subsets() {
+        let elements = (0..4).map(|i| vec![i]).collect_vec();
+        assert_eq!(
+            CanonicalSubsets::new(elements.clone(), |_| true).count(),
+            15
+        );
+
+        // Subsets of labels are canonical if they are 0..k.
+        let subsets =
+            CanonicalSubsets::new(elements, |subset: &[Vec<u8>]| is_normalized(subset, &[]))
+                .collect_vec();
+        assert_eq!(
+            subsets,
+            [
+                vec![vec![0]],
+                vec![vec![0], vec![1]],
+                vec![vec![0], vec![1], vec![2]],
+                vec![vec![0], vec![1], vec![2], vec![3]],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shard() {
+        let all: HashSet<_> = LclProblem::iter_normalized(3, 2, 3).collect();
+
+        let mut count = 0;
+        let mut union = HashSet::new();
+        for index in 0..4 {
+            for problem in LclProblem::iter_normalized(3, 2, 3).shard(index, 4) {
+                count += 1;
+                union.insert(problem);
+            }
+        }
+        assert_eq!(count, all.len(), "Shards must be disjoint");
+        assert_eq!(union, all);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shard_out_of_range() {
+        let _ = LclProblem::iter_normalized(2, 2, 2).shard(2, 2);
+    }
+}
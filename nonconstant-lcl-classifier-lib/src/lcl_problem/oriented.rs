@@ -0,0 +1,306 @@
+use super::{
+    condensed, configurations::split_configurations, configurations::Configurations,
+    default_label_name, number_labels_by_name, parse_error::LclParseError,
+};
+use crate::{BiregularGraph, SatEncoder, SatResult, SatSolver};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// Direction of an edge with respect to a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EdgeDirection {
+    /// The edge points away from the node, e.g. towards the parent in a rooted tree.
+    Outgoing,
+    /// The edge points to the node, e.g. from a child in a rooted tree.
+    Incoming,
+}
+
+impl EdgeDirection {
+    /// Returns the direction of the same edge with respect to the other endpoint.
+    pub fn reverse(self) -> Self {
+        match self {
+            EdgeDirection::Outgoing => EdgeDirection::Incoming,
+            EdgeDirection::Incoming => EdgeDirection::Outgoing,
+        }
+    }
+}
+
+/// Combines `label` and the `direction` of its edge into a single tagged label.
+///
+/// The tagged label is `2 * label` for outgoing edges and `2 * label + 1` for incoming edges.
+pub fn tag_label(label: u8, direction: EdgeDirection) -> u8 {
+    assert!(
+        label < 128,
+        "Label {} cannot be tagged with a direction",
+        label
+    );
+    match direction {
+        EdgeDirection::Outgoing => 2 * label,
+        EdgeDirection::Incoming => 2 * label + 1,
+    }
+}
+
+/// Splits a tagged label into the label and the direction of its edge, see [`tag_label`].
+pub fn untag_label(tagged: u8) -> (u8, EdgeDirection) {
+    let direction = if tagged & 1 == 0 {
+        EdgeDirection::Outgoing
+    } else {
+        EdgeDirection::Incoming
+    };
+    (tagged / 2, direction)
+}
+
+/// Locally Checkable Labeling problem for graphs with oriented edges, such as rooted trees.
+///
+/// Each position of a configuration is tagged with the direction of its edge
+/// with respect to the node, see [`tag_label`].
+/// A configuration is written as its outgoing labels and its incoming labels separated by `:`,
+/// e.g. `A:BB` is a node with label `A` on its outgoing edge and `B` on two incoming edges.
+/// In a rooted tree the outgoing edge leads to the parent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrientedLclProblem {
+    /// Active configurations of tagged labels.
+    pub active: Configurations,
+    /// Passive configurations of tagged labels.
+    pub passive: Configurations,
+    label_names: Vec<String>,
+}
+
+impl OrientedLclProblem {
+    /// Parses an oriented LCL problem from active configurations `a` and passive configurations `p`.
+    ///
+    /// Configurations are separated as in [`Configurations::from_string`],
+    /// and both sides of `:` are in the condensed notation.
+    /// Either side can be empty, e.g. `:AA` is a node without outgoing edges.
+    /// Labels are numbered in the order of their names, as in [`crate::LclProblem::new`].
+    ///
+    /// # Errors
+    /// Returns [`LclParseError`] if a configuration has no `:`, is malformed or has a different width than the others,
+    /// or if there are more than 128 labels.
+    pub fn new(a: &str, p: &str) -> Result<Self, LclParseError> {
+        let mut label_map: HashMap<String, u8> = HashMap::new();
+        let active = parse_oriented(a, &mut label_map)?;
+        let passive = parse_oriented(p, &mut label_map)?;
+        let (permutation, label_names) = number_labels_by_name(label_map);
+
+        let permutation = &permutation;
+        let tag = |configurations: Vec<OrientedConfiguration>| {
+            let data = configurations
+                .into_iter()
+                .map(|(outgoing, incoming)| {
+                    let tagged = |labels: Vec<u8>, direction| {
+                        labels
+                            .into_iter()
+                            .map(move |label| tag_label(permutation[label as usize], direction))
+                    };
+                    tagged(outgoing, EdgeDirection::Outgoing)
+                        .chain(tagged(incoming, EdgeDirection::Incoming))
+                        .collect_vec()
+                })
+                .collect_vec();
+            Configurations::from_configuration_data(data)
+        };
+        Ok(Self {
+            active: tag(active)?,
+            passive: tag(passive)?,
+            label_names,
+        })
+    }
+
+    /// Returns the name of `label`, which is not tagged.
+    ///
+    /// Labels without a name get a default name, see [`default_label_name`].
+    pub fn get_label_name(&self, label: u8) -> String {
+        self.label_names
+            .get(label as usize)
+            .cloned()
+            .unwrap_or_else(|| default_label_name(label))
+    }
+
+    /// Returns the counts of outgoing edges that the active configurations have, in increasing order.
+    pub fn get_active_out_degrees(&self) -> Vec<usize> {
+        get_out_degrees(&self.active)
+    }
+
+    /// Returns the counts of outgoing edges that the passive configurations have, in increasing order.
+    pub fn get_passive_out_degrees(&self) -> Vec<usize> {
+        get_out_degrees(&self.passive)
+    }
+
+    /// Finds an orientation of `graph` on which the problem is unsolvable.
+    ///
+    /// Only orientations where the counts of outgoing edges of active and passive nodes
+    /// are in `active_out_degrees` and `passive_out_degrees` are tried,
+    /// see [`BiregularGraph::get_orientations`].
+    /// For rooted trees both are `[1]`.
+    ///
+    /// Returns the directions of the edges with respect to their active nodes,
+    /// or `None` if the problem is solvable with every orientation.
+    pub fn find_unsolvable_orientation(
+        &self,
+        graph: &BiregularGraph,
+        active_out_degrees: &[usize],
+        passive_out_degrees: &[usize],
+    ) -> Option<Vec<EdgeDirection>> {
+        graph
+            .get_orientations(active_out_degrees, passive_out_degrees)
+            .into_iter()
+            .find(|orientation| {
+                let encoder = SatEncoder::with_orientation(self, graph.clone(), orientation);
+                SatSolver::solve(encoder.encode()) == SatResult::Unsatisfiable
+            })
+    }
+}
+
+/// Returns the counts of outgoing edges in `configurations` of tagged labels, in increasing order.
+fn get_out_degrees(configurations: &Configurations) -> Vec<usize> {
+    configurations
+        .get_configurations()
+        .iter()
+        .map(|configuration| {
+            configuration
+                .iter()
+                .filter(|&&tagged| untag_label(tagged).1 == EdgeDirection::Outgoing)
+                .count()
+        })
+        .unique()
+        .sorted()
+        .collect()
+}
+
+/// Outgoing and incoming labels of a configuration before tagging.
+type OrientedConfiguration = (Vec<u8>, Vec<u8>);
+
+/// Parses oriented configurations into pairs of outgoing and incoming labels.
+fn parse_oriented(
+    encoding: &str,
+    label_map: &mut HashMap<String, u8>,
+) -> Result<Vec<OrientedConfiguration>, LclParseError> {
+    let tokens = split_configurations(encoding);
+    if tokens.is_empty() {
+        return Err(LclParseError::EmptyPartition);
+    }
+
+    let mut configurations = vec![];
+    for (position, token) in tokens.into_iter().enumerate() {
+        let (outgoing, incoming) =
+            token
+                .split_once(':')
+                .ok_or_else(|| LclParseError::MissingDirection {
+                    token: token.to_string(),
+                    position,
+                })?;
+        let mut expand = |part: &str| {
+            if part.trim().is_empty() {
+                return Ok(vec![vec![]]);
+            }
+            condensed::expand_configuration(part, position, None, label_map)
+        };
+        let outgoing = expand(outgoing)?;
+        let incoming = expand(incoming)?;
+        if label_map.len() > 128 {
+            return Err(LclParseError::TooManyLabels {
+                token: token.to_string(),
+                position,
+            });
+        }
+        configurations.extend(outgoing.into_iter().cartesian_product(incoming));
+    }
+    Ok(configurations)
+}
+
+impl fmt::Display for OrientedLclProblem {
+    /// Writes the problem in the format accepted by [`OrientedLclProblem::new`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format_side = |configurations: &Configurations| {
+            configurations
+                .get_configurations()
+                .iter()
+                .map(|configuration| {
+                    let part = |direction| {
+                        let labels = configuration
+                            .iter()
+                            .map(|&tagged| untag_label(tagged))
+                            .filter(|&(_, d)| d == direction)
+                            .map(|(label, _)| label)
+                            .sorted()
+                            .collect_vec();
+                        if labels.is_empty() {
+                            return String::new();
+                        }
+                        condensed::format_configurations(&[labels], |l| self.get_label_name(l))
+                    };
+                    format!(
+                        "{}:{}",
+                        part(EdgeDirection::Outgoing),
+                        part(EdgeDirection::Incoming)
+                    )
+                })
+                .join(" ")
+        };
+        write!(
+            f,
+            "{}; {}",
+            format_side(&self.active),
+            format_side(&self.passive)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let problem = OrientedLclProblem::new("A:B B:A", "A:B B:A").unwrap();
+        assert_eq!(
+            problem.active.get_configurations(),
+            &vec![vec![0, 3], vec![2, 1]]
+        );
+        assert_eq!(problem.get_active_out_degrees(), vec![1]);
+        assert_eq!(problem.to_string(), "A:B B:A; A:B B:A");
+
+        let problem = OrientedLclProblem::new(":AA A:A", "A:A").unwrap();
+        assert_eq!(problem.get_active_out_degrees(), vec![0, 1]);
+        assert_eq!(problem.to_string(), ":AA A:A; A:A");
+
+        assert_eq!(
+            OrientedLclProblem::new("AB", "A:B"),
+            Err(LclParseError::MissingDirection {
+                token: "AB".to_string(),
+                position: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_unsolvable_orientation() {
+        let graph = BiregularGraph::cycle(2);
+
+        // Labels alternate along a directed cycle, which works on a cycle of even length.
+        let problem = OrientedLclProblem::new("A:B B:A", "A:A B:B").unwrap();
+        assert_eq!(
+            problem.find_unsolvable_orientation(&graph, &[1], &[1]),
+            None
+        );
+
+        // Labels increase by one modulo 3 at every active node, which fails after two steps.
+        let problem = OrientedLclProblem::new("B:A C:B A:C", "A:A B:B C:C").unwrap();
+        assert!(problem
+            .find_unsolvable_orientation(&graph, &[1], &[1])
+            .is_some());
+
+        // Only some tagged labels are used, e.g. A is never incoming on an active node.
+        let problem = OrientedLclProblem::new("A:B", "B:A").unwrap();
+        assert_eq!(
+            problem.find_unsolvable_orientation(&graph, &[1], &[1]),
+            None
+        );
+        let problem = OrientedLclProblem::new("A:B", "A:B").unwrap();
+        assert!(problem
+            .find_unsolvable_orientation(&graph, &[1], &[1])
+            .is_some());
+    }
+}
@@ -0,0 +1,69 @@
+use std::{error::Error, fmt};
+
+/// Error returned when an LCL problem or its configurations cannot be parsed.
+///
+/// Variants that point at a single configuration carry the offending `token`
+/// and its `position`, which is the zero-based index of the configuration in its partition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LclParseError {
+    /// The partition contains no configurations, or its configurations contain no labels.
+    EmptyPartition,
+    /// The configuration has a different number of labels than the first configuration.
+    MixedWidths {
+        token: String,
+        position: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The configuration introduces a new label when all `u8` labels are already in use.
+    TooManyLabels { token: String, position: usize },
+    /// The configuration contains a character that cannot be used as a label.
+    BadCharacter {
+        token: String,
+        position: usize,
+        character: char,
+    },
+    /// The oriented configuration has no `:` between its outgoing and incoming labels.
+    MissingDirection { token: String, position: usize },
+}
+
+impl fmt::Display for LclParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LclParseError::EmptyPartition => write!(f, "partition has no configurations"),
+            LclParseError::MixedWidths {
+                token,
+                position,
+                expected,
+                found,
+            } => write!(
+                f,
+                "configuration '{}' at position {} has {} labels, expected {}",
+                token, position, found, expected
+            ),
+            LclParseError::TooManyLabels { token, position } => write!(
+                f,
+                "configuration '{}' at position {} exceeds the maximum of {} labels",
+                token,
+                position,
+                u8::MAX as usize + 1
+            ),
+            LclParseError::BadCharacter {
+                token,
+                position,
+                character,
+            } => write!(
+                f,
+                "configuration '{}' at position {} contains invalid label character '{}'",
+                token, position, character
+            ),
+            LclParseError::MissingDirection { token, position } => write!(
+                f,
+                "oriented configuration '{}' at position {} has no ':' between outgoing and incoming labels",
+                token, position
+            ),
+        }
+    }
+}
+
+impl Error for LclParseError {}
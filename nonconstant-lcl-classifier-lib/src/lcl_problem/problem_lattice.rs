@@ -0,0 +1,281 @@
+use super::LclProblem;
+use itertools::Itertools;
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// Status of a problem on a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphStatus {
+    /// The problem can be solved on the graph.
+    Solvable,
+    /// The problem cannot be solved on the graph, i.e. the graph is a counterexample.
+    Unsolvable,
+}
+
+/// Problems of a class ordered by restriction.
+///
+/// Problem Q is a restriction of problem P if the configurations of Q are subsets of
+/// the configurations of P under some mapping of labels.
+/// If P cannot be solved on a graph, neither can Q,
+/// and if Q can be solved on a graph, so can P.
+///
+/// Direct restrictions of a problem are found by removing a single configuration,
+/// and then purging and normalizing the result.
+/// Classes such as [`LclProblem::generate_normalized`] contain only simplified problems,
+/// so each direct restriction is also simplified, see [`LclProblem::simplify`].
+/// Simplifying only removes configurations, so the simplified problem is still a restriction,
+/// and it is solvable on the same graphs as the direct restriction.
+/// A problem is linked to the direct restrictions and their simplified problems that are in the class.
+///
+/// Statuses are inferred transitively through these links,
+/// but a restriction in the class is not necessarily reached from every problem that it restricts.
+///
+/// Problems are referred to by their index in the slice given to [`ProblemLattice::new`].
+/// Graphs are referred to by keys of type `K`.
+pub struct ProblemLattice<K> {
+    restrictions: Vec<Vec<usize>>,
+    relaxations: Vec<Vec<usize>>,
+    statuses: HashMap<K, Vec<Option<GraphStatus>>>,
+}
+
+impl<K: Hash + Eq> ProblemLattice<K> {
+    /// Indexes `problems` by restriction.
+    ///
+    /// The problems should be purged and they should have the same degrees.
+    pub fn new(problems: &[LclProblem]) -> Self {
+        let normalized = problems
+            .iter()
+            .map(|problem| {
+                let mut problem = problem.clone();
+                problem.normalize();
+                problem
+            })
+            .collect_vec();
+        let indices: HashMap<&LclProblem, usize> = normalized
+            .iter()
+            .enumerate()
+            .map(|(index, problem)| (problem, index))
+            .collect();
+
+        let mut restrictions = vec![vec![]; problems.len()];
+        let mut relaxations = vec![vec![]; problems.len()];
+        for (index, problem) in normalized.iter().enumerate() {
+            for restriction in get_direct_restrictions(problem) {
+                if let Some(&restriction_index) = indices.get(&restriction) {
+                    if restriction_index != index
+                        && !restrictions[index].contains(&restriction_index)
+                    {
+                        restrictions[index].push(restriction_index);
+                        relaxations[restriction_index].push(index);
+                    }
+                }
+            }
+        }
+
+        Self {
+            restrictions,
+            relaxations,
+            statuses: HashMap::new(),
+        }
+    }
+
+    /// Returns the indices of the direct restrictions of problem `index`.
+    pub fn get_restrictions(&self, index: usize) -> &[usize] {
+        &self.restrictions[index]
+    }
+
+    /// Returns the indices of the direct relaxations of problem `index`.
+    pub fn get_relaxations(&self, index: usize) -> &[usize] {
+        &self.relaxations[index]
+    }
+
+    /// Returns the known status of problem `index` on `graph`.
+    pub fn get_status(&self, index: usize, graph: &K) -> Option<GraphStatus> {
+        self.statuses
+            .get(graph)
+            .and_then(|statuses| statuses[index])
+    }
+
+    /// Sets the status of problem `index` on `graph` and propagates it.
+    ///
+    /// [`GraphStatus::Unsolvable`] is propagated to all restrictions and
+    /// [`GraphStatus::Solvable`] to all relaxations.
+    /// Returns the count of other problems whose status was inferred.
+    pub fn set_status(&mut self, index: usize, graph: K, status: GraphStatus) -> usize {
+        let neighbors = match status {
+            GraphStatus::Unsolvable => &self.restrictions,
+            GraphStatus::Solvable => &self.relaxations,
+        };
+        let problem_count = neighbors.len();
+        let statuses = self
+            .statuses
+            .entry(graph)
+            .or_insert_with(|| vec![None; problem_count]);
+
+        if statuses[index].is_some() {
+            return 0;
+        }
+        statuses[index] = Some(status);
+
+        let mut inferred = 0;
+        let mut queue = VecDeque::from(vec![index]);
+        while let Some(current) = queue.pop_front() {
+            for &next in &neighbors[current] {
+                if statuses[next].is_none() {
+                    statuses[next] = Some(status);
+                    inferred += 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        inferred
+    }
+}
+
+/// Returns the problems that are left after removing one configuration from `problem`,
+/// followed by the simplified problem of each of them.
+///
+/// The problems are purged and normalized, and problems with an empty partition are skipped.
+fn get_direct_restrictions(problem: &LclProblem) -> Vec<LclProblem> {
+    let active_count = problem.active.get_configuration_count();
+    let passive_count = problem.passive.get_configuration_count();
+
+    (0..active_count)
+        .map(|index| (true, index))
+        .chain((0..passive_count).map(|index| (false, index)))
+        .filter_map(|(active, index)| {
+            let mut restriction = problem.clone();
+            let configurations = if active {
+                &mut restriction.active
+            } else {
+                &mut restriction.passive
+            };
+            configurations.get_configuration_mut().remove(index);
+            restriction.purge();
+            if restriction.contains_empty_partition() {
+                return None;
+            }
+            let mut simplified = restriction.clone();
+            simplified.simplify();
+            restriction.normalize();
+            simplified.normalize();
+            Some([restriction, simplified])
+        })
+        .flatten()
+        .filter(|restriction| !restriction.contains_empty_partition())
+        .unique()
+        .collect_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Configurations;
+
+    #[test]
+    fn test_restrictions() {
+        let problems = vec![
+            LclProblem::new("AA AB", "AB BB").unwrap(),
+            LclProblem::new("AB", "AB").unwrap(),
+            LclProblem::new("AA", "AA").unwrap(),
+            LclProblem::new("AB", "AB BB").unwrap(),
+        ];
+        let lattice = ProblemLattice::<usize>::new(&problems);
+
+        // Problem 2 is not a restriction of any other problem.
+        assert_eq!(lattice.get_restrictions(0), [3]);
+        assert_eq!(lattice.get_restrictions(3), [1]);
+        assert!(lattice.get_restrictions(1).is_empty());
+        assert!(lattice.get_restrictions(2).is_empty());
+        assert_eq!(lattice.get_relaxations(1), [3]);
+        assert_eq!(lattice.get_relaxations(3), [0]);
+    }
+
+    #[test]
+    fn test_simplified_restrictions() {
+        let problems = vec![
+            LclProblem::new("A[ABC]", "A[BC] BC").unwrap(),
+            LclProblem::new("A[AB]", "AB").unwrap(),
+        ];
+        // Removing AC from the passive configurations gives a problem that is not simplified,
+        // and that is not in the class, but its simplified problem is.
+        let restriction = LclProblem::new("A[ABC]", "AB BC").unwrap();
+        assert!(!restriction.is_simplified());
+        let mut simplified = restriction;
+        simplified.simplify();
+        simplified.normalize();
+        assert_eq!(simplified, problems[1]);
+
+        let mut lattice = ProblemLattice::new(&problems);
+        assert_eq!(lattice.get_restrictions(0), [1]);
+        assert_eq!(lattice.set_status(0, "G", GraphStatus::Unsolvable), 1);
+        assert_eq!(lattice.get_status(1, &"G"), Some(GraphStatus::Unsolvable));
+    }
+
+    #[test]
+    fn test_set_status() {
+        let problems = vec![
+            LclProblem::new("AA AB", "AB BB").unwrap(),
+            LclProblem::new("AB", "AB BB").unwrap(),
+            LclProblem::new("AB", "AB").unwrap(),
+            LclProblem::new("AA", "AA").unwrap(),
+        ];
+        let mut lattice = ProblemLattice::new(&problems);
+
+        assert_eq!(lattice.set_status(1, "G", GraphStatus::Unsolvable), 1);
+        assert_eq!(lattice.get_status(2, &"G"), Some(GraphStatus::Unsolvable));
+        assert_eq!(lattice.get_status(0, &"G"), None);
+        assert_eq!(lattice.get_status(2, &"H"), None);
+
+        assert_eq!(lattice.set_status(2, "H", GraphStatus::Solvable), 2);
+        assert_eq!(lattice.get_status(0, &"H"), Some(GraphStatus::Solvable));
+        assert_eq!(lattice.get_status(3, &"H"), None);
+
+        // Known statuses are not overwritten.
+        assert_eq!(lattice.set_status(0, "H", GraphStatus::Unsolvable), 0);
+        assert_eq!(lattice.get_status(0, &"H"), Some(GraphStatus::Solvable));
+    }
+
+    /// Checks if `restriction` is a restriction of `problem` by trying every mapping of labels.
+    fn is_restriction(restriction: &LclProblem, problem: &LclProblem) -> bool {
+        let label_count = |p: &LclProblem| p.active.get_labels_set().len() as u8;
+        let is_subset = |mapping: &Vec<u8>, from: &Configurations, to: &Configurations| {
+            let mut mapped = from.map_labels(mapping);
+            mapped.sort();
+            let mut to = to.clone();
+            to.sort();
+            mapped
+                .get_configurations()
+                .iter()
+                .all(|configuration| to.get_configurations().contains(configuration))
+        };
+
+        (0..label_count(restriction))
+            .map(|_| 0..label_count(problem))
+            .multi_cartesian_product()
+            .any(|mapping| {
+                is_subset(&mapping, &restriction.active, &problem.active)
+                    && is_subset(&mapping, &restriction.passive, &problem.passive)
+            })
+    }
+
+    #[test]
+    fn test_restrictions_are_restrictions() {
+        for (active_degree, passive_degree, label_count) in [(3, 2, 2), (2, 2, 3)] {
+            let problems =
+                LclProblem::generate_normalized(active_degree, passive_degree, label_count);
+            let lattice = ProblemLattice::<usize>::new(&problems);
+
+            let mut edge_count = 0;
+            for (index, problem) in problems.iter().enumerate() {
+                for &restriction_index in lattice.get_restrictions(index) {
+                    edge_count += 1;
+                    assert!(is_restriction(&problems[restriction_index], problem));
+                }
+            }
+            assert!(edge_count > 0);
+        }
+    }
+}
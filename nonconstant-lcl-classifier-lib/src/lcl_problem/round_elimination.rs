@@ -0,0 +1,273 @@
+use super::{
+    configurations::{Configurations, LabelSet},
+    zero_round::ZeroRoundWitness,
+    LclProblem,
+};
+use itertools::Itertools;
+
+/// Result of iterating round elimination with [`LclProblem::iterate_round_elimination`].
+#[derive(Debug, Clone)]
+pub struct RoundElimination {
+    /// The original problem followed by the simplified and normalized problem after each step.
+    pub problems: Vec<LclProblem>,
+    pub outcome: RoundEliminationOutcome,
+}
+
+/// Reason why round elimination stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundEliminationOutcome {
+    /// The last problem is solvable in 0 rounds.
+    ///
+    /// The original problem is solvable in `problems.len() - 1` rounds on high-girth graphs.
+    ZeroRound(ZeroRoundWitness),
+    /// The last problem is equal to the problem at the given index, up to renaming of labels.
+    ///
+    /// None of the problems is solvable in 0 rounds, which suggests that the original problem
+    /// is not solvable in a constant number of rounds.
+    /// This is only evidence and not a proof of a lower bound, such as Ω(log n) rounds,
+    /// which needs the fixed point to hold on high-girth graphs.
+    FixedPoint(usize),
+    /// The next problem would have no configurations in some partition,
+    /// so the original problem is not solvable in a constant number of rounds.
+    Unsolvable,
+    /// The next problem would have more labels than the budget,
+    /// or the current problem has labels that do not fit in [`LabelSet`].
+    LabelBudgetExceeded,
+}
+
+/// Result of a single round elimination operator, see [`LclProblem::round_eliminate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundEliminationStep {
+    /// The purged result of the operator.
+    Problem(LclProblem),
+    /// The result would have no configurations in some partition.
+    Unsolvable,
+    /// The result would have more labels than fit in `u8`,
+    /// or the problem has labels that do not fit in [`LabelSet`].
+    LabelBudgetExceeded,
+}
+
+impl RoundEliminationStep {
+    /// Returns the problem of a [`RoundEliminationStep::Problem`].
+    pub fn into_problem(self) -> Option<LclProblem> {
+        match self {
+            RoundEliminationStep::Problem(problem) => Some(problem),
+            RoundEliminationStep::Unsolvable | RoundEliminationStep::LabelBudgetExceeded => None,
+        }
+    }
+}
+
+impl LclProblem {
+    /// Applies the round elimination operator R.
+    ///
+    /// The passive configurations of the result are the maximal configurations of label sets
+    /// whose every choice is a passive configuration, see [`Configurations::get_maximal_set_configurations`].
+    /// Each set is a new label.
+    /// The active configurations are the configurations of new labels
+    /// where some choice is an active configuration, see [`Configurations::contains_choice`].
+    /// The result is purged and its labels have no names.
+    ///
+    /// Returns [`RoundEliminationStep::Unsolvable`] if the result has an empty partition,
+    /// and [`RoundEliminationStep::LabelBudgetExceeded`] if the problem has labels that do not fit in [`LabelSet`]
+    /// or the result has more labels than fit in `u8`.
+    pub fn round_eliminate(&self) -> RoundEliminationStep {
+        match eliminate(&self.passive, &self.active, usize::from(u8::MAX) + 1) {
+            RoundEliminationStep::Problem(mut problem) => {
+                problem.swap_partitions();
+                RoundEliminationStep::Problem(problem)
+            }
+            step => step,
+        }
+    }
+
+    /// Applies the round elimination operator R̄.
+    ///
+    /// This is the same as [`Self::round_eliminate`] with the roles of the partitions swapped:
+    /// the active configurations are maximal and the passive configurations exist.
+    /// A full step of round elimination is R̄ applied to the result of R.
+    pub fn round_eliminate_bar(&self) -> RoundEliminationStep {
+        eliminate(&self.active, &self.passive, usize::from(u8::MAX) + 1)
+    }
+
+    /// Applies full steps of round elimination until the result is known.
+    ///
+    /// Each step applies R and then R̄, after which the problem is simplified and normalized.
+    /// Stops when the current problem is solvable in 0 rounds, when a problem repeats,
+    /// when a step gives an empty partition or when a step would have more than `label_budget` labels.
+    /// The budget is at most the count of labels that fit in [`LabelSet`].
+    ///
+    /// The problems are returned, so they can be searched for counterexamples with [`crate::SatEncoder`].
+    pub fn iterate_round_elimination(&self, label_budget: usize) -> RoundElimination {
+        let label_budget = label_budget.min(LabelSet::BITS as usize);
+        let mut problems = vec![self.clone()];
+        let mut current = self.clone();
+
+        let outcome = loop {
+            if let Some(witness) = current.find_zero_round_witness() {
+                break RoundEliminationOutcome::ZeroRound(witness);
+            }
+
+            let step = match eliminate(&current.passive, &current.active, label_budget) {
+                RoundEliminationStep::Problem(mut problem) => {
+                    problem.swap_partitions();
+                    eliminate(&problem.active, &problem.passive, label_budget)
+                }
+                step => step,
+            };
+            let mut next = match step {
+                RoundEliminationStep::Problem(problem) => problem,
+                RoundEliminationStep::Unsolvable => break RoundEliminationOutcome::Unsolvable,
+                RoundEliminationStep::LabelBudgetExceeded => {
+                    break RoundEliminationOutcome::LabelBudgetExceeded
+                }
+            };
+            next.simplify();
+            next.normalize();
+
+            let repeated = problems.iter().skip(1).position(|problem| *problem == next);
+            problems.push(next.clone());
+            if let Some(index) = repeated {
+                break RoundEliminationOutcome::FixedPoint(index + 1);
+            }
+            current = next;
+        };
+
+        RoundElimination { problems, outcome }
+    }
+}
+
+/// Builds a problem with the maximal set configurations of `universal` as the first partition,
+/// and the configurations of the same sets with a choice in `existential` as the second partition.
+fn eliminate(
+    universal: &Configurations,
+    existential: &Configurations,
+    label_budget: usize,
+) -> RoundEliminationStep {
+    let fits = |configurations: &Configurations| {
+        configurations
+            .get_labels()
+            .iter()
+            .all(|&label| u32::from(label) < LabelSet::BITS)
+    };
+    if !fits(universal) || !fits(existential) {
+        return RoundEliminationStep::LabelBudgetExceeded;
+    }
+
+    let maximal = universal.get_maximal_set_configurations();
+    let sets = maximal
+        .iter()
+        .flatten()
+        .copied()
+        .unique()
+        .sorted()
+        .collect_vec();
+    if sets.len() > label_budget {
+        return RoundEliminationStep::LabelBudgetExceeded;
+    }
+    let label = |set: &LabelSet| sets.binary_search(set).unwrap() as u8;
+
+    let universal_data = maximal
+        .iter()
+        .map(|configuration| configuration.iter().map(label).sorted().collect_vec())
+        .collect_vec();
+    let existential_data = (0..sets.len())
+        .combinations_with_replacement(existential.get_labels_per_configuration())
+        .filter(|configuration| {
+            let choice = configuration.iter().map(|&label| sets[label]).collect_vec();
+            existential.contains_choice(&choice)
+        })
+        .map(|configuration| {
+            configuration
+                .into_iter()
+                .map(|label| label as u8)
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let (universal, existential) = match (
+        Configurations::from_configuration_data(universal_data),
+        Configurations::from_configuration_data(existential_data),
+    ) {
+        (Ok(universal), Ok(existential)) => (universal, existential),
+        _ => return RoundEliminationStep::Unsolvable,
+    };
+    let mut problem = LclProblem::from_configurations(universal, existential);
+    problem.purge();
+    if problem.contains_empty_partition() {
+        return RoundEliminationStep::Unsolvable;
+    }
+    RoundEliminationStep::Problem(problem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_eliminate() {
+        // Sinkless orientation: an edge is outgoing (O) on one side and incoming (I) on the other.
+        let problem = LclProblem::new("O[IO]^2", "IO").unwrap();
+
+        let r = problem.round_eliminate().into_problem().unwrap();
+        // The only passive set configuration is [I] [O].
+        assert_eq!(r.passive.get_configurations(), &vec![vec![0, 1]]);
+        assert_eq!(r.active.get_labels_per_configuration(), 3);
+
+        let r_bar = r.round_eliminate_bar().into_problem().unwrap();
+        assert_eq!(r_bar.active.get_labels_per_configuration(), 3);
+        assert_eq!(r_bar.passive.get_labels_per_configuration(), 2);
+    }
+
+    #[test]
+    fn test_round_eliminate_unsolvable() {
+        let problem = LclProblem::new("AA", "BB").unwrap();
+        assert_eq!(problem.round_eliminate(), RoundEliminationStep::Unsolvable);
+    }
+
+    #[test]
+    fn test_zero_round() {
+        let problem = LclProblem::new("AB", "AA AB BB").unwrap();
+        let result = problem.iterate_round_elimination(8);
+        assert_eq!(result.problems, vec![problem]);
+        assert_eq!(
+            result.outcome,
+            RoundEliminationOutcome::ZeroRound(ZeroRoundWitness::Active(vec![0, 1]))
+        );
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        // Sinkless orientation is a fixed point after the first step.
+        let problem = LclProblem::new("O[IO]^2", "IO").unwrap();
+        let result = problem.iterate_round_elimination(8);
+        assert_eq!(result.problems.len(), 3);
+        assert_eq!(result.problems[1], result.problems[2]);
+        assert_eq!(result.outcome, RoundEliminationOutcome::FixedPoint(1));
+    }
+
+    #[test]
+    fn test_label_budget() {
+        let problem = LclProblem::new("ABC", "AB AC BC").unwrap();
+        let result = problem.iterate_round_elimination(1);
+        assert_eq!(result.problems.len(), 1);
+        assert_eq!(result.outcome, RoundEliminationOutcome::LabelBudgetExceeded);
+    }
+
+    #[test]
+    fn test_too_many_labels() {
+        // Label 64 does not fit in a label set.
+        let configurations = Configurations::from_configuration_data(vec![vec![0, 64]]).unwrap();
+        let problem = LclProblem::from_configurations(configurations.clone(), configurations);
+        assert_eq!(
+            problem.round_eliminate(),
+            RoundEliminationStep::LabelBudgetExceeded
+        );
+        assert_eq!(
+            problem.round_eliminate_bar(),
+            RoundEliminationStep::LabelBudgetExceeded
+        );
+        let result = problem.iterate_round_elimination(256);
+        assert_eq!(result.problems, vec![problem]);
+        assert_eq!(result.outcome, RoundEliminationOutcome::LabelBudgetExceeded);
+    }
+}
@@ -0,0 +1,86 @@
+use super::{configurations::Configurations, LclProblem};
+use itertools::Itertools;
+use std::collections::HashSet;
+
+/// Configuration that solves a problem in 0 rounds in the port numbering model.
+///
+/// Every node of one side outputs the same configuration, in any port order.
+/// This works on every graph if each multiset of the labels in the configuration,
+/// with the degree of the other side, is a configuration of the other side.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ZeroRoundWitness {
+    /// Every active node outputs this active configuration.
+    Active(Vec<u8>),
+    /// Every passive node outputs this passive configuration.
+    Passive(Vec<u8>),
+}
+
+impl LclProblem {
+    /// Finds a configuration that solves the problem without communication on any graph.
+    ///
+    /// Such problems are solvable in constant time, so they never have counterexamples.
+    /// Returns `None` if no single configuration works regardless of the port numbering,
+    /// see [`ZeroRoundWitness`].
+    pub fn find_zero_round_witness(&self) -> Option<ZeroRoundWitness> {
+        if let Some(configuration) = find_witness(&self.active, &self.passive) {
+            return Some(ZeroRoundWitness::Active(configuration));
+        }
+        find_witness(&self.passive, &self.active).map(ZeroRoundWitness::Passive)
+    }
+}
+
+/// Returns a configuration of `own` whose labels are accepted together by `other` in any combination.
+fn find_witness(own: &Configurations, other: &Configurations) -> Option<Vec<u8>> {
+    let other_degree = other.get_labels_per_configuration();
+    let other_configurations: HashSet<Vec<u8>> = other
+        .get_configurations()
+        .iter()
+        .map(|configuration| configuration.iter().copied().sorted().collect_vec())
+        .collect();
+
+    own.get_configurations()
+        .iter()
+        .find(|configuration| {
+            configuration
+                .iter()
+                .copied()
+                .sorted()
+                .dedup()
+                .combinations_with_replacement(other_degree)
+                .all(|combination| other_configurations.contains(&combination))
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_witness() {
+        let problem = LclProblem::new("AB", "AA AB BB").unwrap();
+        assert_eq!(
+            problem.find_zero_round_witness(),
+            Some(ZeroRoundWitness::Active(vec![0, 1]))
+        );
+    }
+
+    #[test]
+    fn test_passive_witness() {
+        let problem = LclProblem::new("AAA AAB ABB BBB", "AB").unwrap();
+        assert_eq!(
+            problem.find_zero_round_witness(),
+            Some(ZeroRoundWitness::Passive(vec![0, 1]))
+        );
+    }
+
+    #[test]
+    fn test_no_witness() {
+        // Port numbering decides which labels meet on an edge.
+        let problem = LclProblem::new("AB", "AB").unwrap();
+        assert_eq!(problem.find_zero_round_witness(), None);
+
+        let problem = LclProblem::new("MUU PPP", "MM PU UU").unwrap();
+        assert_eq!(problem.find_zero_round_witness(), None);
+    }
+}
@@ -1,14 +1,39 @@
 pub mod caches;
+mod certificate;
+mod classifier;
 mod graph_utils;
 pub mod lcl_problem;
+pub mod pn_solver;
 pub mod sat_encoder;
 pub mod sat_solver;
+#[cfg(test)]
+mod test_utils;
 
+pub use certificate::{Certificate, CertificateEdge, VerificationMethod, CERTIFICATE_VERSION};
+pub use classifier::{
+    Classification, ClassificationResult, Classifier, Counterexample, UnknownGraph,
+};
 pub use graph_utils::{save_as_svg, BiregularGraph, DotFormat, UndirectedGraph};
-pub use lcl_problem::configurations::Configurations;
+pub use lcl_problem::configurations::{Configurations, LabelSet};
+pub use lcl_problem::general::GeneralLclProblem;
+pub use lcl_problem::inputs::{InputAssignment, LclProblemWithInputs};
+pub use lcl_problem::mixed_degree::MixedDegreeLclProblem;
+pub use lcl_problem::normalized_problems::NormalizedProblems;
+pub use lcl_problem::oriented::{EdgeDirection, OrientedLclProblem};
+pub use lcl_problem::parse_error::LclParseError;
+pub use lcl_problem::problem_lattice::{GraphStatus, ProblemLattice};
+pub use lcl_problem::round_elimination::{
+    RoundElimination, RoundEliminationOutcome, RoundEliminationStep,
+};
+pub use lcl_problem::zero_round::ZeroRoundWitness;
 pub use lcl_problem::LclProblem;
-pub use sat_encoder::SatEncoder;
-pub use sat_solver::{SatResult, SatSolver};
+pub use pn_solver::{brute_force::BruteForceSolver, PnSolver, SatPnSolver};
+pub use sat_encoder::{
+    at_most_one::AtMostOneEncoding, encoding::SatEncoding, labeling::Labeling, EncodingStatistics,
+    SatEncoder, ENCODING_VERSION,
+};
+pub use sat_solver::drat::{DratError, DratProof};
+pub use sat_solver::{Model, SatLimits, SatResult, SatSolver};
 //pub use caches::{GraphCacheParams, GraphSqliteCache};
 
 #[cfg(test)]
@@ -57,7 +82,7 @@ mod tests {
             let sat_encoder = SatEncoder::new(&lcl_problem, graph);
             let clauses = sat_encoder.encode();
             let result = SatSolver::solve(clauses);
-            assert_eq!(result, SatResult::Satisfiable);
+            assert!(result.is_satisfiable());
         });
 
         Ok(())
@@ -93,7 +118,7 @@ mod tests {
         // For n=(1..=9) all results should be satisfiable.
         let (last, rest) = results_grouped.as_slice().split_last().unwrap();
         for results in rest {
-            assert!(results.iter().all(|r| *r == SatResult::Satisfiable));
+            assert!(results.iter().all(|r| r.is_satisfiable()));
         }
 
         // For n=10 at least one results should be unsatisfiable.
@@ -126,9 +151,7 @@ mod tests {
             })
             .collect_vec();
 
-        assert!(results
-            .iter()
-            .all(|result| { *result == SatResult::Satisfiable }));
+        assert!(results.iter().all(|result| { result.is_satisfiable() }));
 
         Ok(())
     }
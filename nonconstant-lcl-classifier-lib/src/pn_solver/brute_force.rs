@@ -0,0 +1,258 @@
+use super::PnSolver;
+use crate::lcl_problem::oriented::untag_label;
+use crate::{BiregularGraph, EdgeDirection, LclProblem, MixedDegreeLclProblem, OrientedLclProblem};
+use itertools::Itertools;
+use petgraph::visit::EdgeRef;
+use std::collections::HashSet;
+
+/// Solver that searches labelings directly by backtracking over permutations of active nodes.
+///
+/// Active nodes are labeled one by one with each permutation of the active configurations,
+/// and a passive node is checked as soon as all of its edges have labels.
+/// This is independent of [`crate::SatEncoder`], so it can be used to check its results,
+/// but it is only practical for small graphs, see [`BruteForceSolver::get_labeling_count`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BruteForceSolver;
+
+impl BruteForceSolver {
+    /// Returns the count of labelings of the active nodes of `graph`, i.e. the size of the search space.
+    ///
+    /// The count is a float, because it easily overflows integers.
+    pub fn get_labeling_count(problem: &LclProblem, graph: &BiregularGraph) -> f64 {
+        let permutation_count = problem.active.get_permutations().len() as f64;
+        permutation_count.powi(graph.partition_a.len() as i32)
+    }
+
+    /// Returns a label for each edge of `graph`, indexed by the edge, with which `problem` is solved.
+    ///
+    /// Returns `None` if the problem cannot be solved on the graph.
+    pub fn find_edge_labels(
+        &self,
+        problem: &LclProblem,
+        graph: &BiregularGraph,
+    ) -> Option<Vec<u8>> {
+        find_edge_labels(
+            graph,
+            &problem.active.get_permutations(),
+            problem.passive.get_configurations(),
+            &[],
+        )
+    }
+
+    /// Returns a label for each edge of `graph` with which the problem of mixed degrees is solved,
+    /// as [`BruteForceSolver::find_edge_labels`].
+    ///
+    /// Each node uses the configurations of its own degree, as in [`crate::SatEncoder::with_mixed_degrees`].
+    pub fn find_edge_labels_with_mixed_degrees(
+        &self,
+        problem: &MixedDegreeLclProblem,
+        graph: &BiregularGraph,
+    ) -> Option<Vec<u8>> {
+        let active_permutations = problem
+            .active
+            .iter()
+            .flat_map(|configurations| configurations.get_permutations())
+            .collect_vec();
+        let passive_configurations = problem
+            .passive
+            .iter()
+            .flat_map(|configurations| configurations.get_configurations().clone())
+            .collect_vec();
+        find_edge_labels(graph, &active_permutations, &passive_configurations, &[])
+    }
+
+    /// Returns a tagged label for each edge of `graph` with which the oriented problem is solved
+    /// with the directions `edge_directions` of the edges, as [`BruteForceSolver::find_edge_labels`].
+    ///
+    /// Labels are tagged with the directions of the edges with respect to their active nodes,
+    /// as in [`crate::SatEncoder::with_orientation`].
+    pub fn find_edge_labels_with_orientation(
+        &self,
+        problem: &OrientedLclProblem,
+        graph: &BiregularGraph,
+        edge_directions: &[EdgeDirection],
+    ) -> Option<Vec<u8>> {
+        // The direction of an edge with respect to its passive node is the reverse.
+        let passive_configurations = problem
+            .passive
+            .get_configurations()
+            .iter()
+            .map(|configuration| configuration.iter().map(|&tagged| tagged ^ 1).collect())
+            .collect_vec();
+        find_edge_labels(
+            graph,
+            &problem.active.get_permutations(),
+            &passive_configurations,
+            edge_directions,
+        )
+    }
+}
+
+/// Searches a label for each edge of `graph` where each active node has one of `active_permutations`
+/// and each passive node one of `passive_configurations` on its edges.
+///
+/// A node can only use permutations of its own degree.
+/// If `edge_directions` is not empty, labels are tagged and an active node can only use the permutations
+/// whose tags are the directions of its edges, see [`crate::lcl_problem::oriented::tag_label`].
+fn find_edge_labels(
+    graph: &BiregularGraph,
+    active_permutations: &[Vec<u8>],
+    passive_configurations: &[Vec<u8>],
+    edge_directions: &[EdgeDirection],
+) -> Option<Vec<u8>> {
+    let passive_configurations: HashSet<Vec<u8>> = passive_configurations
+        .iter()
+        .map(|configuration| configuration.iter().copied().sorted().collect())
+        .collect();
+
+    let edges_of = |node| {
+        graph
+            .graph
+            .edges(node)
+            .map(|edge| edge.id().index())
+            .collect_vec()
+    };
+    let active_edges = graph
+        .partition_a
+        .iter()
+        .map(|&node| edges_of(node))
+        .collect_vec();
+    let passive_edges = graph
+        .partition_b
+        .iter()
+        .map(|&node| edges_of(node))
+        .collect_vec();
+
+    // Permutations that each active node can use on its edges.
+    let node_permutations = active_edges
+        .iter()
+        .map(|edges| {
+            active_permutations
+                .iter()
+                .filter(|permutation| permutation.len() == edges.len())
+                .filter(|permutation| {
+                    edge_directions.is_empty()
+                        || edges
+                            .iter()
+                            .zip(permutation.iter())
+                            .all(|(&edge, &tagged)| untag_label(tagged).1 == edge_directions[edge])
+                })
+                .cloned()
+                .collect_vec()
+        })
+        .collect_vec();
+
+    // Active node of each edge, as an index of the active partition.
+    let mut active_of_edge = vec![0; graph.graph.edge_count()];
+    for (active, edges) in active_edges.iter().enumerate() {
+        for &edge in edges {
+            active_of_edge[edge] = active;
+        }
+    }
+    // Passive nodes whose edges all have labels after labeling each active node.
+    let mut completed: Vec<Vec<usize>> = vec![vec![]; active_edges.len()];
+    for (passive, edges) in passive_edges.iter().enumerate() {
+        if let Some(last) = edges.iter().map(|&edge| active_of_edge[edge]).max() {
+            completed[last].push(passive);
+        }
+    }
+
+    let search = Search {
+        node_permutations: &node_permutations,
+        passive_configurations: &passive_configurations,
+        active_edges: &active_edges,
+        passive_edges: &passive_edges,
+        completed: &completed,
+    };
+    let mut labels = vec![0; graph.graph.edge_count()];
+    if search.is_solvable(0, &mut labels) {
+        Some(labels)
+    } else {
+        None
+    }
+}
+
+impl PnSolver for BruteForceSolver {
+    fn is_solvable(&self, problem: &LclProblem, graph: &BiregularGraph) -> bool {
+        self.find_edge_labels(problem, graph).is_some()
+    }
+}
+
+/// State of the search of labelings.
+///
+/// Nodes are indices of their partitions, and edges are indices of the edges of the graph.
+struct Search<'a> {
+    /// Permutations of each active node.
+    node_permutations: &'a [Vec<Vec<u8>>],
+    /// Passive configurations with sorted labels.
+    passive_configurations: &'a HashSet<Vec<u8>>,
+    active_edges: &'a [Vec<usize>],
+    passive_edges: &'a [Vec<usize>],
+    completed: &'a [Vec<usize>],
+}
+
+impl Search<'_> {
+    /// Returns true if `labels` of the edges of the active nodes before `active` can be extended into a solution.
+    fn is_solvable(&self, active: usize, labels: &mut Vec<u8>) -> bool {
+        if active == self.active_edges.len() {
+            return true;
+        }
+        self.node_permutations[active].iter().any(|permutation| {
+            for (&edge, &label) in self.active_edges[active].iter().zip(permutation) {
+                labels[edge] = label;
+            }
+            let passive_ok = self.completed[active].iter().all(|&passive| {
+                let configuration = self.passive_edges[passive]
+                    .iter()
+                    .map(|&edge| labels[edge])
+                    .sorted()
+                    .collect_vec();
+                self.passive_configurations.contains(&configuration)
+            });
+            passive_ok && self.is_solvable(active + 1, labels)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_parity_problem;
+
+    #[test]
+    fn test_is_solvable() {
+        // Solvable only if the count of active nodes is even.
+        let problem = get_parity_problem();
+        assert!(BruteForceSolver.is_solvable(&problem, &BiregularGraph::cycle(2)));
+        assert!(!BruteForceSolver.is_solvable(&problem, &BiregularGraph::cycle(3)));
+        assert!(BruteForceSolver.is_solvable(&problem, &BiregularGraph::cycle(4)));
+        assert_eq!(
+            BruteForceSolver::get_labeling_count(&problem, &BiregularGraph::cycle(3)),
+            8.0
+        );
+    }
+
+    #[test]
+    fn test_find_edge_labels() {
+        let problem = get_parity_problem();
+        let graph = BiregularGraph::cycle(4);
+        let labels = BruteForceSolver.find_edge_labels(&problem, &graph).unwrap();
+
+        let node_labels = |node| {
+            graph
+                .graph
+                .edges(node)
+                .map(|edge| labels[edge.id().index()])
+                .sorted()
+                .collect_vec()
+        };
+        assert!(graph
+            .partition_a
+            .iter()
+            .all(|&node| node_labels(node) == [0, 1]));
+        assert!(graph.partition_b.iter().all(|&node| {
+            let labels = node_labels(node);
+            labels[0] == labels[1]
+        }));
+    }
+}
@@ -0,0 +1,293 @@
+pub mod brute_force;
+
+use crate::{BiregularGraph, LclProblem, SatEncoder, SatSolver};
+
+/// Decides whether an LCL problem can be solved on a graph in the PN model.
+///
+/// A problem can be solved on a graph if each node can choose a permutation of one of its configurations
+/// over its incident edges so that every edge gets the same label from both of its nodes.
+pub trait PnSolver {
+    /// Returns true if `problem` can be solved on `graph`.
+    fn is_solvable(&self, problem: &LclProblem, graph: &BiregularGraph) -> bool;
+}
+
+/// Solver that encodes the problem with [`SatEncoder`] and solves it with [`SatSolver`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SatPnSolver;
+
+impl PnSolver for SatPnSolver {
+    fn is_solvable(&self, problem: &LclProblem, graph: &BiregularGraph) -> bool {
+        let encoder = SatEncoder::new(problem, graph.clone());
+        SatSolver::solve(encoder.encode()).is_satisfiable()
+    }
+}
+
+/// Problem and graph on which two solvers disagree, see [`find_disagreements`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disagreement {
+    pub problem_index: usize,
+    pub graph_index: usize,
+    /// Result of the first solver. The second solver has the opposite result.
+    pub first_result: bool,
+}
+
+/// Compares two solvers on every problem of `problems` and graph of `graphs` with the same degrees.
+///
+/// This is used for differential testing, e.g. of [`SatPnSolver`] against [`brute_force::BruteForceSolver`].
+/// Returns the pairs of a problem and a graph on which the solvers disagree.
+pub fn find_disagreements(
+    first: &dyn PnSolver,
+    second: &dyn PnSolver,
+    problems: &[LclProblem],
+    graphs: &[BiregularGraph],
+) -> Vec<Disagreement> {
+    let mut disagreements = vec![];
+    for (problem_index, problem) in problems.iter().enumerate() {
+        let degree_a = problem.active.get_labels_per_configuration();
+        let degree_b = problem.passive.get_labels_per_configuration();
+        for (graph_index, graph) in graphs.iter().enumerate() {
+            if graph.degree_a != degree_a || graph.degree_b != degree_b {
+                continue;
+            }
+            let first_result = first.is_solvable(problem, graph);
+            if first_result != second.is_solvable(problem, graph) {
+                disagreements.push(Disagreement {
+                    problem_index,
+                    graph_index,
+                    first_result,
+                });
+            }
+        }
+    }
+    disagreements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::brute_force::BruteForceSolver;
+    use super::*;
+    use crate::{Configurations, MixedDegreeLclProblem, OrientedLclProblem, UndirectedGraph};
+    use itertools::Itertools;
+    use petgraph::graph::NodeIndex;
+
+    /// Biregular graph with the first `active_count` nodes of `edges` active and the rest passive.
+    fn get_graph(
+        active_count: u32,
+        passive_count: u32,
+        degree_a: usize,
+        degree_b: usize,
+        edges: &[(u32, u32)],
+    ) -> BiregularGraph {
+        let mut graph = UndirectedGraph::new_undirected();
+        for node in 0..active_count + passive_count {
+            graph.add_node(node);
+        }
+        for &(a, b) in edges {
+            graph.add_edge(NodeIndex::new(a as usize), NodeIndex::new(b as usize), ());
+        }
+        BiregularGraph {
+            graph,
+            partition_a: (0..active_count as usize).map(NodeIndex::new).collect(),
+            partition_b: (active_count as usize..(active_count + passive_count) as usize)
+                .map(NodeIndex::new)
+                .collect(),
+            degree_a,
+            degree_b,
+        }
+    }
+
+    /// Small (2,2)- and (3,3)-biregular multigraphs that can be built without graph generation.
+    fn get_small_graphs() -> Vec<BiregularGraph> {
+        let mut graphs = vec![
+            // Double edge.
+            get_graph(1, 1, 2, 2, &[(0, 1), (0, 1)]),
+            // Triple edge.
+            get_graph(1, 1, 3, 3, &[(0, 1), (0, 1), (0, 1)]),
+            // Double edge and a single edge.
+            get_graph(
+                2,
+                2,
+                3,
+                3,
+                &[(0, 2), (0, 2), (0, 3), (1, 3), (1, 3), (1, 2)],
+            ),
+            // Complete bipartite graph K_{3,3}.
+            get_graph(
+                3,
+                3,
+                3,
+                3,
+                &[
+                    (0, 3),
+                    (0, 4),
+                    (0, 5),
+                    (1, 3),
+                    (1, 4),
+                    (1, 5),
+                    (2, 3),
+                    (2, 4),
+                    (2, 5),
+                ],
+            ),
+        ];
+        // Cycles of 4, 6, 8 and 10 nodes.
+        graphs.extend((2..=5).map(BiregularGraph::cycle));
+        graphs
+    }
+
+    #[test]
+    fn test_solvers_agree_on_small_graphs() {
+        let graphs = get_small_graphs();
+        for (active_degree, passive_degree, label_count) in [(2, 2, 2), (2, 2, 3), (3, 3, 2)] {
+            let problems =
+                LclProblem::generate_normalized(active_degree, passive_degree, label_count);
+            assert!(!problems.is_empty());
+            let disagreements =
+                find_disagreements(&SatPnSolver, &BruteForceSolver, &problems, &graphs);
+            assert_eq!(disagreements, vec![]);
+        }
+    }
+
+    #[test]
+    fn test_solvers_agree_on_gapped_labels() {
+        let graphs = get_small_graphs();
+        let mut problems = vec![];
+        for problem in LclProblem::generate_normalized(2, 2, 3) {
+            problems.push(LclProblem::from_configurations(
+                problem.active.map_labels(&vec![1, 3, 4]),
+                problem.passive.map_labels(&vec![1, 3, 4]),
+            ));
+        }
+        let disagreements = find_disagreements(&SatPnSolver, &BruteForceSolver, &problems, &graphs);
+        assert_eq!(disagreements, vec![]);
+
+        // Simplified problems have no gaps, and are solved the same.
+        let mut problem = LclProblem::new("AC BC", "AC BC CC").unwrap();
+        problem.simplify();
+        assert_eq!(problem.active.get_labels(), [0, 1]);
+        let disagreements =
+            find_disagreements(&SatPnSolver, &BruteForceSolver, &[problem], &graphs);
+        assert_eq!(disagreements, vec![]);
+    }
+
+    #[test]
+    fn test_solvers_agree_on_simplified_problems() {
+        let graphs = get_small_graphs();
+        let mut problems = vec![];
+        for problem in LclProblem::generate_normalized(2, 2, 3) {
+            // The labels of the problem with gaps are renumbered by purging and simplifying.
+            let gapped = LclProblem::from_configurations(
+                problem.active.map_labels(&vec![1, 3, 4]),
+                problem.passive.map_labels(&vec![1, 3, 4]),
+            );
+            for mut problem in [problem, gapped] {
+                let mut purged = problem.clone();
+                purged.purge();
+                problem.simplify();
+                problems.extend([purged, problem]);
+            }
+        }
+        problems.retain(|problem| {
+            !problem.active.get_configurations().is_empty()
+                && !problem.passive.get_configurations().is_empty()
+        });
+        assert!(!problems.is_empty());
+        let disagreements = find_disagreements(&SatPnSolver, &BruteForceSolver, &problems, &graphs);
+        assert_eq!(disagreements, vec![]);
+    }
+
+    #[test]
+    fn test_solvers_agree_on_oriented_problems() {
+        // Each node has one outgoing and one incoming edge, e.g. in an oriented cycle.
+        let configurations = ["A:A", "A:B", "B:A", "B:B"];
+        let graphs = get_small_graphs()
+            .into_iter()
+            .filter(|graph| graph.degree_a == 2)
+            .collect_vec();
+        for (active, passive) in configurations
+            .iter()
+            .powerset()
+            .filter(|subset| !subset.is_empty())
+            .map(|subset| subset.into_iter().join(" "))
+            .tuple_combinations()
+        {
+            let problem = OrientedLclProblem::new(&active, &passive).unwrap();
+            for graph in &graphs {
+                for orientation in graph.get_orientations(&[1], &[1]) {
+                    let encoder =
+                        SatEncoder::with_orientation(&problem, graph.clone(), &orientation);
+                    assert_eq!(
+                        SatSolver::solve(encoder.encode()).is_satisfiable(),
+                        BruteForceSolver
+                            .find_edge_labels_with_orientation(&problem, graph, &orientation)
+                            .is_some(),
+                        "{} on {:?} with {:?}",
+                        problem,
+                        graph.graph,
+                        orientation
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solvers_agree_on_mixed_degrees() {
+        // Paths and trees with leaves of degree 1 among active nodes of degree 2 or 3.
+        let graphs = [
+            BiregularGraph::generate_with_degrees(&[2, 1, 1], &[2, 2]).unwrap(),
+            BiregularGraph::generate_with_degrees(&[2, 2, 1, 1], &[2, 2, 2]).unwrap(),
+            BiregularGraph::generate_with_degrees(&[3, 1, 1, 1], &[2, 2, 2]).unwrap(),
+        ]
+        .concat();
+        assert!(!graphs.is_empty());
+        let leaf_configurations = [vec![vec![0]], vec![vec![1]], vec![vec![0], vec![1]]];
+        for problem in [
+            LclProblem::generate_normalized(2, 2, 2),
+            LclProblem::generate_normalized(3, 2, 2),
+        ]
+        .concat()
+        {
+            for leaves in &leaf_configurations {
+                let mut problem = MixedDegreeLclProblem::from(problem.clone());
+                problem.active.insert(
+                    0,
+                    Configurations::from_configuration_data(leaves.clone()).unwrap(),
+                );
+                let degrees = problem.get_active_degrees();
+                for graph in &graphs {
+                    if !graph
+                        .partition_a
+                        .iter()
+                        .all(|&node| degrees.contains(&graph.graph.edges(node).count()))
+                    {
+                        continue;
+                    }
+                    let encoder = SatEncoder::with_mixed_degrees(&problem, graph.clone());
+                    assert_eq!(
+                        SatSolver::solve(encoder.encode()).is_satisfiable(),
+                        BruteForceSolver
+                            .find_edge_labels_with_mixed_degrees(&problem, graph)
+                            .is_some(),
+                        "{} on {:?}",
+                        problem,
+                        graph.graph
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solvers_agree_on_generated_graphs() {
+        for (degree_a, degree_b, label_count) in [(2, 3, 2), (3, 2, 2), (3, 3, 2)] {
+            let graphs = (1..=8)
+                .flat_map(|n| BiregularGraph::generate(n, degree_a, degree_b))
+                .collect::<Vec<_>>();
+            let problems = LclProblem::generate_normalized(degree_a, degree_b, label_count);
+            let disagreements =
+                find_disagreements(&SatPnSolver, &BruteForceSolver, &problems, &graphs);
+            assert_eq!(disagreements, vec![]);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use super::Clauses;
+use itertools::Itertools;
+use std::error::Error;
+
+/// Size of the groups of [`AtMostOneEncoding::Commander`].
+const COMMANDER_GROUP_SIZE: usize = 3;
+
+/// Encoding of the constraint that at most one of a group of variables is true.
+///
+/// Encodings other than [`AtMostOneEncoding::Pairwise`] use auxiliary variables,
+/// which are numbered consecutively from the next free variable given to [`AtMostOneEncoding::encode`].
+/// Their count is given by [`AtMostOneEncoding::get_auxiliary_variable_count`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtMostOneEncoding {
+    /// A clause for each pair of variables, without auxiliary variables.
+    Pairwise,
+    /// Sequential counter of Sinz, with an auxiliary variable for each variable but the last.
+    SequentialCounter,
+    /// Commander encoding of Klieber and Kwon, with a commander variable for each group of three variables.
+    ///
+    /// The commanders are constrained recursively.
+    Commander,
+    /// Product encoding of Chen, which places the variables on a grid
+    /// with an auxiliary variable for each row and column.
+    ///
+    /// The rows and the columns are constrained recursively.
+    Product,
+}
+
+impl AtMostOneEncoding {
+    /// Returns the encoding that is used for a group of `group_size` variables unless another one is set.
+    ///
+    /// Small groups are encoded pairwise, and larger groups with encodings that have fewer clauses
+    /// but more auxiliary variables, see [`crate::SatEncoder::with_at_most_one`].
+    pub fn for_group_size(group_size: usize) -> Self {
+        match group_size {
+            0..=6 => Self::Pairwise,
+            7..=16 => Self::SequentialCounter,
+            17..=64 => Self::Commander,
+            _ => Self::Product,
+        }
+    }
+
+    /// Returns the name of the encoding.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Pairwise => "pairwise",
+            Self::SequentialCounter => "sequential-counter",
+            Self::Commander => "commander",
+            Self::Product => "product",
+        }
+    }
+
+    /// Returns the encoding with `name`, see [`get_at_most_one_names`].
+    ///
+    /// # Errors
+    /// Returns an error if there is no such encoding.
+    pub fn from_name(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "pairwise" => Ok(Self::Pairwise),
+            "sequential-counter" => Ok(Self::SequentialCounter),
+            "commander" => Ok(Self::Commander),
+            "product" => Ok(Self::Product),
+            name => Err(format!(
+                "Unknown at-most-one encoding '{}', expected one of: {}",
+                name,
+                get_at_most_one_names().join(", ")
+            )
+            .into()),
+        }
+    }
+
+    /// Returns the count of auxiliary variables that the encoding uses for `group_size` variables.
+    pub fn get_auxiliary_variable_count(&self, group_size: usize) -> usize {
+        match self {
+            Self::Pairwise => 0,
+            Self::SequentialCounter => group_size.saturating_sub(1),
+            Self::Commander => {
+                if group_size <= COMMANDER_GROUP_SIZE {
+                    return 0;
+                }
+                let commander_count = (group_size - 1) / COMMANDER_GROUP_SIZE + 1;
+                commander_count + self.get_auxiliary_variable_count(commander_count)
+            }
+            Self::Product => {
+                if group_size <= 3 {
+                    return 0;
+                }
+                let (row_count, column_count) = get_grid_size(group_size);
+                row_count
+                    + column_count
+                    + self.get_auxiliary_variable_count(row_count)
+                    + self.get_auxiliary_variable_count(column_count)
+            }
+        }
+    }
+
+    /// Returns the clauses that at most one of `variables` is true.
+    ///
+    /// Auxiliary variables are taken from `next_variable` onwards, and `next_variable` is moved past them.
+    pub fn encode(&self, variables: &[i32], next_variable: &mut i32) -> Clauses {
+        match self {
+            Self::Pairwise => pairwise(variables),
+            Self::SequentialCounter => sequential_counter(variables, next_variable),
+            Self::Commander => commander(variables, next_variable),
+            Self::Product => product(variables, next_variable),
+        }
+    }
+}
+
+/// Returns the names of the at-most-one encodings.
+pub fn get_at_most_one_names() -> Vec<&'static str> {
+    vec!["pairwise", "sequential-counter", "commander", "product"]
+}
+
+fn pairwise(variables: &[i32]) -> Clauses {
+    variables.iter().map(|x| -x).combinations(2).collect_vec()
+}
+
+fn sequential_counter(variables: &[i32], next_variable: &mut i32) -> Clauses {
+    if variables.len() <= 1 {
+        return vec![];
+    }
+
+    // Counter variable `counters[i]` is true if one of the variables up to index `i` is true.
+    let counters = take_variables(variables.len() - 1, next_variable);
+    let last = variables.len() - 1;
+    let mut clauses = vec![vec![-variables[0], counters[0]]];
+    for i in 1..last {
+        clauses.push(vec![-variables[i], counters[i]]);
+        clauses.push(vec![-counters[i - 1], counters[i]]);
+        clauses.push(vec![-variables[i], -counters[i - 1]]);
+    }
+    clauses.push(vec![-variables[last], -counters[last - 1]]);
+    clauses
+}
+
+fn commander(variables: &[i32], next_variable: &mut i32) -> Clauses {
+    if variables.len() <= COMMANDER_GROUP_SIZE {
+        return pairwise(variables);
+    }
+
+    let groups = variables.chunks(COMMANDER_GROUP_SIZE).collect_vec();
+    let commanders = take_variables(groups.len(), next_variable);
+    let mut clauses = vec![];
+    for (group, &commander) in groups.iter().zip(&commanders) {
+        clauses.extend(pairwise(group));
+        // The commander is true if and only if a variable of its group is true.
+        clauses.extend(group.iter().map(|&variable| vec![-variable, commander]));
+        let mut clause = vec![-commander];
+        clause.extend(group.iter());
+        clauses.push(clause);
+    }
+    clauses.extend(commander(&commanders, next_variable));
+    clauses
+}
+
+fn product(variables: &[i32], next_variable: &mut i32) -> Clauses {
+    if variables.len() <= 3 {
+        return pairwise(variables);
+    }
+
+    // Each variable implies its row and its column, and at most one row and column can be true.
+    // Two true variables would differ in their row or their column.
+    let (row_count, column_count) = get_grid_size(variables.len());
+    let rows = take_variables(row_count, next_variable);
+    let columns = take_variables(column_count, next_variable);
+    let mut clauses = vec![];
+    for (index, &variable) in variables.iter().enumerate() {
+        clauses.push(vec![-variable, rows[index / column_count]]);
+        clauses.push(vec![-variable, columns[index % column_count]]);
+    }
+    clauses.extend(product(&rows, next_variable));
+    clauses.extend(product(&columns, next_variable));
+    clauses
+}
+
+/// Returns `count` new variables from `next_variable` onwards, and moves `next_variable` past them.
+fn take_variables(count: usize, next_variable: &mut i32) -> Vec<i32> {
+    let variables = (*next_variable..*next_variable + count as i32).collect_vec();
+    *next_variable += count as i32;
+    variables
+}
+
+/// Returns the counts of rows and columns of the grid of [`AtMostOneEncoding::Product`] for `group_size` variables.
+///
+/// `group_size` has to be positive.
+fn get_grid_size(group_size: usize) -> (usize, usize) {
+    let mut column_count = 1;
+    while column_count * column_count < group_size {
+        column_count += 1;
+    }
+    ((group_size - 1) / column_count + 1, column_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sat_solver::get_variable_count;
+    use crate::SatSolver;
+
+    fn get_encodings() -> Vec<AtMostOneEncoding> {
+        get_at_most_one_names()
+            .into_iter()
+            .map(|name| AtMostOneEncoding::from_name(name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_auxiliary_variable_count() {
+        for encoding in get_encodings() {
+            for group_size in 0..=100 {
+                let variables = (1..=group_size as i32).collect_vec();
+                let mut next_variable = group_size as i32 + 1;
+                let clauses = encoding.encode(&variables, &mut next_variable);
+                let auxiliary_variable_count = encoding.get_auxiliary_variable_count(group_size);
+                assert_eq!(
+                    next_variable as usize,
+                    group_size + auxiliary_variable_count + 1,
+                    "{} of {} variables",
+                    encoding.get_name(),
+                    group_size
+                );
+                assert!(get_variable_count(&clauses) < next_variable as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn test_at_most_one_encodings() {
+        for encoding in get_encodings() {
+            for group_size in 1..=8 {
+                let variables = (1..=group_size).collect_vec();
+                let mut next_variable = group_size + 1;
+                let clauses = encoding.encode(&variables, &mut next_variable);
+
+                for assignment in 0..1u32 << group_size {
+                    let mut with_assignment = clauses.clone();
+                    with_assignment.extend(variables.iter().map(|&variable| {
+                        if assignment & (1 << (variable - 1)) != 0 {
+                            vec![variable]
+                        } else {
+                            vec![-variable]
+                        }
+                    }));
+                    assert_eq!(
+                        SatSolver::solve(with_assignment).is_satisfiable(),
+                        assignment.count_ones() <= 1,
+                        "{} of {} variables with assignment {:b}",
+                        encoding.get_name(),
+                        group_size,
+                        assignment
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fewer_clauses_for_large_groups() {
+        let variables = (1..=200).collect_vec();
+        let pairwise_count = AtMostOneEncoding::Pairwise
+            .encode(&variables, &mut 201)
+            .len();
+        for encoding in get_encodings().into_iter().skip(1) {
+            assert!(encoding.encode(&variables, &mut 201).len() < pairwise_count / 10);
+        }
+        assert_eq!(
+            AtMostOneEncoding::for_group_size(200),
+            AtMostOneEncoding::Product
+        );
+        assert!(AtMostOneEncoding::from_name("binary").is_err());
+    }
+}
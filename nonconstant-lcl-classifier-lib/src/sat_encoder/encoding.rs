@@ -0,0 +1,300 @@
+use super::{at_most_one::AtMostOneEncoding, only_one, Clauses, SatEncoder};
+use crate::{BiregularGraph, LclProblem};
+use itertools::Itertools;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::HashSet;
+use std::error::Error;
+
+/// Scheme of encoding an LCL problem on a biregular graph into CNF form.
+///
+/// The clauses of every encoding are satisfiable if and only if the problem can be solved on the graph,
+/// but they differ in their variables and clauses, and so in how fast they are solved.
+/// The encoding is selected with [`crate::Classifier::encoding`], see [`get_encoding_names`].
+pub trait SatEncoding: Send + Sync {
+    /// Returns the name of the encoding.
+    fn get_name(&self) -> String;
+
+    /// Encodes `problem` on `graph` into CNF form.
+    fn encode(&self, problem: &LclProblem, graph: &BiregularGraph) -> Clauses;
+}
+
+/// Encoding of [`SatEncoder`] with a variable for each permutation of each node
+/// and variables for the labels of both halves of each edge.
+///
+/// This is the default encoding.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PermutationEncoding;
+
+impl SatEncoding for PermutationEncoding {
+    fn get_name(&self) -> String {
+        "permutation".to_string()
+    }
+
+    fn encode(&self, problem: &LclProblem, graph: &BiregularGraph) -> Clauses {
+        // TODO use immutable reference instead of cloning.
+        SatEncoder::new(problem, graph.clone()).encode()
+    }
+}
+
+/// Encoding with only a variable for each label of each edge.
+///
+/// Each edge has exactly one label, and each node has a clause for each tuple of labels on its edges
+/// that is not a permutation of an allowed configuration.
+/// The count of clauses grows exponentially in the degree, so this is meant for small degrees.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ForbiddenTupleEncoding;
+
+impl SatEncoding for ForbiddenTupleEncoding {
+    fn get_name(&self) -> String {
+        "forbidden-tuple".to_string()
+    }
+
+    fn encode(&self, problem: &LclProblem, graph: &BiregularGraph) -> Clauses {
+        let labels = get_labels(problem);
+        let mut clauses = encode_edge_labels(graph, labels.len());
+
+        for (nodes, configurations) in [
+            (&graph.partition_a, problem.active.get_configurations()),
+            (&graph.partition_b, problem.passive.get_configurations()),
+        ] {
+            let allowed: HashSet<Vec<u8>> = configurations
+                .iter()
+                .map(|configuration| configuration.iter().copied().sorted().collect())
+                .collect();
+
+            for &node in nodes {
+                let edges = get_edge_indices(graph, node);
+                for tuple in edges
+                    .iter()
+                    .map(|_| 0..labels.len())
+                    .multi_cartesian_product()
+                {
+                    let configuration = tuple.iter().map(|&i| labels[i]).sorted().collect_vec();
+                    if allowed.contains(&configuration) {
+                        continue;
+                    }
+                    clauses.push(
+                        edges
+                            .iter()
+                            .zip(&tuple)
+                            .map(|(&edge, &i)| -var_edge_label(edge, i, labels.len()))
+                            .collect(),
+                    );
+                }
+            }
+        }
+        clauses
+    }
+}
+
+/// Encoding with a variable for each label of each edge and for each configuration of each node.
+///
+/// Each node has exactly one configuration, and the configuration implies counting constraints:
+/// for each label, at least as many edges of the node have the label as the configuration has it.
+/// Because each edge has exactly one label, the counts are then exact.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingEncoding;
+
+impl SatEncoding for CountingEncoding {
+    fn get_name(&self) -> String {
+        "counting".to_string()
+    }
+
+    fn encode(&self, problem: &LclProblem, graph: &BiregularGraph) -> Clauses {
+        let labels = get_labels(problem);
+        let mut clauses = encode_edge_labels(graph, labels.len());
+
+        // Variables of configurations come after the variables of edge labels.
+        let mut next_variable = (graph.graph.edge_count() * labels.len()) as i32 + 1;
+
+        for (nodes, configurations) in [
+            (&graph.partition_a, problem.active.get_configurations()),
+            (&graph.partition_b, problem.passive.get_configurations()),
+        ] {
+            for &node in nodes {
+                let edges = get_edge_indices(graph, node);
+                let vars_configuration = (0..configurations.len())
+                    .map(|i| next_variable + i as i32)
+                    .collect_vec();
+                next_variable += configurations.len() as i32;
+                clauses.push(vars_configuration.clone());
+                clauses.extend(
+                    AtMostOneEncoding::for_group_size(vars_configuration.len())
+                        .encode(&vars_configuration, &mut next_variable),
+                );
+
+                for (configuration, &var_configuration) in
+                    configurations.iter().zip(&vars_configuration)
+                {
+                    // Configurations of other degrees cannot be used.
+                    if configuration.len() != edges.len() {
+                        clauses.push(vec![-var_configuration]);
+                        continue;
+                    }
+
+                    for (label_index, label) in labels.iter().enumerate() {
+                        let count = configuration.iter().filter(|&l| l == label).count();
+                        let vars_label = edges
+                            .iter()
+                            .map(|&edge| var_edge_label(edge, label_index, labels.len()))
+                            .collect_vec();
+
+                        if count == 0 {
+                            clauses.extend(
+                                vars_label
+                                    .iter()
+                                    .map(|&var_label| vec![-var_configuration, -var_label]),
+                            );
+                            continue;
+                        }
+
+                        // At least `count` edges have the label if every set of
+                        // `edges.len() - count + 1` edges has one with the label.
+                        for subset in vars_label.iter().combinations(edges.len() - count + 1) {
+                            let mut clause = vec![-var_configuration];
+                            clause.extend(subset);
+                            clauses.push(clause);
+                        }
+                    }
+                }
+            }
+        }
+        clauses
+    }
+}
+
+/// Returns the names of the encodings, the default first.
+pub fn get_encoding_names() -> Vec<&'static str> {
+    vec!["permutation", "forbidden-tuple", "counting"]
+}
+
+/// Returns the encoding with `name`, see [`get_encoding_names`].
+///
+/// # Errors
+/// Returns an error if there is no such encoding.
+pub fn encoding_from_name(name: &str) -> Result<Box<dyn SatEncoding>, Box<dyn Error>> {
+    match name {
+        "permutation" => Ok(Box::new(PermutationEncoding)),
+        "forbidden-tuple" => Ok(Box::new(ForbiddenTupleEncoding)),
+        "counting" => Ok(Box::new(CountingEncoding)),
+        name => Err(format!(
+            "Unknown encoding '{}', expected one of: {}",
+            name,
+            get_encoding_names().join(", ")
+        )
+        .into()),
+    }
+}
+
+/// Returns the labels that the active or passive configurations of `problem` use.
+fn get_labels(problem: &LclProblem) -> Vec<u8> {
+    let labels_active = problem.active.get_labels_set();
+    let labels_passive = problem.passive.get_labels_set();
+    labels_active
+        .union(&labels_passive)
+        .copied()
+        .sorted()
+        .collect()
+}
+
+/// Returns the indices of the edges of `node`.
+fn get_edge_indices(graph: &BiregularGraph, node: NodeIndex) -> Vec<usize> {
+    graph
+        .graph
+        .edges(node)
+        .map(|edge| edge.id().index())
+        .collect()
+}
+
+/// Returns the clauses that each edge has exactly one of `label_count` labels.
+fn encode_edge_labels(graph: &BiregularGraph, label_count: usize) -> Clauses {
+    (0..graph.graph.edge_count())
+        .flat_map(|edge| {
+            let vars = (0..label_count)
+                .map(|label_index| var_edge_label(edge, label_index, label_count))
+                .collect_vec();
+            only_one(&vars)
+        })
+        .collect()
+}
+
+/// Returns the variable of the label with `label_index` on the edge with `edge_index`.
+fn var_edge_label(edge_index: usize, label_index: usize, label_count: usize) -> i32 {
+    (edge_index * label_count + label_index + 1) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_parity_problem;
+    use crate::{SatSolver, UndirectedGraph};
+
+    /// Complete bipartite graph of 2 active nodes of degree 3 and 3 passive nodes of degree 2.
+    fn get_complete_bipartite() -> BiregularGraph {
+        let edges = (0..2).flat_map(|a| (2..5).map(move |b| (a, b)));
+        let graph: UndirectedGraph = petgraph::graph::UnGraph::from_edges(edges);
+        BiregularGraph {
+            graph,
+            partition_a: (0..2).map(NodeIndex::new).collect(),
+            partition_b: (2..5).map(NodeIndex::new).collect(),
+            degree_a: 3,
+            degree_b: 2,
+        }
+    }
+
+    #[test]
+    fn test_encodings_agree() {
+        let encodings = get_encoding_names()
+            .into_iter()
+            .map(|name| encoding_from_name(name).unwrap())
+            .collect_vec();
+        let cases = [
+            (
+                LclProblem::generate_normalized(2, 2, 3),
+                BiregularGraph::cycle(3),
+            ),
+            (
+                LclProblem::generate_normalized(2, 2, 2),
+                BiregularGraph::cycle(2),
+            ),
+            (
+                LclProblem::generate_normalized(3, 2, 2),
+                get_complete_bipartite(),
+            ),
+        ];
+
+        for (problems, graph) in &cases {
+            for problem in problems {
+                let results = encodings
+                    .iter()
+                    .map(|encoding| {
+                        SatSolver::solve(encoding.encode(problem, graph)).is_satisfiable()
+                    })
+                    .collect_vec();
+                assert!(results.iter().all_equal(), "{}", problem.to_string());
+            }
+        }
+
+        // Solvable only if the count of active nodes is even.
+        let problem = get_parity_problem();
+        for encoding in &encodings {
+            assert!(
+                SatSolver::solve(encoding.encode(&problem, &BiregularGraph::cycle(2)))
+                    .is_satisfiable()
+            );
+            assert!(
+                !SatSolver::solve(encoding.encode(&problem, &BiregularGraph::cycle(3)))
+                    .is_satisfiable()
+            );
+        }
+    }
+
+    #[test]
+    fn test_encoding_from_name() {
+        for name in get_encoding_names() {
+            assert_eq!(encoding_from_name(name).unwrap().get_name(), name);
+        }
+        assert!(encoding_from_name("onehot").is_err());
+    }
+}
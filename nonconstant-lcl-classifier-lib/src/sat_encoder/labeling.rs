@@ -0,0 +1,128 @@
+use crate::lcl_problem::default_label_name;
+use crate::BiregularGraph;
+use itertools::Itertools;
+use petgraph::dot::Dot;
+use petgraph::visit::EdgeRef;
+use std::fmt;
+
+/// Labeling of a graph decoded from a satisfying assignment, see [`crate::SatEncoder::decode`].
+///
+/// Labels are the labels of the encoded problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Labeling {
+    /// Permutation of a configuration chosen by each node, indexed by the node.
+    ///
+    /// The labels are in the order of the ports of the incident edges of the node, see `ports`.
+    /// Without ports, they are in the order of the incident edges of the node in the graph,
+    /// i.e. the order of `graph.edges(node)`.
+    pub permutations: Vec<Vec<u8>>,
+    /// Labels of each edge, indexed by the edge,
+    /// as the pair of labels on the half of its active node and on the half of its passive node.
+    pub half_edge_labels: Vec<(u8, u8)>,
+    /// Port of each edge at its active node and at its passive node, indexed by the edge,
+    /// see [`crate::SatEncoder::with_ports`].
+    /// Empty if the ports are in the order of the edges of the graph.
+    pub ports: Vec<(usize, usize)>,
+}
+
+impl Labeling {
+    /// Returns the labeling with `label_name` used for the names of the labels.
+    ///
+    /// Each node is written as `node <index>: <labels>` on its own line,
+    /// followed by each edge as `edge <index>: <active label> <passive label>`.
+    pub fn to_string_with_names<F>(&self, label_name: F) -> String
+    where
+        F: Fn(u8) -> String,
+    {
+        let nodes = self.permutations.iter().enumerate().map(|(node, labels)| {
+            let labels = labels.iter().map(|&label| label_name(label)).join("");
+            format!("node {}: {}", node, labels)
+        });
+        let edges = self
+            .half_edge_labels
+            .iter()
+            .enumerate()
+            .map(|(edge, &(active, passive))| {
+                format!(
+                    "edge {}: {} {}",
+                    edge,
+                    label_name(active),
+                    label_name(passive)
+                )
+            });
+        nodes.chain(edges).join("\n")
+    }
+
+    /// Returns `graph` in .dot format with the labels of the labeling.
+    ///
+    /// Nodes are labeled with their index and permutation, and edges with their labels.
+    /// An edge has a single label if both of its halves have the same label,
+    /// and otherwise the label of the active half followed by the label of the passive half.
+    /// `graph` must be the graph that the labeling was decoded for.
+    pub fn get_dot<F>(&self, graph: &BiregularGraph, label_name: F) -> String
+    where
+        F: Fn(u8) -> String,
+    {
+        let labeled = graph.graph.map(
+            |node, _| {
+                let labels = self.permutations[node.index()]
+                    .iter()
+                    .map(|&label| label_name(label))
+                    .join("");
+                format!("{}: {}", node.index(), labels)
+            },
+            |edge, _| {
+                let (active, passive) = self.half_edge_labels[edge.index()];
+                if active == passive {
+                    label_name(active)
+                } else {
+                    format!("{} {}", label_name(active), label_name(passive))
+                }
+            },
+        );
+        format!("{}", Dot::new(&labeled))
+    }
+
+    /// Returns true if both halves of every edge have the same label
+    /// and each node has the labels of its edges in the order of their ports.
+    pub fn is_consistent(&self, graph: &BiregularGraph) -> bool {
+        let halves_agree = self
+            .half_edge_labels
+            .iter()
+            .all(|(active, passive)| active == passive);
+        let is_active = |node| graph.partition_a.contains(&node);
+        let nodes_agree = graph.graph.node_indices().all(|node| {
+            let mut edges = graph.graph.edges(node).collect_vec();
+            if !self.ports.is_empty() {
+                edges.sort_by_key(|edge| {
+                    let (active_port, passive_port) = self.ports[edge.id().index()];
+                    if is_active(node) {
+                        active_port
+                    } else {
+                        passive_port
+                    }
+                });
+            }
+            let labels = edges
+                .into_iter()
+                .map(|edge| {
+                    let (active, passive) = self.half_edge_labels[edge.id().index()];
+                    if is_active(node) {
+                        active
+                    } else {
+                        passive
+                    }
+                })
+                .collect_vec();
+            labels == self.permutations[node.index()]
+        });
+        halves_agree && nodes_agree
+    }
+}
+
+impl fmt::Display for Labeling {
+    /// Writes the labeling with the default label names, see [`Labeling::to_string_with_names`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_with_names(default_label_name))
+    }
+}
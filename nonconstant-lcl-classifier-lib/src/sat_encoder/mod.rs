@@ -1,15 +1,36 @@
-use crate::lcl_problem::LclProblem;
+pub mod at_most_one;
+pub mod encoding;
+pub mod labeling;
+pub mod selectors;
+
+use crate::lcl_problem::{
+    configurations::Configurations,
+    inputs::{InputAssignment, LclProblemWithInputs},
+    mixed_degree::MixedDegreeLclProblem,
+    oriented::{untag_label, EdgeDirection, OrientedLclProblem},
+    LclProblem,
+};
+use crate::sat_solver::{clauses_to_dimacs_with_variable_count, get_variable_count, Model};
 use crate::BiregularGraph;
+use at_most_one::AtMostOneEncoding;
 use itertools::Itertools;
-use petgraph::graph::EdgeReference;
+use labeling::Labeling;
+use petgraph::graph::{EdgeReference, NodeIndex};
 use petgraph::visit::EdgeIndexable;
 use petgraph::visit::EdgeRef;
+use std::collections::HashSet;
 use NodeOrderInEdgeRef::{ActivePassive, PassiveActive};
 
 pub type Clause = Vec<i32>;
 pub type Clauses = Vec<Clause>;
 pub type Permutations = Vec<Vec<u8>>;
 
+/// Version of the clauses from [`SatEncoder::encode`].
+///
+/// It is increased whenever the clauses change, so that proofs of older clauses can be recognized,
+/// see [`crate::Certificate`].
+pub const ENCODING_VERSION: u32 = 2;
+
 /// SAT problem encoder for LCL problems and biregular graphs.
 ///
 /// `SatEncoder` can be used to encode LCL problems and biregular graphs into CNF DIMACS format.
@@ -22,8 +43,42 @@ pub struct SatEncoder {
     active_permutations: Permutations,
     passive_permutations: Permutations,
     labels: Vec<u8>,
+    /// Position of each label in `labels`, indexed by the label, see [`get_label_indices`].
+    label_indices: Vec<Option<usize>>,
+    /// Permutations that nodes cannot use because of their inputs,
+    /// as pairs of a node index and a permutation index.
+    forbidden_permutations: Vec<(usize, usize)>,
+    /// Direction of each edge with respect to its active node, indexed by the edge.
+    /// Empty if the edges are not oriented.
+    edge_directions: Vec<EdgeDirection>,
+    /// Encoding of the at-most-one constraints of the permutations of each node.
+    /// Chosen by the count of permutations if not set.
+    at_most_one: Option<AtMostOneEncoding>,
+    /// Port of each edge at its active node and at its passive node, indexed by the edge.
+    /// Empty if the ports are in the order of the edges of the graph.
+    ports: Vec<(usize, usize)>,
+}
+
+/// Counts of variables and clauses in CNF form, for comparing encodings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncodingStatistics {
+    pub variable_count: usize,
+    pub clause_count: usize,
+}
+
+impl EncodingStatistics {
+    /// Returns the counts of variables and clauses in `clauses`.
+    ///
+    /// The count of variables is the largest variable in `clauses`.
+    pub fn from_clauses(clauses: &Clauses) -> Self {
+        Self {
+            variable_count: get_variable_count(clauses),
+            clause_count: clauses.len(),
+        }
+    }
 }
 
+#[derive(Clone, Copy)]
 enum NodeOrderInEdgeRef {
     ActivePassive,
     PassiveActive,
@@ -42,21 +97,270 @@ impl SatEncoder {
 
         let labels_active = lcl_problem.active.get_labels_set();
         let labels_passive = lcl_problem.passive.get_labels_set();
-        let labels = labels_active.union(&labels_passive).copied().collect_vec();
+        let labels = labels_active
+            .union(&labels_passive)
+            .copied()
+            .sorted()
+            .collect_vec();
+
+        SatEncoder {
+            graph,
+            active_permutations,
+            passive_permutations,
+            label_indices: get_label_indices(&labels),
+            labels,
+            forbidden_permutations: vec![],
+            edge_directions: vec![],
+            at_most_one: None,
+            ports: vec![],
+        }
+    }
+
+    /// Initializes new SatEncoder with an LCL problem of mixed degrees and a graph.
+    ///
+    /// The permutations of all degrees are used,
+    /// and each node can only use the permutations of its own degree.
+    /// The graph can have nodes of different degrees in the same partition,
+    /// see [`BiregularGraph::generate_with_degrees`].
+    pub fn with_mixed_degrees(
+        lcl_problem: &MixedDegreeLclProblem,
+        graph: BiregularGraph,
+    ) -> SatEncoder {
+        let get_permutations = |by_degree: &[Configurations]| {
+            by_degree
+                .iter()
+                .flat_map(|configurations| configurations.get_permutations())
+                .collect_vec()
+        };
+        let active_permutations = get_permutations(&lcl_problem.active);
+        let passive_permutations = get_permutations(&lcl_problem.passive);
+
+        let labels = active_permutations
+            .iter()
+            .chain(&passive_permutations)
+            .flatten()
+            .copied()
+            .unique()
+            .collect_vec();
+
+        SatEncoder {
+            graph,
+            active_permutations,
+            passive_permutations,
+            label_indices: get_label_indices(&labels),
+            labels,
+            forbidden_permutations: vec![],
+            edge_directions: vec![],
+            at_most_one: None,
+            ports: vec![],
+        }
+    }
+
+    /// Initializes new SatEncoder with an LCL problem with inputs, a biregular graph and inputs of its nodes.
+    ///
+    /// The permutations of all inputs are used, and each node is forbidden
+    /// the permutations that are not allowed with its input.
+    ///
+    /// # Panics
+    /// Panics if `assignment` does not have an input for each node of the graph.
+    pub fn with_inputs(
+        lcl_problem: &LclProblemWithInputs,
+        graph: BiregularGraph,
+        assignment: &InputAssignment,
+    ) -> SatEncoder {
+        assert_eq!(assignment.active.len(), graph.partition_a.len());
+        assert_eq!(assignment.passive.len(), graph.partition_b.len());
+
+        let get_permutations = |inputs: &[Configurations]| {
+            inputs
+                .iter()
+                .map(|configurations| configurations.get_permutations())
+                .collect_vec()
+        };
+        let active_input_permutations = get_permutations(&lcl_problem.active);
+        let passive_input_permutations = get_permutations(&lcl_problem.passive);
+
+        let active_permutations: Permutations = active_input_permutations
+            .iter()
+            .flatten()
+            .cloned()
+            .unique()
+            .collect();
+        let passive_permutations: Permutations = passive_input_permutations
+            .iter()
+            .flatten()
+            .cloned()
+            .unique()
+            .collect();
+
+        let labels = active_permutations
+            .iter()
+            .chain(&passive_permutations)
+            .flatten()
+            .copied()
+            .unique()
+            .collect_vec();
+
+        let mut forbidden_permutations = vec![];
+        let mut forbid = |nodes: &[NodeIndex],
+                          inputs: &[u8],
+                          input_permutations: &[Permutations],
+                          permutations: &Permutations| {
+            for (node, &input) in nodes.iter().zip(inputs) {
+                let allowed: HashSet<&Vec<u8>> =
+                    input_permutations[input as usize].iter().collect();
+                for (permutation_index, permutation) in permutations.iter().enumerate() {
+                    if !allowed.contains(permutation) {
+                        forbidden_permutations.push((node.index(), permutation_index));
+                    }
+                }
+            }
+        };
+        forbid(
+            &graph.partition_a,
+            &assignment.active,
+            &active_input_permutations,
+            &active_permutations,
+        );
+        forbid(
+            &graph.partition_b,
+            &assignment.passive,
+            &passive_input_permutations,
+            &passive_permutations,
+        );
+
+        SatEncoder {
+            graph,
+            active_permutations,
+            passive_permutations,
+            label_indices: get_label_indices(&labels),
+            labels,
+            forbidden_permutations,
+            edge_directions: vec![],
+            at_most_one: None,
+            ports: vec![],
+        }
+    }
+
+    /// Initializes new SatEncoder with an oriented LCL problem, a biregular graph and an orientation of its edges.
+    ///
+    /// `edge_directions` has the direction of each edge with respect to its active node, indexed by the edge,
+    /// see [`BiregularGraph::get_orientations`].
+    /// Labels stay tagged with their directions, see [`crate::lcl_problem::oriented::tag_label`].
+    /// The directions of passive labels are reversed, so both endpoints of an edge use the same tagged label,
+    /// and each edge can only have the labels of its direction.
+    ///
+    /// # Panics
+    /// Panics if `edge_directions` does not have a direction for each edge of the graph.
+    pub fn with_orientation(
+        lcl_problem: &OrientedLclProblem,
+        graph: BiregularGraph,
+        edge_directions: &[EdgeDirection],
+    ) -> SatEncoder {
+        assert_eq!(edge_directions.len(), graph.graph.edge_count());
+
+        let active_permutations = lcl_problem.active.get_permutations();
+        let passive_permutations = lcl_problem
+            .passive
+            .get_permutations()
+            .into_iter()
+            .map(|permutation| permutation.into_iter().map(|tagged| tagged ^ 1).collect())
+            .collect_vec();
+
+        let labels = active_permutations
+            .iter()
+            .chain(&passive_permutations)
+            .flatten()
+            .copied()
+            .unique()
+            .sorted()
+            .collect_vec();
 
         SatEncoder {
             graph,
             active_permutations,
             passive_permutations,
+            label_indices: get_label_indices(&labels),
             labels,
+            forbidden_permutations: vec![],
+            edge_directions: edge_directions.to_vec(),
+            at_most_one: None,
+            ports: vec![],
         }
     }
 
+    /// Sets the encoding of the constraints that each node has at most one permutation.
+    ///
+    /// By default the encoding is chosen by the count of permutations,
+    /// see [`AtMostOneEncoding::for_group_size`].
+    pub fn with_at_most_one(mut self, encoding: AtMostOneEncoding) -> Self {
+        self.at_most_one = Some(encoding);
+        self
+    }
+
+    /// Sets the port of each edge at its active node and at its passive node, indexed by the edge.
+    ///
+    /// The permutation of a node assigns its labels to its edges in the order of their ports.
+    /// By default the ports of a node are in the order of its edges in the graph.
+    /// Ports do not affect solvability, but they affect the clauses, see [`crate::Certificate`].
+    /// [`SatEncoder::decode`] gives permutations in the order of the ports.
+    ///
+    /// # Panics
+    /// Panics if `ports` does not have the ports of each edge of the graph.
+    pub fn with_ports(mut self, ports: Vec<(usize, usize)>) -> Self {
+        assert_eq!(ports.len(), self.graph.graph.edge_count());
+        self.ports = ports;
+        self
+    }
+
     /// Returns a reference of the inner graph.
     pub fn get_graph(&self) -> &BiregularGraph {
         &self.graph
     }
 
+    /// Returns the count of variables in the clauses from [`SatEncoder::encode`].
+    ///
+    /// Variables are numbered from 1, so larger variables can be used for extending the encoding.
+    pub fn get_variable_count(&self) -> usize {
+        let auxiliary_variable_count = |permutations_len: usize| {
+            self.get_at_most_one(permutations_len)
+                .get_auxiliary_variable_count(permutations_len)
+        };
+        self.get_auxiliary_variable_base()
+            + self.graph.partition_a.len()
+                * auxiliary_variable_count(self.active_permutations.len())
+            + self.graph.partition_b.len()
+                * auxiliary_variable_count(self.passive_permutations.len())
+    }
+
+    /// Returns the counts of variables and clauses of [`SatEncoder::encode`].
+    pub fn get_statistics(&self) -> EncodingStatistics {
+        EncodingStatistics {
+            variable_count: self.get_variable_count(),
+            clause_count: self.encode().len(),
+        }
+    }
+
+    /// Returns the permutations of the active configurations, in the order of their variables.
+    pub fn get_active_permutations(&self) -> &Permutations {
+        &self.active_permutations
+    }
+
+    /// Returns the permutations of the passive configurations, in the order of their variables.
+    pub fn get_passive_permutations(&self) -> &Permutations {
+        &self.passive_permutations
+    }
+
+    /// Returns the variable of the permutation with `permutation_index` of the active or passive `node`.
+    pub fn get_permutation_variable(
+        &self,
+        active: bool,
+        node: NodeIndex,
+        permutation_index: usize,
+    ) -> i32 {
+        self.var_permutation(active, node.index(), permutation_index)
+    }
+
     /// Encodes LCL problem and a bipartite graph into CNF form.
     ///
     /// Returns clauses of type `Clauses`.
@@ -69,14 +373,13 @@ impl SatEncoder {
         // 1. Adjacent nodes need to agree on the edge's label.
         // In other words, two adjacent nodes cannot label their shared edge differently.
         for node in &self.graph.partition_a {
-            for incident_edge in self.graph.graph.edges(*node) {
+            for incident_edge in self.get_edges(*node, true) {
                 let all_label_pairs = self.labels.iter().permutations(2);
 
                 for label_pair in all_label_pairs {
-                    let var_node =
-                        self.var_label(ActivePassive, incident_edge, *label_pair[0] as usize);
+                    let var_node = self.var_label(ActivePassive, incident_edge, *label_pair[0]);
                     let var_neighbour =
-                        self.var_label(PassiveActive, incident_edge, *label_pair[1] as usize);
+                        self.var_label(PassiveActive, incident_edge, *label_pair[1]);
                     clauses.extend(at_most_one(&[var_node, var_neighbour]));
                 }
             }
@@ -84,22 +387,31 @@ impl SatEncoder {
 
         // 2. Nodes need to have a valid labeling.
 
+        // Auxiliary variables of at-most-one constraints come after the variables of labels.
+        let mut next_variable = self.get_auxiliary_variable_base() as i32 + 1;
+
         // 2.1 Each active node has only one permutation
+        let at_most_one = self.get_at_most_one(active_permutations_len);
         for active_node in &self.graph.partition_a {
             let vars = (0..active_permutations_len)
                 .map(|permutation_index| {
                     self.var_permutation(true, active_node.index(), permutation_index)
                 })
                 .collect_vec();
-            clauses.extend(only_one(&vars));
+            clauses.extend(at_least_one(&vars));
+            clauses.extend(at_most_one.encode(&vars, &mut next_variable));
         }
 
         // 2.2 Each passive node has only one permutation
+        let at_most_one = self.get_at_most_one(passive_permutations_len);
         for passive_node in &self.graph.partition_b {
-            let vars = (0..passive_permutations_len).map(|permutation_index| {
-                self.var_permutation(false, passive_node.index(), permutation_index)
-            });
-            clauses.extend(only_one(&vars.collect_vec()));
+            let vars = (0..passive_permutations_len)
+                .map(|permutation_index| {
+                    self.var_permutation(false, passive_node.index(), permutation_index)
+                })
+                .collect_vec();
+            clauses.extend(at_least_one(&vars));
+            clauses.extend(at_most_one.encode(&vars, &mut next_variable));
         }
 
         // 2.3 If a node has a labeling (a permutation of a configuration) then and only then
@@ -111,13 +423,18 @@ impl SatEncoder {
                 let var_permutation =
                     self.var_permutation(true, active_node.index(), permutation_index);
 
-                for (incident_edge_index, incident_edge) in
-                    self.graph.graph.edges(*active_node).enumerate()
-                {
+                // Permutations of other degrees cannot be used.
+                let incident_edges = self.get_edges(*active_node, true);
+                if permutation.len() != incident_edges.len() {
+                    clauses.push(vec![-var_permutation]);
+                    continue;
+                }
+
+                for (incident_edge_index, &incident_edge) in incident_edges.iter().enumerate() {
                     let var_label = self.var_label(
                         ActivePassive,
                         incident_edge,
-                        permutation[incident_edge_index] as usize,
+                        permutation[incident_edge_index],
                     );
 
                     clauses.extend(implies(var_permutation, var_label));
@@ -131,13 +448,18 @@ impl SatEncoder {
                 let var_permutation =
                     self.var_permutation(false, passive_node.index(), permutation_index);
 
-                for (incident_edge_index, incident_edge) in
-                    self.graph.graph.edges(*passive_node).enumerate()
-                {
+                // Permutations of other degrees cannot be used.
+                let incident_edges = self.get_edges(*passive_node, false);
+                if permutation.len() != incident_edges.len() {
+                    clauses.push(vec![-var_permutation]);
+                    continue;
+                }
+
+                for (incident_edge_index, &incident_edge) in incident_edges.iter().enumerate() {
                     let var_label = self.var_label(
                         PassiveActive,
                         incident_edge,
-                        permutation[incident_edge_index] as usize,
+                        permutation[incident_edge_index],
                     );
 
                     clauses.extend(implies(var_permutation, var_label));
@@ -145,9 +467,86 @@ impl SatEncoder {
             }
         }
 
+        // 3. Nodes cannot use permutations that their inputs do not allow.
+        let active_nodes: HashSet<usize> = self
+            .graph
+            .partition_a
+            .iter()
+            .map(|node| node.index())
+            .collect();
+        for &(node_index, permutation_index) in &self.forbidden_permutations {
+            let active = active_nodes.contains(&node_index);
+            clauses.push(vec![-self.var_permutation(
+                active,
+                node_index,
+                permutation_index,
+            )]);
+        }
+
+        // 4. Edges cannot have labels of the opposite direction.
+        for edge in self.graph.graph.edge_references() {
+            let direction = match self.edge_directions.get(edge.id().index()) {
+                Some(&direction) => direction,
+                None => break,
+            };
+            for &label in &self.labels {
+                if untag_label(label).1 != direction {
+                    clauses.push(vec![-self.var_label(ActivePassive, edge, label)]);
+                }
+            }
+        }
+
         clauses
     }
 
+    /// Decodes a satisfying assignment of the encoded clauses into a labeling of the graph.
+    ///
+    /// Each node gets the permutation whose variable is true, and each half of an edge the label whose variable is true.
+    /// The permutations are in the order of the ports, see [`SatEncoder::with_ports`].
+    /// Labels are as encoded, so with [`SatEncoder::with_orientation`]
+    /// they are tagged and passive labels have reversed directions.
+    ///
+    /// # Panics
+    /// Panics if `model` does not satisfy the clauses from [`SatEncoder::encode`].
+    pub fn decode(&self, model: &Model) -> Labeling {
+        let is_true = |variable: i32| model.get(variable as usize - 1) == Some(&variable);
+
+        let mut permutations = vec![vec![]; self.graph.graph.node_count()];
+        for (active, nodes, node_permutations) in [
+            (true, &self.graph.partition_a, &self.active_permutations),
+            (false, &self.graph.partition_b, &self.passive_permutations),
+        ] {
+            for node in nodes {
+                let permutation_index = (0..node_permutations.len())
+                    .find(|&index| is_true(self.var_permutation(active, node.index(), index)))
+                    .expect("Node has no permutation in the model");
+                permutations[node.index()] = node_permutations[permutation_index].clone();
+            }
+        }
+
+        let half_edge_labels = self
+            .graph
+            .graph
+            .edge_references()
+            .map(|edge| {
+                let label = |node_order: NodeOrderInEdgeRef| {
+                    *self
+                        .labels
+                        .iter()
+                        .find(|&&label| is_true(self.var_label(node_order, edge, label)))
+                        .expect("Edge has no label in the model")
+                };
+                (label(ActivePassive), label(PassiveActive))
+            })
+            .collect();
+
+        Labeling {
+            permutations,
+            half_edge_labels,
+            ports: self.ports.clone(),
+        }
+    }
+
     /// Returns a string containing CNF DIMACS formatted clauses.
     ///
     /// # Useful links
@@ -155,14 +554,37 @@ impl SatEncoder {
     /// - [Specification](http://www.domagoj-babic.com/uploads/ResearchProjects/Spear/dimacs-cnf.pdf)
     /// - [Some site](https://people.sc.fsu.edu/~jburkardt/data/cnf/cnf.html)
     pub fn clauses_into_cnf_dimacs(&self, clauses: &Clauses, variable_count: usize) -> String {
-        let mut result = String::new();
-        result.push_str(&format!("p cnf{} {}\n", variable_count, clauses.len()));
-
-        clauses.iter().for_each(|x| {
-            let clause = format!("{} 0\n", x.iter().join(" "));
-            result.push_str(&clause);
-        });
-        result
+        clauses_to_dimacs_with_variable_count(clauses, variable_count)
+    }
+
+    /// Returns the edges of the active or passive `node` in the order of its ports, see [`SatEncoder::with_ports`].
+    fn get_edges(&self, node: NodeIndex, active: bool) -> Vec<EdgeReference<'_, (), u32>> {
+        let mut edges = self.graph.graph.edges(node).collect_vec();
+        if !self.ports.is_empty() {
+            edges.sort_by_key(|edge| {
+                let (active_port, passive_port) = self.ports[edge.id().index()];
+                if active {
+                    active_port
+                } else {
+                    passive_port
+                }
+            });
+        }
+        edges
+    }
+
+    /// Returns the encoding of the at-most-one constraint of `group_size` permutation variables.
+    fn get_at_most_one(&self, group_size: usize) -> AtMostOneEncoding {
+        self.at_most_one
+            .unwrap_or_else(|| AtMostOneEncoding::for_group_size(group_size))
+    }
+
+    /// Returns the count of variables of permutations and labels,
+    /// after which the auxiliary variables of at-most-one constraints start.
+    fn get_auxiliary_variable_base(&self) -> usize {
+        self.graph.partition_a.len() * self.active_permutations.len()
+            + self.graph.partition_b.len() * self.passive_permutations.len()
+            + 2 * self.graph.graph.edge_count() * self.labels.len()
     }
 
     /// Returns a variable representing a permutation of labels in some configuration.
@@ -214,12 +636,15 @@ impl SatEncoder {
     /// # Parameters
     /// - `first_active` tells if the first node of the `edge` is active or passive. The second node is always in the opposite partition of the graph.
     /// - `edge` is the reference to the edge in internal graph [`self.graph.graph`].
-    /// - `label` is the label of the label.
+    /// - `label` is the label, whose variables are indexed by its position in the labels of the problem.
+    ///
+    /// # Panics
+    /// Panics if `label` is not a label of the problem.
     fn var_label(
         &self,
         node_order: NodeOrderInEdgeRef,
         edge: EdgeReference<(), u32>,
-        label: usize,
+        label: u8,
     ) -> i32 {
         let active_permutations_size = self.active_permutations.len();
         let passive_permutations_size = self.passive_permutations.len();
@@ -233,7 +658,9 @@ impl SatEncoder {
 
         let labels_count = self.labels.len();
 
-        let v = edge.id().index() * labels_count + label;
+        let label_index =
+            self.label_indices[label as usize].expect("Label is not a label of the problem");
+        let v = edge.id().index() * labels_count + label_index;
 
         match node_order {
             ActivePassive => return base + (v as i32),
@@ -257,7 +684,7 @@ impl SatEncoder {
 
     /// Variable to a human-readable string.
     ///
-    /// There are 4 types of variables:
+    /// There are 5 types of variables:
     /// - Active node permutation
     ///   - Output: "<sign>A<node_index>_<permutation_index>"
     ///   - Example: "-A3_4"
@@ -270,6 +697,9 @@ impl SatEncoder {
     /// - Label of an edge between passive and active node
     ///   - Output: "<sign>PA_<edge_index>_<label>"
     ///   - Example: "-AP_2_1"
+    /// - Auxiliary variable of an at-most-one constraint
+    ///   - Output: "<sign>X_<variable>"
+    ///   - Example: "-X_42"
     ///
     fn var_to_string(&self, variable: i32) -> String {
         let is_positive = variable > 0;
@@ -312,8 +742,7 @@ impl SatEncoder {
         if range_active_edge_labels.contains(&variable_abs) {
             let edge_index =
                 EdgeIndexable::from_index(&self.graph.graph, (variable_abs - base) / labels_count);
-            let temp = (variable_abs - base) % (passive_nodes_len * labels_count);
-            let label = temp % labels_count;
+            let label = self.labels[(variable_abs - base) % labels_count];
             return format!("{}AP_{}_{}", sign_str, edge_index.index(), label);
         }
 
@@ -323,12 +752,11 @@ impl SatEncoder {
         if range_passive_edge_labels.contains(&variable_abs) {
             let edge_index =
                 EdgeIndexable::from_index(&self.graph.graph, (variable_abs - base) / labels_count);
-            let temp = (variable_abs - base) % (active_nodes_len * labels_count);
-            let label = temp % labels_count;
+            let label = self.labels[(variable_abs - base) % labels_count];
             return format!("{}PA_{}_{}", sign_str, edge_index.index(), label);
         }
 
-        unreachable!();
+        format!("{}X_{}", sign_str, variable_abs)
     }
 
     /// Prints clauses in a human-readable format.
@@ -342,6 +770,15 @@ impl SatEncoder {
     }
 }
 
+/// Returns the position of each label of `labels`, indexed by the label, or `None` for other labels.
+fn get_label_indices(labels: &[u8]) -> Vec<Option<usize>> {
+    let mut indices = vec![None; usize::from(u8::MAX) + 1];
+    for (index, &label) in labels.iter().enumerate() {
+        indices[label as usize] = Some(index);
+    }
+    indices
+}
+
 fn at_least_one(variables: &[i32]) -> Clauses {
     vec![variables.iter().copied().collect_vec()]
 }
@@ -361,6 +798,104 @@ fn implies(variable_0: i32, variable_1: i32) -> Clauses {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::get_parity_problem;
+    use crate::{SatResult, SatSolver};
+
+    #[test]
+    fn test_decode() {
+        let problem = get_parity_problem();
+        let encoder = SatEncoder::new(&problem, BiregularGraph::cycle(2));
+        let model = match SatSolver::solve(encoder.encode()) {
+            SatResult::Satisfiable(model) => model,
+            _ => panic!("Problem should be solvable on the cycle"),
+        };
+        let labeling = encoder.decode(&model);
+        assert!(labeling.is_consistent(encoder.get_graph()));
+        for node in [0, 2] {
+            assert!(labeling.permutations[node].iter().sorted().eq(&[0, 1]));
+        }
+        for node in [1, 3] {
+            assert!(labeling.permutations[node].iter().all_equal());
+        }
+
+        let text = labeling.to_string();
+        assert_eq!(text.lines().count(), 8);
+        assert!(text.starts_with("node 0: "));
+        let dot = labeling.get_dot(encoder.get_graph(), |l| problem.get_label_name(l));
+        assert!(dot.contains("label = \"0: "));
+    }
+
+    #[test]
+    fn test_decode_with_ports() {
+        // Ports in the reverse order of the edges of each node.
+        let graph = BiregularGraph::cycle(2);
+        let mut ports = vec![(0, 0); graph.graph.edge_count()];
+        for node in graph.graph.node_indices() {
+            for (port, edge) in graph
+                .graph
+                .edges(node)
+                .collect_vec()
+                .iter()
+                .rev()
+                .enumerate()
+            {
+                let ports = &mut ports[edge.id().index()];
+                if graph.partition_a.contains(&node) {
+                    ports.0 = port;
+                } else {
+                    ports.1 = port;
+                }
+            }
+        }
+
+        let encoder = SatEncoder::new(&get_parity_problem(), graph).with_ports(ports.clone());
+        let model = match SatSolver::solve(encoder.encode()) {
+            SatResult::Satisfiable(model) => model,
+            _ => panic!("Problem should be solvable on the cycle"),
+        };
+        let mut labeling = encoder.decode(&model);
+        assert_eq!(labeling.ports, ports);
+        assert!(labeling.is_consistent(encoder.get_graph()));
+
+        // Active nodes have different labels, which are in the wrong order without the ports.
+        labeling.ports.clear();
+        assert!(!labeling.is_consistent(encoder.get_graph()));
+    }
+
+    #[test]
+    fn test_at_most_one_encodings_agree() {
+        // Labels 1 and 2 with a gap before them must not leave unused variables.
+        let problem = LclProblem::new("AB BB", "AA AB").unwrap();
+        let gapped = LclProblem::from_configurations(
+            problem.active.map_labels(&vec![1, 2]),
+            problem.passive.map_labels(&vec![1, 2]),
+        );
+        let problems = [
+            get_parity_problem(),
+            LclProblem::new("AB AC BC", "AA BB CC").unwrap(),
+            gapped,
+        ];
+        for problem in &problems {
+            let expected =
+                SatSolver::solve(SatEncoder::new(problem, BiregularGraph::cycle(2)).encode());
+            for name in at_most_one::get_at_most_one_names() {
+                let encoder = SatEncoder::new(problem, BiregularGraph::cycle(2))
+                    .with_at_most_one(AtMostOneEncoding::from_name(name).unwrap());
+                let clauses = encoder.encode();
+                assert_eq!(
+                    encoder.get_statistics(),
+                    EncodingStatistics::from_clauses(&clauses),
+                    "{}",
+                    name
+                );
+                let result = SatSolver::solve(clauses);
+                assert_eq!(result.is_satisfiable(), expected.is_satisfiable());
+                if let SatResult::Satisfiable(model) = result {
+                    assert!(encoder.decode(&model).is_consistent(encoder.get_graph()));
+                }
+            }
+        }
+    }
 
     #[test]
     fn test_at_least_one() {
@@ -0,0 +1,205 @@
+use super::{Clauses, SatEncoder};
+use crate::lcl_problem::configurations::Configurations;
+use crate::{BiregularGraph, LclProblem};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// SAT encoder of a graph for all problems of a class, which are selected with assumptions.
+///
+/// The graph is encoded with [`SatEncoder`] for the problem that allows every configuration of the class,
+/// and each configuration gets a selector variable that must be true if a node uses one of its permutations.
+/// A problem of the class is then solved by assuming the selectors of the configurations
+/// that it does not allow to be false, see [`SelectorEncoder::get_assumptions`].
+/// So the clauses can be given to an incremental SAT solver once and solved for each problem,
+/// see [`crate::sat_solver::incremental::IncrementalSatSolver`].
+pub struct SelectorEncoder {
+    encoder: SatEncoder,
+    /// Index of each active configuration, with sorted labels.
+    active_configurations: HashMap<Vec<u8>, usize>,
+    /// Index of each passive configuration, with sorted labels.
+    passive_configurations: HashMap<Vec<u8>, usize>,
+}
+
+impl SelectorEncoder {
+    /// Initializes the encoder for the problems of `label_count` labels on `graph`.
+    ///
+    /// The degrees of the class are the degrees of the graph, and `label_count` is at most 256.
+    pub fn new(graph: BiregularGraph, label_count: usize) -> Self {
+        assert!(label_count <= 256, "Labels are u8");
+        let get_configurations = |degree: usize| {
+            (0..label_count)
+                .map(|label| label as u8)
+                .combinations_with_replacement(degree)
+                .collect_vec()
+        };
+        let active = get_configurations(graph.degree_a);
+        let passive = get_configurations(graph.degree_b);
+        let get_indices = |configurations: &[Vec<u8>]| {
+            configurations
+                .iter()
+                .cloned()
+                .enumerate()
+                .map(|(index, configuration)| (configuration, index))
+                .collect()
+        };
+        let active_configurations = get_indices(&active);
+        let passive_configurations = get_indices(&passive);
+
+        let problem = LclProblem::from_configurations(
+            Configurations::from_configuration_data(active)
+                .expect("Class has at least one configuration"),
+            Configurations::from_configuration_data(passive)
+                .expect("Class has at least one configuration"),
+        );
+        Self {
+            encoder: SatEncoder::new(&problem, graph),
+            active_configurations,
+            passive_configurations,
+        }
+    }
+
+    /// Returns the inner encoder of the problem that allows every configuration.
+    pub fn get_encoder(&self) -> &SatEncoder {
+        &self.encoder
+    }
+
+    /// Encodes the graph and the selectors into CNF form.
+    pub fn encode(&self) -> Clauses {
+        let mut clauses = self.encoder.encode();
+        let graph = self.encoder.get_graph();
+
+        // A node can use a permutation only if the configuration of the permutation is selected.
+        for (active, nodes, permutations, configurations) in [
+            (
+                true,
+                &graph.partition_a,
+                self.encoder.get_active_permutations(),
+                &self.active_configurations,
+            ),
+            (
+                false,
+                &graph.partition_b,
+                self.encoder.get_passive_permutations(),
+                &self.passive_configurations,
+            ),
+        ] {
+            for (permutation_index, permutation) in permutations.iter().enumerate() {
+                let configuration = permutation.iter().copied().sorted().collect_vec();
+                let var_selector = self.var_selector(active, configurations[&configuration]);
+                for &node in nodes {
+                    let var_permutation =
+                        self.encoder
+                            .get_permutation_variable(active, node, permutation_index);
+                    clauses.push(vec![-var_permutation, var_selector]);
+                }
+            }
+        }
+        clauses
+    }
+
+    /// Returns the assumptions with which the clauses from [`SelectorEncoder::encode`]
+    /// are satisfiable if and only if `problem` can be solved on the graph.
+    ///
+    /// Returns `None` if the problem is not in the class of the encoder,
+    /// i.e. it has other degrees or labels that are not below the label count.
+    pub fn get_assumptions(&self, problem: &LclProblem) -> Option<Vec<i32>> {
+        let mut assumptions = vec![];
+        for (active, problem_configurations, configurations) in [
+            (true, &problem.active, &self.active_configurations),
+            (false, &problem.passive, &self.passive_configurations),
+        ] {
+            let mut selected = vec![false; configurations.len()];
+            for configuration in problem_configurations.get_configurations() {
+                let configuration = configuration.iter().copied().sorted().collect_vec();
+                selected[*configurations.get(&configuration)?] = true;
+            }
+            assumptions.extend(
+                selected
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &selected)| !selected)
+                    .map(|(index, _)| -self.var_selector(active, index)),
+            );
+        }
+        Some(assumptions)
+    }
+
+    /// Returns the selector variable of the active or passive configuration with `configuration_index`.
+    ///
+    /// Selectors come after the variables of the inner encoder.
+    fn var_selector(&self, active: bool, configuration_index: usize) -> i32 {
+        let base = self.encoder.get_variable_count();
+        let offset = if active {
+            0
+        } else {
+            self.active_configurations.len()
+        };
+        (base + offset + configuration_index + 1) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sat_solver::get_variable_count;
+    use crate::test_utils::get_parity_problem;
+    use crate::{SatResult, SatSolver};
+
+    #[test]
+    fn test_assumptions_agree_with_encoder() {
+        for k in 2..=3 {
+            let encoder = SelectorEncoder::new(BiregularGraph::cycle(k), 3);
+            let clauses = encoder.encode();
+            assert_eq!(
+                encoder.get_encoder().get_variable_count(),
+                get_variable_count(&encoder.get_encoder().encode())
+            );
+
+            for problem in LclProblem::generate_normalized(2, 2, 3) {
+                let mut with_assumptions = clauses.clone();
+                let assumptions = encoder.get_assumptions(&problem).unwrap();
+                with_assumptions.extend(assumptions.into_iter().map(|literal| vec![literal]));
+
+                let expected =
+                    SatSolver::solve(SatEncoder::new(&problem, BiregularGraph::cycle(k)).encode());
+                assert_eq!(
+                    SatSolver::solve(with_assumptions).is_satisfiable(),
+                    expected.is_satisfiable(),
+                    "{} on a cycle of {} nodes",
+                    problem.to_string(),
+                    2 * k
+                );
+            }
+        }
+
+        let problem = get_parity_problem();
+        let encoder = SelectorEncoder::new(BiregularGraph::cycle(3), 2);
+        let mut clauses = encoder.encode();
+        clauses.extend(
+            encoder
+                .get_assumptions(&problem)
+                .unwrap()
+                .into_iter()
+                .map(|literal| vec![literal]),
+        );
+        assert_eq!(SatSolver::solve(clauses), SatResult::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_assumptions_outside_class() {
+        let encoder = SelectorEncoder::new(BiregularGraph::cycle(2), 2);
+        assert_eq!(
+            encoder.get_assumptions(&LclProblem::new("AB", "AC").unwrap()),
+            None
+        );
+        assert_eq!(
+            encoder.get_assumptions(&LclProblem::new("AAB", "AA").unwrap()),
+            None
+        );
+        // Every configuration of the class is allowed, so nothing is assumed.
+        assert_eq!(
+            encoder.get_assumptions(&LclProblem::new("AA AB BB", "AA AB BB").unwrap()),
+            Some(vec![])
+        );
+    }
+}
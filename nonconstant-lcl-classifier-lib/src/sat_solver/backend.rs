@@ -0,0 +1,494 @@
+use super::{clauses_to_dimacs, get_variable_count, SatLimits, SatResult};
+use crate::sat_encoder::Clauses;
+use log::warn;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval of checking whether an external solver has finished before its time limit.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// SAT solver that [`super::SatSolver`] uses to solve problems.
+///
+/// The backend is selected at runtime with [`super::SatSolver::set_backend`].
+/// Kissat is the default if the `kissat` feature is enabled, which it is by default.
+pub trait SatBackend: Send + Sync {
+    /// Returns the name of the backend.
+    fn get_name(&self) -> String;
+
+    /// Solves the SAT problem `clauses`, or returns [`SatResult::Unknown`] if the solver reaches one of the `limits`.
+    ///
+    /// The model of a satisfiable result has every variable up to the largest variable in `clauses`.
+    ///
+    /// # Errors
+    /// Returns an error if the solver fails.
+    fn solve(&self, clauses: Clauses, limits: &SatLimits) -> Result<SatResult, Box<dyn Error>>;
+}
+
+/// Kissat SAT solver linked into the library through `kissat-rs`.
+///
+/// Requires the `kissat` feature.
+#[cfg(feature = "kissat")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KissatBackend;
+
+#[cfg(feature = "kissat")]
+impl SatBackend for KissatBackend {
+    fn get_name(&self) -> String {
+        "kissat".to_string()
+    }
+
+    /// Variables that can have either value are false in the model.
+    ///
+    /// Kissat cannot be interrupted through `kissat-rs`, so if any limit is set,
+    /// the Kissat executable is run instead, see [`get_kissat_executable_backend`].
+    fn solve(&self, clauses: Clauses, limits: &SatLimits) -> Result<SatResult, Box<dyn Error>> {
+        use kissat_rs::Assignment;
+
+        if !limits.is_unlimited() {
+            return get_kissat_executable_backend().solve(clauses, limits);
+        }
+        let variable_count = get_variable_count(&clauses) as i32;
+        let assignment = kissat_rs::Solver::solve_formula(clauses)
+            .map_err(|_| "Kissat failed to solve the problem".to_string())?;
+        Ok(match assignment {
+            Some(assignment) => {
+                let model = (1..=variable_count)
+                    .map(|variable| match assignment.get(&variable) {
+                        Some(Assignment::True) => variable,
+                        _ => -variable,
+                    })
+                    .collect();
+                SatResult::Satisfiable(model)
+            }
+            None => SatResult::Unsatisfiable,
+        })
+    }
+}
+
+/// Varisat SAT solver, which is written in Rust and needs no C toolchain.
+///
+/// Requires the `varisat` feature. Each solve uses a new [`VarisatSolver`].
+#[cfg(feature = "varisat")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VarisatBackend;
+
+#[cfg(feature = "varisat")]
+impl SatBackend for VarisatBackend {
+    fn get_name(&self) -> String {
+        "varisat".to_string()
+    }
+
+    /// Only the time limit is supported, and only on a best-effort basis, see [`solve_with_time_limit`].
+    fn solve(&self, clauses: Clauses, limits: &SatLimits) -> Result<SatResult, Box<dyn Error>> {
+        use super::incremental::{IncrementalSatSolver, VarisatSolver};
+
+        warn_unsupported_limits(self, limits);
+        solve_with_time_limit(limits.time, move || {
+            let mut solver = VarisatSolver::new();
+            for clause in &clauses {
+                solver.add_clause(clause);
+            }
+            solver
+                .solve_with_assumptions(&[])
+                .map_err(|error| error.to_string())
+        })
+    }
+}
+
+/// Any SAT solver executable that reads CNF DIMACS from stdin and writes its result in the SAT competition format.
+///
+/// The output has the line `s SATISFIABLE`, `s UNSATISFIABLE` or `s UNKNOWN`,
+/// and for satisfiable problems lines starting with `v` that contain the model, ending in `0`.
+/// Solvers such as Kissat, CaDiCaL and MiniSat (with `-verb=0 /dev/stdin /dev/stdout`) can be used this way.
+///
+/// The solver is killed at the time limit.
+/// Limits on conflicts and propagations are passed to the solver by replacing
+/// `{conflicts}` and `{propagations}` in its arguments with the limits,
+/// e.g. `kissat -q --conflicts={conflicts}`.
+/// Arguments with a placeholder of a limit that is not set are left out,
+/// and limits without a placeholder are ignored with a warning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalBackend {
+    /// Executable of the solver, which is searched from `PATH`.
+    pub command: String,
+    /// Arguments given to the executable.
+    pub args: Vec<String>,
+}
+
+impl ExternalBackend {
+    /// Creates a backend from a command line, whose first word is the executable and the rest are arguments.
+    ///
+    /// Returns `None` if the command line is empty.
+    pub fn from_command_line(command_line: &str) -> Option<Self> {
+        let mut words = command_line.split_whitespace().map(str::to_string);
+        let command = words.next()?;
+        Some(Self {
+            command,
+            args: words.collect(),
+        })
+    }
+
+    /// Returns the arguments with the placeholders replaced by the `limits`.
+    fn get_args(&self, limits: &SatLimits) -> Vec<String> {
+        let placeholders = [
+            ("{conflicts}", limits.conflicts),
+            ("{propagations}", limits.propagations),
+        ];
+        self.args
+            .iter()
+            .filter_map(|arg| {
+                let mut arg = arg.clone();
+                for (placeholder, limit) in placeholders {
+                    if arg.contains(placeholder) {
+                        arg = arg.replace(placeholder, &limit?.to_string());
+                    }
+                }
+                Some(arg)
+            })
+            .collect()
+    }
+}
+
+impl SatBackend for ExternalBackend {
+    fn get_name(&self) -> String {
+        self.command.clone()
+    }
+
+    fn solve(&self, clauses: Clauses, limits: &SatLimits) -> Result<SatResult, Box<dyn Error>> {
+        let placeholders = [
+            ("{conflicts}", limits.conflicts, "conflicts"),
+            ("{propagations}", limits.propagations, "propagations"),
+        ];
+        for (placeholder, limit, name) in placeholders.iter() {
+            if limit.is_some() && !self.args.iter().any(|arg| arg.contains(placeholder)) {
+                warn!(
+                    "SAT solver {} has no {} argument, so the limit on {} is ignored",
+                    self.command, placeholder, name
+                );
+            }
+        }
+
+        let mut child = Command::new(&self.command)
+            .args(self.get_args(limits))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("stdin of the solver is piped")
+            .write_all(clauses_to_dimacs(&clauses).as_bytes())?;
+
+        // Output is read in another thread, so that the solver does not block on a full pipe.
+        let mut stdout = child.stdout.take().expect("stdout of the solver is piped");
+        let reader = thread::spawn(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).map(|_| output)
+        });
+
+        if let Some(time) = limits.time {
+            let deadline = Instant::now() + time;
+            while child.try_wait()?.is_none() {
+                if Instant::now() >= deadline {
+                    child.kill()?;
+                    child.wait()?;
+                    return Ok(SatResult::Unknown);
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        } else {
+            child.wait()?;
+        }
+        let output = reader
+            .join()
+            .map_err(|_| "Reading the output of the solver failed")??;
+        parse_solver_output(&output, get_variable_count(&clauses))
+    }
+}
+
+/// Parses the output of a SAT solver in the SAT competition format, see [`ExternalBackend`].
+///
+/// Variables up to `variable_count` that are missing from the model are false.
+pub fn parse_solver_output(
+    output: &str,
+    variable_count: usize,
+) -> Result<SatResult, Box<dyn Error>> {
+    let mut satisfiable = None;
+    let mut values = vec![false; variable_count + 1];
+    for line in output.lines().map(str::trim) {
+        if let Some(status) = line.strip_prefix("s ") {
+            satisfiable = match status.trim() {
+                "SATISFIABLE" => Some(true),
+                "UNSATISFIABLE" => Some(false),
+                "UNKNOWN" => return Ok(SatResult::Unknown),
+                status => return Err(format!("Solver returned status '{}'", status).into()),
+            };
+        } else if let Some(literals) = line.strip_prefix('v') {
+            for literal in literals.split_whitespace() {
+                let literal = literal.parse::<i64>()?;
+                if literal > 0 && literal as usize <= variable_count {
+                    values[literal as usize] = true;
+                }
+            }
+        }
+    }
+
+    match satisfiable {
+        Some(true) => {
+            let model = (1..=variable_count as i32)
+                .map(|variable| {
+                    if values[variable as usize] {
+                        variable
+                    } else {
+                        -variable
+                    }
+                })
+                .collect();
+            Ok(SatResult::Satisfiable(model))
+        }
+        Some(false) => Ok(SatResult::Unsatisfiable),
+        None => Err("Solver output has no status line".into()),
+    }
+}
+
+/// Runs `solve` in another thread and returns [`SatResult::Unknown`] if it does not finish in `time`.
+///
+/// This is best-effort: solvers that run in this process cannot be interrupted,
+/// so after the time limit the thread keeps running in the background until the solver finishes,
+/// and such threads pile up if the limit is reached often.
+/// Backends that can be killed, see [`ExternalBackend`], should be preferred with limits.
+// Unused if the varisat feature is not enabled.
+#[allow(dead_code)]
+fn solve_with_time_limit<F>(time: Option<Duration>, solve: F) -> Result<SatResult, Box<dyn Error>>
+where
+    F: FnOnce() -> Result<SatResult, String> + Send + 'static,
+{
+    let time = match time {
+        Some(time) => time,
+        None => return Ok(solve()?),
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone if the time limit was reached.
+        let _ = sender.send(solve());
+    });
+    match receiver.recv_timeout(time) {
+        Ok(result) => Ok(result?),
+        Err(RecvTimeoutError::Timeout) => Ok(SatResult::Unknown),
+        Err(RecvTimeoutError::Disconnected) => Err("SAT solver panicked".into()),
+    }
+}
+
+/// Logs a warning if limits on conflicts or propagations are set for a backend that does not support them.
+// Unused if the varisat feature is not enabled.
+#[allow(dead_code)]
+fn warn_unsupported_limits(backend: &dyn SatBackend, limits: &SatLimits) {
+    if limits.conflicts.is_some() || limits.propagations.is_some() {
+        warn!(
+            "SAT solver {} does not support limits on conflicts or propagations, they are ignored",
+            backend.get_name()
+        );
+    }
+}
+
+/// Returns the names of the backends that [`backend_from_name`] accepts.
+pub fn get_backend_names() -> Vec<&'static str> {
+    let mut names = vec![];
+    if cfg!(feature = "kissat") {
+        names.push("kissat");
+    }
+    if cfg!(feature = "varisat") {
+        names.push("varisat");
+    }
+    names.push("external");
+    names
+}
+
+/// Returns the backend with `name`, see [`get_backend_names`].
+///
+/// The backend `external` runs `command_line`, see [`ExternalBackend::from_command_line`].
+///
+/// # Errors
+/// Returns an error if there is no such backend, e.g. because its feature is not enabled,
+/// or if `external` is given no command line.
+pub fn backend_from_name(
+    name: &str,
+    command_line: Option<&str>,
+) -> Result<Box<dyn SatBackend>, Box<dyn Error>> {
+    match name {
+        #[cfg(feature = "kissat")]
+        "kissat" => Ok(Box::new(KissatBackend)),
+        #[cfg(feature = "varisat")]
+        "varisat" => Ok(Box::new(VarisatBackend)),
+        "external" => command_line
+            .and_then(ExternalBackend::from_command_line)
+            .map(|backend| Box::new(backend) as Box<dyn SatBackend>)
+            .ok_or_else(|| "External solver needs a command".into()),
+        name => Err(format!(
+            "Unknown SAT solver '{}', expected one of: {}",
+            name,
+            get_backend_names().join(", ")
+        )
+        .into()),
+    }
+}
+
+/// Returns the backend that is used unless another one is set.
+///
+/// This is the first available of Kissat, Varisat and the Kissat executable.
+pub fn get_default_backend() -> Box<dyn SatBackend> {
+    #[cfg(feature = "kissat")]
+    return Box::new(KissatBackend);
+    #[cfg(all(not(feature = "kissat"), feature = "varisat"))]
+    return Box::new(VarisatBackend);
+    #[cfg(not(any(feature = "kissat", feature = "varisat")))]
+    return Box::new(get_kissat_executable_backend());
+}
+
+/// Returns the Kissat executable, which has to be in `PATH`, as an [`ExternalBackend`].
+///
+/// The executable is killed at the time limit and it stops at the limit on conflicts.
+/// Kissat has no limit on propagations, so that limit is ignored.
+pub fn get_kissat_executable_backend() -> ExternalBackend {
+    ExternalBackend {
+        command: "kissat".to_string(),
+        args: vec!["-q".to_string(), "--conflicts={conflicts}".to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_solver_output() {
+        let output = "c comment\ns SATISFIABLE\nv -1 2\nv 3 0\n";
+        assert_eq!(
+            parse_solver_output(output, 4).unwrap(),
+            SatResult::Satisfiable(vec![-1, 2, 3, -4])
+        );
+        assert_eq!(
+            parse_solver_output("s UNSATISFIABLE\n", 4).unwrap(),
+            SatResult::Unsatisfiable
+        );
+        assert_eq!(
+            parse_solver_output("s UNKNOWN\n", 4).unwrap(),
+            SatResult::Unknown
+        );
+        assert!(parse_solver_output("s INDETERMINATE\n", 4).is_err());
+        assert!(parse_solver_output("", 4).is_err());
+    }
+
+    #[test]
+    fn test_external_backend() {
+        // Shell script that ignores the problem and answers with a fixed model.
+        let backend = ExternalBackend {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "cat > /dev/null; echo 's SATISFIABLE'; echo 'v 1 -2 0'".to_string(),
+            ],
+        };
+        assert_eq!(
+            backend
+                .solve(vec![vec![1], vec![-2]], &SatLimits::default())
+                .unwrap(),
+            SatResult::Satisfiable(vec![1, -2])
+        );
+        assert_eq!(
+            ExternalBackend::from_command_line("cadical -q"),
+            Some(ExternalBackend {
+                command: "cadical".to_string(),
+                args: vec!["-q".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_backend_from_name() {
+        assert!(backend_from_name("external", Some("kissat -q")).is_ok());
+        assert!(backend_from_name("external", None).is_err());
+        assert!(backend_from_name("unknown", None).is_err());
+        for name in get_backend_names() {
+            if name != "external" {
+                assert_eq!(backend_from_name(name, None).unwrap().get_name(), name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_external_backend_limits() {
+        let backend = ExternalBackend::from_command_line(
+            "solver --conflicts={conflicts} --propagations={propagations} -q",
+        )
+        .unwrap();
+        let limits = SatLimits {
+            conflicts: Some(100),
+            ..SatLimits::default()
+        };
+        assert_eq!(
+            backend.get_args(&limits),
+            vec!["--conflicts=100".to_string(), "-q".to_string()]
+        );
+
+        // Solver that never answers is killed at the time limit.
+        let backend = ExternalBackend::from_command_line("sleep 10").unwrap();
+        let limits = SatLimits {
+            time: Some(Duration::from_millis(100)),
+            ..SatLimits::default()
+        };
+        let now = Instant::now();
+        assert_eq!(
+            backend.solve(vec![vec![1]], &limits).unwrap(),
+            SatResult::Unknown
+        );
+        assert!(now.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    #[cfg(feature = "kissat")]
+    #[ignore = "Requires the kissat executable"]
+    fn test_kissat_backend_limits() {
+        // Pigeonhole problem of 9 pigeons and 8 holes, which needs many conflicts.
+        let (pigeons, holes) = (9, 8);
+        let variable = |pigeon: i32, hole: i32| pigeon * holes + hole + 1;
+        let mut clauses: Clauses = (0..pigeons)
+            .map(|pigeon| (0..holes).map(|hole| variable(pigeon, hole)).collect())
+            .collect();
+        for hole in 0..holes {
+            for pigeon in 0..pigeons {
+                for other in pigeon + 1..pigeons {
+                    clauses.push(vec![-variable(pigeon, hole), -variable(other, hole)]);
+                }
+            }
+        }
+
+        let limits = SatLimits {
+            conflicts: Some(10),
+            ..SatLimits::default()
+        };
+        assert_eq!(
+            KissatBackend.solve(clauses, &limits).unwrap(),
+            SatResult::Unknown
+        );
+    }
+
+    #[test]
+    fn test_solve_with_time_limit() {
+        let result = solve_with_time_limit(Some(Duration::from_millis(10)), || {
+            thread::sleep(Duration::from_secs(1));
+            Ok(SatResult::Unsatisfiable)
+        });
+        assert_eq!(result.unwrap(), SatResult::Unknown);
+
+        let result = solve_with_time_limit(Some(Duration::from_secs(10)), || {
+            Ok(SatResult::Unsatisfiable)
+        });
+        assert_eq!(result.unwrap(), SatResult::Unsatisfiable);
+        assert!(solve_with_time_limit(None, || Err("failed".to_string())).is_err());
+    }
+}
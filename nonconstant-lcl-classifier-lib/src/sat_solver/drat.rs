@@ -0,0 +1,412 @@
+use crate::sat_encoder::{Clause, Clauses};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::{error::Error, fmt};
+
+/// Step of a DRAT proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofStep {
+    /// Adds a lemma that is implied by the current clauses.
+    Add(Clause),
+    /// Deletes a clause.
+    Delete(Clause),
+}
+
+/// Proof of unsatisfiability in the DRAT format.
+///
+/// The proof adds lemmas to the clauses of a formula and deletes clauses
+/// until the empty clause is added.
+/// Each lemma is a reverse unit propagation (RUP) or a resolution asymmetric tautology (RAT)
+/// with respect to its first literal, which can be checked with [`DratProof::check`].
+///
+/// More about DRAT [here](https://www.cs.utexas.edu/~marijn/drat-trim/).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DratProof {
+    pub steps: Vec<ProofStep>,
+}
+
+/// Reason why a DRAT proof is not valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DratError {
+    /// The lemma at the step with the given index is neither RUP nor RAT.
+    InvalidLemma(usize),
+    /// The proof never derives the empty clause.
+    NoEmptyClause,
+}
+
+impl fmt::Display for DratError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DratError::InvalidLemma(step) => {
+                write!(f, "lemma at step {} is not implied by the clauses", step)
+            }
+            DratError::NoEmptyClause => write!(f, "proof does not derive the empty clause"),
+        }
+    }
+}
+
+impl Error for DratError {}
+
+impl DratProof {
+    /// Parses a proof in the textual DRAT format.
+    ///
+    /// Each step is on its own line and ends in `0`, and deletions start with `d`.
+    /// Empty lines and comments starting with `c` are skipped.
+    ///
+    /// Example:
+    /// ```text
+    /// -1 0
+    /// d 1 2 0
+    /// 0
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut steps = vec![];
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+            let (delete, literals) = match line.strip_prefix('d') {
+                Some(literals) => (true, literals),
+                None => (false, line),
+            };
+            let mut clause = literals
+                .split_whitespace()
+                .map(|literal| literal.parse::<i32>())
+                .collect::<Result<Clause, _>>()?;
+            if clause.pop() != Some(0) || clause.contains(&0) {
+                return Err(format!("Proof step '{}' does not end in 0", line).into());
+            }
+            steps.push(if delete {
+                ProofStep::Delete(clause)
+            } else {
+                ProofStep::Add(clause)
+            });
+        }
+        Ok(Self { steps })
+    }
+
+    /// Checks that the proof shows `clauses` to be unsatisfiable.
+    ///
+    /// The lemmas are checked in order against the clauses and the earlier lemmas,
+    /// and the check succeeds when the empty clause is added.
+    /// Deletions of unit clauses are ignored, as in drat-trim.
+    ///
+    /// # Errors
+    /// Returns [`DratError`] at the first lemma that is neither RUP nor RAT,
+    /// or if the proof ends before the empty clause.
+    pub fn check(&self, clauses: &Clauses) -> Result<(), DratError> {
+        let variable_count = clauses
+            .iter()
+            .chain(self.steps.iter().map(|step| match step {
+                ProofStep::Add(clause) | ProofStep::Delete(clause) => clause,
+            }))
+            .flatten()
+            .map(|literal| literal.unsigned_abs() as usize)
+            .max()
+            .unwrap_or(0);
+
+        let mut database = Database::new(variable_count);
+        for clause in clauses {
+            database.add_clause(clause);
+        }
+
+        if database.propagates_to_conflict(&[]) {
+            return Ok(());
+        }
+
+        for (step_index, step) in self.steps.iter().enumerate() {
+            match step {
+                ProofStep::Add(lemma) => {
+                    if !database.is_rup(lemma) && !database.is_rat(lemma) {
+                        return Err(DratError::InvalidLemma(step_index));
+                    }
+                    if lemma.is_empty() {
+                        return Ok(());
+                    }
+                    database.add_clause(lemma);
+                }
+                ProofStep::Delete(clause) if clause.len() > 1 => database.delete_clause(clause),
+                ProofStep::Delete(_) => (),
+            }
+        }
+        Err(DratError::NoEmptyClause)
+    }
+}
+
+impl fmt::Display for DratProof {
+    /// Writes the proof in the textual DRAT format accepted by [`DratProof::parse`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            let (prefix, clause) = match step {
+                ProofStep::Add(clause) => ("", clause),
+                ProofStep::Delete(clause) => ("d ", clause),
+            };
+            let literals = clause.iter().map(|literal| literal.to_string());
+            writeln!(
+                f,
+                "{}{}",
+                prefix,
+                literals.chain(["0".to_string()]).join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Clauses of a formula and the lemmas of a proof, with two watched literals in each clause.
+///
+/// Each clause of at least two literals is watched by its first two literals,
+/// so unit propagation only visits the clauses whose watched literal becomes false.
+struct Database {
+    clauses: Vec<Option<Clause>>,
+    /// Indices of the clauses by their sorted literals.
+    indices: HashMap<Clause, Vec<usize>>,
+    /// Indices of the clauses that watch each literal, see [`literal_index`].
+    watches: Vec<Vec<usize>>,
+    /// Indices of the clauses of fewer than two literals, which are not watched.
+    units: Vec<usize>,
+    /// Value of each variable: 1 for true, -1 for false and 0 for unassigned.
+    ///
+    /// Every variable is unassigned between propagations.
+    values: Vec<i8>,
+}
+
+impl Database {
+    fn new(variable_count: usize) -> Self {
+        Self {
+            clauses: vec![],
+            indices: HashMap::new(),
+            watches: vec![vec![]; 2 * (variable_count + 1)],
+            units: vec![],
+            values: vec![0; variable_count + 1],
+        }
+    }
+
+    /// Adds `clause` to the end of the database.
+    fn add_clause(&mut self, clause: &[i32]) {
+        let index = self.clauses.len();
+        self.indices
+            .entry(clause.iter().copied().sorted().collect())
+            .or_default()
+            .push(index);
+        if clause.len() < 2 {
+            self.units.push(index);
+        } else {
+            self.watches[literal_index(clause[0])].push(index);
+            self.watches[literal_index(clause[1])].push(index);
+        }
+        self.clauses.push(Some(clause.to_vec()));
+    }
+
+    /// Deletes the latest clause with the literals of `clause`, if there is one.
+    ///
+    /// Watches of deleted clauses are removed when they are next visited.
+    fn delete_clause(&mut self, clause: &[i32]) {
+        let key: Clause = clause.iter().copied().sorted().collect();
+        if let Some(index) = self.indices.get_mut(&key).and_then(|indices| indices.pop()) {
+            self.clauses[index] = None;
+        }
+    }
+
+    /// Returns true if assigning the negations of `lemma` and propagating units leads to a conflict.
+    fn is_rup(&mut self, lemma: &[i32]) -> bool {
+        let negated = lemma.iter().map(|literal| -literal).collect_vec();
+        self.propagates_to_conflict(&negated)
+    }
+
+    /// Returns true if `lemma` is RUP after resolving with each clause that contains the negation of its first literal.
+    fn is_rat(&mut self, lemma: &[i32]) -> bool {
+        let pivot = match lemma.first() {
+            Some(&pivot) => pivot,
+            None => return false,
+        };
+        let resolvents = self
+            .clauses
+            .iter()
+            .flatten()
+            .filter(|clause| clause.contains(&-pivot))
+            .map(|clause| {
+                lemma
+                    .iter()
+                    .chain(clause.iter().filter(|&&literal| literal != -pivot))
+                    .copied()
+                    .collect_vec()
+            })
+            .collect_vec();
+        resolvents.iter().all(|resolvent| self.is_rup(resolvent))
+    }
+
+    /// Returns true if propagating units from the `assumptions` leads to a conflict.
+    ///
+    /// Contradicting assumptions are a conflict, i.e. tautological lemmas are RUP.
+    fn propagates_to_conflict(&mut self, assumptions: &[i32]) -> bool {
+        let mut trail = vec![];
+        let conflict = self.propagate(assumptions, &mut trail);
+        for literal in trail {
+            self.values[literal.unsigned_abs() as usize] = 0;
+        }
+        conflict
+    }
+
+    /// Assigns the `assumptions` and the unit clauses and propagates them, adding the assigned literals to `trail`.
+    fn propagate(&mut self, assumptions: &[i32], trail: &mut Vec<i32>) -> bool {
+        let units = self
+            .units
+            .iter()
+            .filter_map(|&index| self.clauses[index].as_ref())
+            .cloned()
+            .collect_vec();
+        for unit in units.iter().map(|clause| clause.first()) {
+            match unit {
+                // The empty clause is a conflict.
+                None => return true,
+                Some(&literal) if !assign(&mut self.values, trail, literal) => return true,
+                Some(_) => (),
+            }
+        }
+        for &literal in assumptions {
+            if !assign(&mut self.values, trail, literal) {
+                return true;
+            }
+        }
+
+        let mut head = 0;
+        while head < trail.len() {
+            let falsified = -trail[head];
+            head += 1;
+
+            let watching = std::mem::take(&mut self.watches[literal_index(falsified)]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = false;
+            for index in watching {
+                if conflict {
+                    kept.push(index);
+                    continue;
+                }
+                // Watches of deleted clauses and of clauses that watch other literals are dropped.
+                let clause = match &mut self.clauses[index] {
+                    Some(clause) => clause,
+                    None => continue,
+                };
+                // The falsified literal is moved to the second watch.
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+                if clause[1] != falsified {
+                    continue;
+                }
+                let other = clause[0];
+                if value(&self.values, other) == 1 {
+                    kept.push(index);
+                    continue;
+                }
+                let values = &self.values;
+                if let Some(position) =
+                    (2..clause.len()).find(|&position| value(values, clause[position]) != -1)
+                {
+                    clause.swap(1, position);
+                    self.watches[literal_index(clause[1])].push(index);
+                    continue;
+                }
+                kept.push(index);
+                if !assign(&mut self.values, trail, other) {
+                    conflict = true;
+                }
+            }
+            self.watches[literal_index(falsified)] = kept;
+            if conflict {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Returns the index of `literal` in the watch lists, `2 * variable` for positive and `2 * variable + 1` for negative literals.
+fn literal_index(literal: i32) -> usize {
+    2 * literal.unsigned_abs() as usize + (literal < 0) as usize
+}
+
+/// Returns the value of `literal`: 1 for true, -1 for false and 0 for unassigned.
+fn value(values: &[i8], literal: i32) -> i8 {
+    let value = values[literal.unsigned_abs() as usize];
+    if literal > 0 {
+        value
+    } else {
+        -value
+    }
+}
+
+/// Assigns `literal` true and adds it to `trail` if it is unassigned.
+///
+/// Returns false if the literal is already false, i.e. on a conflict.
+fn assign(values: &mut [i8], trail: &mut Vec<i32>, literal: i32) -> bool {
+    match value(values, literal) {
+        -1 => false,
+        0 => {
+            values[literal.unsigned_abs() as usize] = if literal > 0 { 1 } else { -1 };
+            trail.push(literal);
+            true
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All four clauses over two variables, which is unsatisfiable.
+    fn get_clauses() -> Clauses {
+        vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]]
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "c comment\n2 0\nd 1 2 0\n\n0\n";
+        let proof = DratProof::parse(text).unwrap();
+        assert_eq!(
+            proof.steps,
+            vec![
+                ProofStep::Add(vec![2]),
+                ProofStep::Delete(vec![1, 2]),
+                ProofStep::Add(vec![]),
+            ]
+        );
+        assert_eq!(proof.to_string(), "2 0\nd 1 2 0\n0\n");
+        assert!(DratProof::parse("1 2").is_err());
+    }
+
+    #[test]
+    fn test_check_rup() {
+        let proof = DratProof::parse("2 0\n0\n").unwrap();
+        assert_eq!(proof.check(&get_clauses()), Ok(()));
+
+        // The empty clause is not RUP before the lemma 2.
+        let proof = DratProof::parse("d 1 2 0\n2 0\n0\n").unwrap();
+        assert_eq!(proof.check(&get_clauses()), Err(DratError::InvalidLemma(1)));
+
+        let proof = DratProof::parse("2 0\n").unwrap();
+        assert_eq!(proof.check(&get_clauses()), Err(DratError::NoEmptyClause));
+        assert_eq!(
+            proof.check(&vec![vec![1, 2]]),
+            Err(DratError::NoEmptyClause)
+        );
+    }
+
+    #[test]
+    fn test_check_rat() {
+        // Lemma 3 is not RUP, but it is RAT because its resolvent 1 2 with -3 1 2 is RUP.
+        let mut clauses = get_clauses();
+        clauses.push(vec![-3, 1, 2]);
+        let proof = DratProof::parse("3 0\n2 0\n0\n").unwrap();
+        assert_eq!(proof.check(&clauses), Ok(()));
+
+        // Lemma 1 is neither, because its resolvent 1 -2 with -1 -2 is not RUP.
+        let proof = DratProof::parse("1 0\n").unwrap();
+        assert_eq!(
+            proof.check(&vec![vec![1, 2], vec![-1, -2]]),
+            Err(DratError::InvalidLemma(0))
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use super::SatResult;
+use std::error::Error;
+
+/// SAT solver that keeps its clauses between solves and solves them under assumptions.
+///
+/// Clauses that are learned in one solve can be reused in the next ones,
+/// which makes solving many similar problems faster than solving each from scratch,
+/// see [`crate::sat_encoder::selectors::SelectorEncoder`].
+pub trait IncrementalSatSolver {
+    /// Adds `clause` permanently.
+    fn add_clause(&mut self, clause: &[i32]);
+
+    /// Solves the clauses with the literals of `assumptions` true only for this solve.
+    ///
+    /// # Errors
+    /// Returns an error if the solver fails.
+    fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<SatResult, Box<dyn Error>>;
+}
+
+/// Returns an incremental solver, which is [`VarisatSolver`].
+///
+/// Requires the `varisat` feature.
+#[cfg(feature = "varisat")]
+pub fn new_incremental_solver() -> Box<dyn IncrementalSatSolver> {
+    Box::new(VarisatSolver::new())
+}
+
+/// Varisat SAT solver, which supports assumptions natively.
+///
+/// Requires the `varisat` feature.
+/// Limits are not supported, as Varisat cannot be stopped.
+#[cfg(feature = "varisat")]
+pub struct VarisatSolver {
+    solver: varisat::Solver<'static>,
+    variable_count: i32,
+}
+
+#[cfg(feature = "varisat")]
+impl VarisatSolver {
+    pub fn new() -> Self {
+        Self {
+            solver: varisat::Solver::new(),
+            variable_count: 0,
+        }
+    }
+}
+
+#[cfg(feature = "varisat")]
+impl Default for VarisatSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "varisat")]
+impl IncrementalSatSolver for VarisatSolver {
+    fn add_clause(&mut self, clause: &[i32]) {
+        use varisat::{ExtendFormula, Lit};
+
+        let literals: Vec<Lit> = clause
+            .iter()
+            .map(|&literal| Lit::from_dimacs(literal as isize))
+            .collect();
+        self.solver.add_clause(&literals);
+        self.variable_count = clause.iter().fold(self.variable_count, |count, literal| {
+            count.max(literal.abs())
+        });
+    }
+
+    /// Variables that do not appear in the model of Varisat are false.
+    fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<SatResult, Box<dyn Error>> {
+        use varisat::Lit;
+
+        // Assumptions of Varisat stay until they are replaced, so they are always set.
+        let assumptions: Vec<Lit> = assumptions
+            .iter()
+            .map(|&literal| Lit::from_dimacs(literal as isize))
+            .collect();
+        self.solver.assume(&assumptions);
+        let satisfiable = self
+            .solver
+            .solve()
+            .map_err(|error| format!("Varisat failed: {:?}", error))?;
+        if !satisfiable {
+            return Ok(SatResult::Unsatisfiable);
+        }
+
+        let mut values = vec![false; self.variable_count as usize + 1];
+        for literal in self.solver.model().unwrap_or_default() {
+            let literal = literal.to_dimacs();
+            if literal > 0 && literal <= self.variable_count as isize {
+                values[literal as usize] = true;
+            }
+        }
+        let model = (1..=self.variable_count)
+            .map(|variable| {
+                if values[variable as usize] {
+                    variable
+                } else {
+                    -variable
+                }
+            })
+            .collect();
+        Ok(SatResult::Satisfiable(model))
+    }
+}
+
+#[cfg(all(test, feature = "varisat"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_with_assumptions() {
+        let mut solver = new_incremental_solver();
+        solver.add_clause(&[1, 2]);
+        solver.add_clause(&[-1, 3]);
+
+        let result = solver.solve_with_assumptions(&[-2]).unwrap();
+        assert_eq!(result, SatResult::Satisfiable(vec![1, -2, 3]));
+        let result = solver.solve_with_assumptions(&[-2, -3]).unwrap();
+        assert_eq!(result, SatResult::Unsatisfiable);
+        // Assumptions do not stay after a solve.
+        let result = solver.solve_with_assumptions(&[-3]).unwrap();
+        assert_eq!(result, SatResult::Satisfiable(vec![-1, 2, -3]));
+        let result = solver.solve_with_assumptions(&[]).unwrap();
+        assert!(result.is_satisfiable());
+    }
+}
@@ -1,34 +1,219 @@
 use crate::sat_encoder::Clauses;
-use kissat_rs;
+use backend::{get_default_backend, SatBackend};
+use drat::DratProof;
+use itertools::Itertools;
+use std::error::Error;
+use std::fs;
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+pub mod backend;
+pub mod drat;
+pub mod incremental;
+
+/// Name of the Kissat executable that is used for proofs. It has to be in `PATH`.
+const KISSAT_EXECUTABLE: &str = "kissat";
+
+/// Count of files written for the Kissat executable, used for unique file names.
+static KISSAT_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Backend set with [`SatSolver::set_backend`], or `None` for the default backend.
+static BACKEND: RwLock<Option<Arc<dyn SatBackend>>> = RwLock::new(None);
+
+/// Satisfying assignment of a SAT problem.
+///
+/// Contains a literal of each variable in increasing order of variable,
+/// which is positive if the variable is true and negative if it is false,
+/// e.g. `[1, -2, 3]`.
+pub type Model = Vec<i32>;
+
 /// Enumerator for SAT solver's result.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SatResult {
-    Satisfiable,
+    /// The problem is satisfiable with the model.
+    Satisfiable(Model),
     Unsatisfiable,
+    /// The solver reached a limit before it could decide the problem, see [`SatLimits`].
+    Unknown,
+}
+
+impl SatResult {
+    /// Returns true if the result is [`SatResult::Satisfiable`].
+    pub fn is_satisfiable(&self) -> bool {
+        matches!(self, SatResult::Satisfiable(_))
+    }
+
+    /// Returns the model of a satisfiable result.
+    pub fn get_model(&self) -> Option<&Model> {
+        match self {
+            SatResult::Satisfiable(model) => Some(model),
+            SatResult::Unsatisfiable | SatResult::Unknown => None,
+        }
+    }
+
+    /// Returns true if the result is [`SatResult::Unknown`].
+    pub fn is_unknown(&self) -> bool {
+        *self == SatResult::Unknown
+    }
+}
+
+/// Limits for solving a single SAT problem, see [`SatSolver::solve_with_limits`].
+///
+/// A solver that reaches a limit gives up with [`SatResult::Unknown`].
+/// Limits are enforced by running the solver as an executable that is killed at the time limit,
+/// and that gets the limits on conflicts and propagations as arguments, see [`backend::ExternalBackend`].
+/// The Kissat backend runs the Kissat executable whenever a limit is set,
+/// see [`backend::get_kissat_executable_backend`].
+///
+/// Backends that solve in this process, such as Varisat, support only the time limit and only on a best-effort basis:
+/// the result is Unknown at the limit, but the solver keeps running in the background until it finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SatLimits {
+    /// Largest count of conflicts.
+    pub conflicts: Option<u64>,
+    /// Largest count of propagations.
+    pub propagations: Option<u64>,
+    /// Largest wall-clock time.
+    pub time: Option<Duration>,
+}
+
+impl SatLimits {
+    /// Returns true if no limit is set.
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// SAT problem solver.
 ///
-/// The solution from the solver is either [`SatResult::Satisfiable`] or [`SatResult::Unsatisfiable`].
+/// The solution from the solver is either [`SatResult::Satisfiable`] or [`SatResult::Unsatisfiable`],
+/// or [`SatResult::Unknown`] if the solver reaches a limit.
 ///
 /// More about SAT [here](https://en.wikipedia.org/wiki/Boolean_satisfiability_problem).
 pub struct SatSolver {}
 
 impl SatSolver {
-    /// Solves SAT problem using Kissat SAT solver.
+    /// Solves SAT problem using the selected backend, see [`SatSolver::set_backend`].
     ///
     /// Returns enumerator [`SatResult`] stating the solver's result.
+    ///
+    /// # Panics
+    /// Panics if the backend fails.
     pub fn solve(clauses: Clauses) -> SatResult {
-        let unsat_result = kissat_rs::Solver::decide_formula(clauses).unwrap();
-        match unsat_result {
-            true => SatResult::Satisfiable,
-            false => SatResult::Unsatisfiable,
-        }
+        Self::solve_with_limits(clauses, &SatLimits::default())
+    }
+
+    /// Solves SAT problem like [`SatSolver::solve`], but gives up with [`SatResult::Unknown`] at the `limits`.
+    ///
+    /// # Panics
+    /// Panics if the backend fails.
+    pub fn solve_with_limits(clauses: Clauses, limits: &SatLimits) -> SatResult {
+        Self::solve_with_backend(clauses, limits, Self::get_backend().as_ref())
+    }
+
+    /// Solves SAT problem like [`SatSolver::solve_with_limits`], but with `backend` instead of the selected backend.
+    ///
+    /// # Panics
+    /// Panics if the backend fails.
+    pub fn solve_with_backend(
+        clauses: Clauses,
+        limits: &SatLimits,
+        backend: &dyn SatBackend,
+    ) -> SatResult {
+        backend
+            .solve(clauses, limits)
+            .unwrap_or_else(|error| panic!("SAT solver {} failed: {}", backend.get_name(), error))
+    }
+
+    /// Selects the backend that is used by [`SatSolver::solve`] in the whole process.
+    ///
+    /// By default Kissat is used, see [`backend::get_default_backend`].
+    /// This is meant for applications such as the command line tool, which select the backend once.
+    /// A backend can be given to a single [`crate::Classifier`] with [`crate::Classifier::backend`] instead.
+    pub fn set_backend(backend: Box<dyn SatBackend>) {
+        *BACKEND.write().unwrap() = Some(Arc::from(backend));
+    }
+
+    /// Returns the backend that is used by [`SatSolver::solve`].
+    pub fn get_backend() -> Arc<dyn SatBackend> {
+        BACKEND
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| Arc::from(get_default_backend()))
+    }
+
+    /// Solves SAT problem using the Kissat executable, and returns a DRAT proof if it is unsatisfiable.
+    ///
+    /// The clauses and the proof are passed through temporary files.
+    /// The proof can be checked with [`DratProof::check`].
+    ///
+    /// Returns `None` if the problem is satisfiable.
+    ///
+    /// # Errors
+    /// Returns an error if the executable cannot be run or its output cannot be read.
+    pub fn prove_unsatisfiable(clauses: &Clauses) -> Result<Option<DratProof>, Box<dyn Error>> {
+        let name = format!(
+            "nonconstant-lcl-classifier-{}-{}",
+            process::id(),
+            KISSAT_FILE_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let cnf_path = std::env::temp_dir().join(format!("{}.cnf", name));
+        let proof_path = std::env::temp_dir().join(format!("{}.drat", name));
+
+        let prove = || -> Result<Option<DratProof>, Box<dyn Error>> {
+            fs::write(&cnf_path, clauses_to_dimacs(clauses))?;
+            let status = Command::new(KISSAT_EXECUTABLE)
+                .arg("-q")
+                .arg("--no-binary")
+                .arg(&cnf_path)
+                .arg(&proof_path)
+                .status()?;
+            // Kissat exits with 10 if the problem is satisfiable and 20 if it is unsatisfiable.
+            match status.code() {
+                Some(10) => Ok(None),
+                Some(20) => Ok(Some(DratProof::parse(&fs::read_to_string(&proof_path)?)?)),
+                code => Err(format!("Kissat exited with code {:?}", code).into()),
+            }
+        };
+        let result = prove();
+        let _ = fs::remove_file(&cnf_path);
+        let _ = fs::remove_file(&proof_path);
+        result
     }
 }
 
+/// Returns the count of variables in `clauses`, i.e. the largest variable.
+pub fn get_variable_count(clauses: &Clauses) -> usize {
+    clauses
+        .iter()
+        .flatten()
+        .map(|literal| literal.unsigned_abs() as usize)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns `clauses` in the CNF DIMACS format.
+///
+/// The count of variables is the largest variable in the clauses.
+pub fn clauses_to_dimacs(clauses: &Clauses) -> String {
+    clauses_to_dimacs_with_variable_count(clauses, get_variable_count(clauses))
+}
+
+/// Returns `clauses` in the CNF DIMACS format with `variable_count` variables in the header.
+pub fn clauses_to_dimacs_with_variable_count(clauses: &Clauses, variable_count: usize) -> String {
+    let mut result = format!("p cnf {} {}\n", variable_count, clauses.len());
+    for clause in clauses {
+        result.push_str(&format!("{} 0\n", clause.iter().join(" ")));
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::{SatResult, SatSolver};
 
     #[test]
@@ -36,7 +221,54 @@ mod tests {
         // Simple CNF satisfiability problem that is satisfiable.
         let clauses = vec![vec![1, -2, 3, 4]];
         let result = SatSolver::solve(clauses);
-        assert_eq!(result, SatResult::Satisfiable);
+        assert!(result.is_satisfiable());
+    }
+
+    #[test]
+    fn test_clauses_to_dimacs() {
+        let clauses = vec![vec![1, -3], vec![2]];
+        assert_eq!(clauses_to_dimacs(&clauses), "p cnf 3 2\n1 -3 0\n2 0\n");
+        assert_eq!(
+            clauses_to_dimacs_with_variable_count(&clauses, 4),
+            "p cnf 4 2\n1 -3 0\n2 0\n"
+        );
+    }
+
+    #[test]
+    #[ignore = "Requires the kissat executable"]
+    fn test_prove_unsatisfiable() {
+        let clauses = vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]];
+        let proof = SatSolver::prove_unsatisfiable(&clauses).unwrap().unwrap();
+        assert_eq!(proof.check(&clauses), Ok(()));
+
+        assert_eq!(
+            SatSolver::prove_unsatisfiable(&vec![vec![1]]).unwrap(),
+            None
+        );
+
+        // Pigeonhole problem of 7 pigeons and 6 holes, whose proof has many lemmas.
+        let (pigeons, holes) = (7, 6);
+        let variable = |pigeon: i32, hole: i32| pigeon * holes + hole + 1;
+        let mut clauses: Clauses = (0..pigeons)
+            .map(|pigeon| (0..holes).map(|hole| variable(pigeon, hole)).collect())
+            .collect();
+        for hole in 0..holes {
+            for pigeon in 0..pigeons {
+                for other in pigeon + 1..pigeons {
+                    clauses.push(vec![-variable(pigeon, hole), -variable(other, hole)]);
+                }
+            }
+        }
+        let proof = SatSolver::prove_unsatisfiable(&clauses).unwrap().unwrap();
+        assert_eq!(proof.check(&clauses), Ok(()));
+    }
+
+    #[test]
+    fn test_solver_returns_model() {
+        let clauses = vec![vec![1, 2], vec![-1], vec![-2, 3]];
+        let result = SatSolver::solve(clauses);
+        assert_eq!(result, SatResult::Satisfiable(vec![-1, 2, 3]));
+        assert_eq!(result.get_model(), Some(&vec![-1, 2, 3]));
     }
 
     #[test]
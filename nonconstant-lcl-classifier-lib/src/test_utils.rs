@@ -0,0 +1,11 @@
+//! Fixtures that are shared by the tests of several modules.
+
+use crate::LclProblem;
+
+/// Problem where each active node has different labels and each passive node equal labels on its edges.
+///
+/// On a cycle, see [`crate::BiregularGraph::cycle`], the problem is solvable
+/// if and only if the count of active nodes is even.
+pub fn get_parity_problem() -> LclProblem {
+    LclProblem::new("AB", "AA BB").unwrap()
+}